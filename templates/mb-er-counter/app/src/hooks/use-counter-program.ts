@@ -27,7 +27,7 @@ export type DelegationStatus = "undelegated" | "delegated" | "checking";
  * Provides real-time updates via WebSocket subscriptions.
  * Supports MagicBlock Ephemeral Rollups for delegation, commit, and undelegation.
  */
-export function useCounterProgram() {
+export function useCounterProgram(counterId: number = 0) {
     const { connection } = useConnection();
     const wallet = useWallet();
 
@@ -110,14 +110,14 @@ export function useCounterProgram() {
         return await sdkCreateSession(new PublicKey(IDL.address));
     }, [sdkCreateSession]);
 
-    // Derive PDA from wallet public key
+    // Derive PDA from wallet public key and counter id
     const derivePDA = useCallback((authority: PublicKey) => {
         const [pda] = PublicKey.findProgramAddressSync(
-            [authority.toBuffer()],
+            [authority.toBuffer(), new BN(counterId).toArrayLike(Buffer, "le", 8)],
             new PublicKey(IDL.address)
         );
         return pda;
-    }, []);
+    }, [counterId]);
 
     // Auto-derive counter PDA when wallet connects
     useEffect(() => {
@@ -261,6 +261,7 @@ export function useCounterProgram() {
     }, [erProgram, counterPubkey, erConnection, delegationStatus]);
 
     // Initialize a new counter (uses PDA derived from wallet)
+    // Bounded to 0..=1000 and wrapping, like the original demo counter
     const initialize = useCallback(async (): Promise<string> => {
         if (!program || !wallet.publicKey) {
             throw new Error("Wallet not connected");
@@ -271,9 +272,10 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .initialize()
+                .initialize(new BN(counterId), new BN(0), new BN(1000), { wrap: {} }, null)
                 .accounts({
                     authority: wallet.publicKey,
+                    multisig: null,
                 })
                 .rpc();
 
@@ -287,7 +289,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, fetchCounterAccount]);
+    }, [program, wallet.publicKey, fetchCounterAccount, counterId]);
 
     // Increment the counter (on base layer)
     const increment = useCallback(async (): Promise<string> => {
@@ -300,11 +302,17 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .increment()
+                .increment(new BN(counterId))
                 .accounts({
                     counter: counterPubkey,
                     signer: wallet.publicKey,
                     sessionToken: null,
+                    history: null,
+                    multisig: null,
+                    sessionUsage: null,
+                    contribution: null,
+                    treasury: null,
+                    feeEscrow: null,
                 } as any)
                 .rpc();
 
@@ -316,7 +324,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     const performErAction = useCallback(async (
         methodBuilder: any,
@@ -339,6 +347,12 @@ export function useCounterProgram() {
                 counter: counterPubkey,
                 signer: signer,
                 sessionToken: hasSession ? sessionToken : null,
+                history: null,
+                multisig: null,
+                sessionUsage: null,
+                contribution: null,
+                treasury: null,
+                feeEscrow: null,
             };
 
             // Build transaction using base program structure but targeted at ER accounts
@@ -394,20 +408,32 @@ export function useCounterProgram() {
     // Increment the counter on Ephemeral Rollup
     const incrementOnER = useCallback(async (): Promise<string> => {
         if (!program) throw new Error("Program not loaded");
-        return performErAction(program.methods.increment(), "increment");
-    }, [program, performErAction]);
+        return performErAction(program.methods.increment(new BN(counterId)), "increment");
+    }, [program, performErAction, counterId]);
 
     // Decrement the counter on Ephemeral Rollup
     const decrementOnER = useCallback(async (): Promise<string> => {
         if (!program) throw new Error("Program not loaded");
-        return performErAction(program.methods.decrement(), "decrement");
-    }, [program, performErAction]);
+        return performErAction(program.methods.decrement(new BN(counterId)), "decrement");
+    }, [program, performErAction, counterId]);
+
+    // Increment the counter by an amount on Ephemeral Rollup (one transaction instead of many)
+    const incrementByOnER = useCallback(async (amount: number): Promise<string> => {
+        if (!program) throw new Error("Program not loaded");
+        return performErAction(program.methods.incrementBy(new BN(counterId), new BN(amount)), "increment");
+    }, [program, performErAction, counterId]);
+
+    // Decrement the counter by an amount on Ephemeral Rollup
+    const decrementByOnER = useCallback(async (amount: number): Promise<string> => {
+        if (!program) throw new Error("Program not loaded");
+        return performErAction(program.methods.decrementBy(new BN(counterId), new BN(amount)), "decrement");
+    }, [program, performErAction, counterId]);
 
     // Set the counter to a specific value on Ephemeral Rollup
     const setOnER = useCallback(async (value: number): Promise<string> => {
         if (!program) throw new Error("Program not loaded");
-        return performErAction(program.methods.set(new BN(value)), "set");
-    }, [program, performErAction]);
+        return performErAction(program.methods.set(new BN(counterId), new BN(value)), "set");
+    }, [program, performErAction, counterId]);
 
     // Decrement the counter (on base layer)
     const decrement = useCallback(async (): Promise<string> => {
@@ -420,11 +446,17 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .decrement()
+                .decrement(new BN(counterId))
                 .accounts({
                     counter: counterPubkey,
                     signer: wallet.publicKey,
                     sessionToken: null,
+                    history: null,
+                    multisig: null,
+                    sessionUsage: null,
+                    contribution: null,
+                    treasury: null,
+                    feeEscrow: null,
                 } as any)
                 .rpc();
 
@@ -436,7 +468,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     // Set the counter to a specific value (on base layer)
     const set = useCallback(async (value: number): Promise<string> => {
@@ -449,11 +481,17 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .set(new BN(value))
+                .set(new BN(counterId), new BN(value))
                 .accounts({
                     counter: counterPubkey,
                     signer: wallet.publicKey,
                     sessionToken: null,
+                    history: null,
+                    multisig: null,
+                    sessionUsage: null,
+                    contribution: null,
+                    treasury: null,
+                    feeEscrow: null,
                 } as any)
                 .rpc();
 
@@ -465,7 +503,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     // ========================================
     // Ephemeral Rollups Functions
@@ -473,7 +511,7 @@ export function useCounterProgram() {
 
     // Delegate the counter to Ephemeral Rollups
     const delegate = useCallback(async (): Promise<string> => {
-        if (!program || !wallet.publicKey) {
+        if (!program || !wallet.publicKey || !counterPubkey) {
             throw new Error("Wallet not connected");
         }
 
@@ -483,9 +521,11 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .delegate()
+                .delegate(new BN(counterId))
                 .accounts({
                     payer: wallet.publicKey,
+                    pda: counterPubkey,
+                    multisig: null,
                 })
                 .rpc({
                     skipPreflight: true,
@@ -506,7 +546,7 @@ export function useCounterProgram() {
             setIsLoading(false);
             setIsDelegating(false);
         }
-    }, [program, wallet.publicKey, checkDelegationStatus]);
+    }, [program, wallet.publicKey, counterPubkey, checkDelegationStatus, counterId]);
 
     // Commit state from ER to base layer (runs on ER)
     const commit = useCallback(async (): Promise<string> => {
@@ -520,9 +560,14 @@ export function useCounterProgram() {
         try {
             // Build transaction using base program
             let tx = await program.methods
-                .commit()
-                .accounts({
+                .commit(new BN(counterId))
+                .accountsPartial({
                     payer: wallet.publicKey,
+                    counter: counterPubkey,
+                    history: null,
+                    multisig: null,
+                    sessionUsage: null,
+                    treasury: null,
                 })
                 .transaction();
 
@@ -558,7 +603,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, erProvider, erConnection, wallet.publicKey, counterPubkey, fetchCounterAccount]);
+    }, [program, erProvider, erConnection, wallet.publicKey, counterPubkey, fetchCounterAccount, counterId]);
 
     // Undelegate the counter from ER (runs on ER)
     const undelegate = useCallback(async (): Promise<string> => {
@@ -572,9 +617,14 @@ export function useCounterProgram() {
         try {
             // Build transaction using base program
             let tx = await program.methods
-                .undelegate()
-                .accounts({
+                .undelegate(new BN(counterId))
+                .accountsPartial({
                     payer: wallet.publicKey,
+                    counter: counterPubkey,
+                    history: null,
+                    multisig: null,
+                    sessionUsage: null,
+                    treasury: null,
                 })
                 .transaction();
 
@@ -607,7 +657,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, erProvider, erConnection, wallet.publicKey, counterPubkey, fetchCounterAccount]);
+    }, [program, erProvider, erConnection, wallet.publicKey, counterPubkey, fetchCounterAccount, counterId]);
 
     return {
         program,
@@ -627,6 +677,8 @@ export function useCounterProgram() {
         undelegate,
         incrementOnER,
         decrementOnER,
+        incrementByOnER,
+        decrementByOnER,
         setOnER,
         // Delegation status
         delegationStatus,
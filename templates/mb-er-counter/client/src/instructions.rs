@@ -0,0 +1,1722 @@
+//! Builders for every instruction of the program
+//! PDAs are derived from the counter's key, so callers only pass the signers and
+//! the accounts that can't be derived
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_lang::InstructionData;
+use counter::{accounts, instruction};
+use counter::{
+    MultisigAction, MultisigConfig, OverflowPolicy, RateLimit, ResetPeriod, SessionScope,
+};
+use ephemeral_rollups_sdk::consts::{MAGIC_CONTEXT_ID, MAGIC_PROGRAM_ID};
+
+use crate::{pda, CounterKey};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: counter::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Optional accounts of the update instructions (increment, decrement, set, ...),
+/// none of them passed by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateAccounts {
+    /// Session token the signer acts through for the authority
+    pub session_token: Option<Pubkey>,
+    /// Leave out the program config, for deployments where init_config hasn't run
+    pub without_config: bool,
+    /// Append the change to the counter's history
+    pub history: bool,
+    /// Pass the counter's multisig, needed when the signer is one of its members
+    pub multisig: bool,
+    /// Pass the signer's session usage, needed while the session scope caps uses
+    pub session_usage: bool,
+    /// Pass the signer's contribution, needed to increment a public counter
+    pub contribution: bool,
+    /// Pass the counter's treasury, needed while it charges a fee on the base layer
+    pub treasury: bool,
+    /// Pass the signer's fee escrow, which pays the fee instead of the treasury on the ER
+    pub fee_escrow: bool,
+    /// The signer's token account of the gate mint, needed while the counter is gated
+    pub gate_token_account: Option<Pubkey>,
+}
+
+fn update(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> accounts::Update {
+    let address = counter.address();
+    let passed = |pass: bool, (key, _): (Pubkey, u8)| pass.then_some(key);
+    accounts::Update {
+        counter: address,
+        signer,
+        session_token: options.session_token,
+        config: passed(!options.without_config, pda::config()),
+        history: passed(options.history, pda::history(&address)),
+        multisig: passed(options.multisig, pda::multisig(&address)),
+        session_usage: passed(options.session_usage, pda::session_usage(&address, &signer)),
+        contribution: passed(options.contribution, pda::contribution(&address, &signer)),
+        treasury: passed(options.treasury, pda::treasury(&address)),
+        fee_escrow: passed(options.fee_escrow, pda::fee_escrow(&address, &signer)),
+        gate_token_account: options.gate_token_account,
+        system_program: system_program::ID,
+    }
+}
+
+/// Create counter `counter_id` of `authority`, optionally held by a new multisig
+pub fn initialize(
+    authority: Pubkey,
+    counter_id: u64,
+    min: u64,
+    max: u64,
+    overflow_policy: OverflowPolicy,
+    multisig: Option<MultisigConfig>,
+) -> Instruction {
+    let counter = pda::counter(&authority, counter_id).0;
+    build(
+        accounts::Initialize {
+            counter,
+            authority,
+            multisig: multisig.as_ref().map(|_| pda::multisig(&counter).0),
+            system_program: system_program::ID,
+        },
+        instruction::Initialize {
+            counter_id,
+            min,
+            max,
+            overflow_policy,
+            multisig,
+        },
+    )
+}
+
+pub fn increment(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Increment {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn decrement(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Decrement {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn increment_by(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::IncrementBy {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+pub fn decrement_by(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::DecrementBy {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+pub fn set(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    value: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Set {
+            counter_id: counter.counter_id,
+            value,
+        },
+    )
+}
+
+pub fn compare_and_set(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    expected: u64,
+    new: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::CompareAndSet {
+            counter_id: counter.counter_id,
+            expected,
+            new,
+        },
+    )
+}
+
+pub fn schedule_set(counter: CounterKey, authority: Pubkey, value: u64, delay: u64) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::ScheduleSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::ScheduleSet {
+            counter_id: counter.counter_id,
+            value,
+            delay,
+        },
+    )
+}
+
+/// Execute the scheduled set, refunding its rent to `authority`, who scheduled it
+/// Of `options`, only the config, history and treasury apply
+pub fn execute_set(
+    counter: CounterKey,
+    authority: Pubkey,
+    executor: Pubkey,
+    options: &UpdateAccounts,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::ExecuteSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+            config: (!options.without_config).then(|| pda::config().0),
+            executor,
+            history: options.history.then(|| pda::history(&address).0),
+            treasury: options.treasury.then(|| pda::treasury(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteSet {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn cancel_set(counter: CounterKey, authority: Pubkey) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::CancelSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+        },
+        instruction::CancelSet {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_history(counter: CounterKey, authority: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitHistory {
+            counter: address,
+            history: pda::history(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitHistory {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn init_session_usage(
+    counter: CounterKey,
+    payer: Pubkey,
+    session_signer: Pubkey,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitSessionUsage {
+            counter: address,
+            session_usage: pda::session_usage(&address, &session_signer).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitSessionUsage {
+            counter_id: counter.counter_id,
+            session_signer,
+        },
+    )
+}
+
+/// Accounts closed along with the counter, besides its history, treasury and pending
+/// set, which are always passed
+#[derive(Clone, Debug, Default)]
+pub struct CloseAccounts {
+    /// Session signers whose usage accounts were created
+    pub session_signers: Vec<Pubkey>,
+    /// Contributors whose contributions were created, refunded their rent
+    pub contributors: Vec<Pubkey>,
+    /// Payers whose fee escrows were created, refunded what is left of their deposit
+    pub fee_payers: Vec<Pubkey>,
+}
+
+/// Close the counter along with `options`, returning their rent to `recipient`
+pub fn close(
+    counter: CounterKey,
+    authority: Pubkey,
+    recipient: Pubkey,
+    options: &CloseAccounts,
+) -> Instruction {
+    let address = counter.address();
+    let mut ix = build(
+        accounts::Close {
+            counter: address,
+            authority,
+            recipient,
+            history: pda::history(&address).0,
+            treasury: pda::treasury(&address).0,
+            pending_set: pda::pending_set(&address).0,
+            multisig: pda::multisig(&address).0,
+        },
+        instruction::Close {
+            counter_id: counter.counter_id,
+        },
+    );
+    ix.accounts.extend(
+        options
+            .session_signers
+            .iter()
+            .map(|signer| AccountMeta::new(pda::session_usage(&address, signer).0, false)),
+    );
+    for contributor in &options.contributors {
+        ix.accounts.extend([
+            AccountMeta::new(pda::contribution(&address, contributor).0, false),
+            AccountMeta::new(*contributor, false),
+        ]);
+    }
+    for payer in &options.fee_payers {
+        ix.accounts.extend([
+            AccountMeta::new(pda::fee_escrow(&address, payer).0, false),
+            AccountMeta::new(*payer, false),
+        ]);
+    }
+    ix
+}
+
+pub fn migrate(counter: CounterKey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::Migrate {
+            counter: counter.address(),
+            relocated: None,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Move a counter still in the original layout to the address of `counter_id`
+pub fn migrate_baseline(authority: Pubkey, counter_id: u64) -> Instruction {
+    build(
+        accounts::Migrate {
+            counter: pda::baseline_counter(&authority).0,
+            relocated: Some(pda::counter(&authority, counter_id).0),
+            payer: authority,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate { counter_id },
+    )
+}
+
+pub fn propose_authority(
+    counter: CounterKey,
+    authority: Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ProposeAuthority {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::ProposeAuthority {
+            counter_id: counter.counter_id,
+            new_authority,
+        },
+    )
+}
+
+pub fn accept_authority(counter: CounterKey, new_authority: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAuthority {
+            counter: counter.address(),
+            new_authority,
+        },
+        instruction::AcceptAuthority {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn create_proposal(
+    counter: CounterKey,
+    proposer: Pubkey,
+    index: u64,
+    action: MultisigAction,
+) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    build(
+        accounts::CreateProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            proposer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateProposal {
+            counter_id: counter.counter_id,
+            action,
+        },
+    )
+}
+
+pub fn approve_proposal(counter: CounterKey, member: Pubkey, index: u64) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    build(
+        accounts::ApproveProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            member,
+        },
+        instruction::ApproveProposal {
+            counter_id: counter.counter_id,
+            index,
+        },
+    )
+}
+
+/// Execute approved proposal `index`, refunding its rent to `proposer`
+/// The `action` decides the accounts passed along: the counter's treasury and the
+/// recipient for withdrawals, its pending set for scheduled sets, and the recipient
+/// followed by the accounts closed along with the counter for closing it
+/// `options` applies to count changes as to execute_set
+pub fn execute_proposal(
+    counter: CounterKey,
+    proposer: Pubkey,
+    executor: Pubkey,
+    index: u64,
+    action: &MultisigAction,
+    options: &UpdateAccounts,
+) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    let (recipient, treasury) = match *action {
+        MultisigAction::Withdraw { recipient, .. } => (Some(recipient), true),
+        MultisigAction::Close { recipient } => (Some(recipient), false),
+        _ => (None, false),
+    };
+    let pending_set = matches!(
+        action,
+        MultisigAction::ScheduleSet { .. } | MultisigAction::CancelSet
+    );
+    let mut ix = build(
+        accounts::ExecuteProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            proposer,
+            config: (!options.without_config).then(|| pda::config().0),
+            executor,
+            history: options.history.then(|| pda::history(&address).0),
+            treasury: (options.treasury || treasury).then(|| pda::treasury(&address).0),
+            pending_set: pending_set.then(|| pda::pending_set(&address).0),
+            recipient,
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteProposal {
+            counter_id: counter.counter_id,
+            index,
+        },
+    );
+    if let MultisigAction::Close { .. } = action {
+        ix.accounts.extend(
+            [
+                pda::history(&address).0,
+                pda::treasury(&address).0,
+                pda::pending_set(&address).0,
+            ]
+            .map(|address| AccountMeta::new(address, false)),
+        );
+    }
+    ix
+}
+
+pub fn set_frozen(counter: CounterKey, authority: Pubkey, frozen: bool) -> Instruction {
+    build(
+        accounts::SetFrozen {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetFrozen {
+            counter_id: counter.counter_id,
+            frozen,
+        },
+    )
+}
+
+pub fn set_rate_limit(
+    counter: CounterKey,
+    authority: Pubkey,
+    rate_limit: RateLimit,
+) -> Instruction {
+    build(
+        accounts::SetRateLimit {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetRateLimit {
+            counter_id: counter.counter_id,
+            rate_limit,
+        },
+    )
+}
+
+pub fn set_reset_period(
+    counter: CounterKey,
+    authority: Pubkey,
+    reset_period: ResetPeriod,
+) -> Instruction {
+    build(
+        accounts::SetResetPeriod {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetResetPeriod {
+            counter_id: counter.counter_id,
+            reset_period,
+        },
+    )
+}
+
+pub fn set_public(counter: CounterKey, authority: Pubkey, public: bool) -> Instruction {
+    build(
+        accounts::SetPublic {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetPublic {
+            counter_id: counter.counter_id,
+            public,
+        },
+    )
+}
+
+pub fn set_fee(counter: CounterKey, authority: Pubkey, fee: u64) -> Instruction {
+    build(
+        accounts::SetFee {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetFee {
+            counter_id: counter.counter_id,
+            fee,
+        },
+    )
+}
+
+pub fn set_token_gate(
+    counter: CounterKey,
+    authority: Pubkey,
+    gate_mint: Option<Pubkey>,
+    min_balance: u64,
+) -> Instruction {
+    build(
+        accounts::SetTokenGate {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetTokenGate {
+            counter_id: counter.counter_id,
+            gate_mint,
+            min_balance,
+        },
+    )
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_reward_mint(
+    counter: CounterKey,
+    authority: Pubkey,
+    multisig: bool,
+    decimals: u8,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitRewardMint {
+            counter: address,
+            reward_mint: pda::reward_mint(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            token_program: anchor_spl::token_2022::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitRewardMint {
+            counter_id: counter.counter_id,
+            decimals,
+        },
+    )
+}
+
+pub fn set_reward(counter: CounterKey, authority: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::SetReward {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetReward {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+/// Mint the rewards pending in `contributor`'s contribution, or in the counter
+/// without one, to `recipient`, a Token-2022 account of the contributor or authority,
+/// once they are back on the base layer
+pub fn mint_rewards(
+    counter: CounterKey,
+    contributor: Option<Pubkey>,
+    recipient: Pubkey,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::MintRewards {
+            counter: address,
+            reward_mint: pda::reward_mint(&address).0,
+            contribution: contributor
+                .map(|contributor| pda::contribution(&address, &contributor).0),
+            recipient,
+            token_program: anchor_spl::token_2022::ID,
+        },
+        instruction::MintRewards {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Pass `multisig` when the contributor is a member of the multisig holding the
+/// authority, to accrue rewards on a counter that isn't public
+pub fn init_contribution(counter: CounterKey, contributor: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitContribution {
+            counter: address,
+            contribution: pda::contribution(&address, &contributor).0,
+            contributor,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitContribution {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn set_caller_program(
+    counter: CounterKey,
+    authority: Pubkey,
+    program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetCallerProgram {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetCallerProgram {
+            counter_id: counter.counter_id,
+            program,
+        },
+    )
+}
+
+pub fn init_leaderboard(authority: Pubkey) -> Instruction {
+    build(
+        accounts::InitLeaderboard {
+            leaderboard: pda::leaderboard(&authority).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitLeaderboard {},
+    )
+}
+
+/// Submit the counter's count to the leaderboard created by `leaderboard_authority`,
+/// who signs the submission
+pub fn submit_score(leaderboard_authority: Pubkey, counter: CounterKey) -> Instruction {
+    build(
+        accounts::SubmitScore {
+            leaderboard: pda::leaderboard(&leaderboard_authority).0,
+            counter: counter.address(),
+            authority: leaderboard_authority,
+        },
+        instruction::SubmitScore {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Drop the entries of the closed `counters` from the leaderboard created by
+/// `leaderboard_authority`
+pub fn prune_leaderboard(leaderboard_authority: Pubkey, counters: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::PruneLeaderboard {
+            leaderboard: pda::leaderboard(&leaderboard_authority).0,
+        },
+        instruction::PruneLeaderboard {},
+    );
+    ix.accounts.extend(
+        counters
+            .iter()
+            .map(|counter| AccountMeta::new_readonly(*counter, false)),
+    );
+    ix
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_treasury(counter: CounterKey, authority: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitTreasury {
+            counter: address,
+            treasury: pda::treasury(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn withdraw(
+    counter: CounterKey,
+    signer: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::Withdraw {
+            counter: address,
+            treasury: pda::treasury(&address).0,
+            config: Some(pda::config().0),
+            signer,
+            recipient,
+        },
+        instruction::Withdraw {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+/// Create the fee escrow `payer` funds the counter's fees from on the ER, with
+/// `deposit` lamports on top of its rent
+pub fn init_fee_escrow(counter: CounterKey, payer: Pubkey, deposit: u64) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitFeeEscrow {
+            counter: address,
+            fee_escrow: pda::fee_escrow(&address, &payer).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitFeeEscrow {
+            counter_id: counter.counter_id,
+            deposit,
+        },
+    )
+}
+
+/// Close the fee escrow of `payer`, once its fees are settled into the treasury
+pub fn close_fee_escrow(counter: &Pubkey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::CloseFeeEscrow {
+            fee_escrow: pda::fee_escrow(counter, &payer).0,
+            payer,
+        },
+        instruction::CloseFeeEscrow {},
+    )
+}
+
+pub fn set_session_scope(
+    counter: CounterKey,
+    authority: Pubkey,
+    scope: SessionScope,
+) -> Instruction {
+    build(
+        accounts::SetSessionScope {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetSessionScope {
+            counter_id: counter.counter_id,
+            scope,
+        },
+    )
+}
+
+/// Create the program config, signed by the program's upgrade authority
+pub fn init_config(payer: Pubkey, admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitConfig {
+            config: pda::config().0,
+            program_data: pda::program_data().0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitConfig { admin },
+    )
+}
+
+pub fn set_paused(admin: Pubkey, paused: bool) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config().0,
+            admin,
+        },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn set_admin(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config().0,
+            admin,
+        },
+        instruction::SetAdmin { new_admin },
+    )
+}
+
+// ========================================
+// MagicBlock Ephemeral Rollups Instructions
+// ========================================
+
+/// Append the validator the delegate_* instructions read from their first remaining
+/// account
+fn with_validator(mut ix: Instruction, validator: Option<Pubkey>) -> Instruction {
+    ix.accounts
+        .extend(validator.map(|key| AccountMeta::new_readonly(key, false)));
+    ix
+}
+
+/// Delegate the counter to `validator`, or to any validator when None
+pub fn delegate(
+    counter: CounterKey,
+    payer: Pubkey,
+    multisig: bool,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let address = counter.address();
+    let ix = build(
+        accounts::DelegateInput {
+            payer,
+            buffer_pda: pda::delegate_buffer(&address).0,
+            delegation_record_pda: pda::delegation_record(&address).0,
+            delegation_metadata_pda: pda::delegation_metadata(&address).0,
+            pda: address,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::Delegate {
+            counter_id: counter.counter_id,
+        },
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the counter's history; put it before delegate in the same transaction
+pub fn delegate_history(
+    counter: CounterKey,
+    payer: Pubkey,
+    multisig: bool,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let address = counter.address();
+    let history = pda::history(&address).0;
+    let ix = build(
+        accounts::DelegateHistory {
+            payer,
+            counter: address,
+            buffer_history: pda::delegate_buffer(&history).0,
+            delegation_record_history: pda::delegation_record(&history).0,
+            delegation_metadata_history: pda::delegation_metadata(&history).0,
+            history,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateHistory {
+            counter_id: counter.counter_id,
+        },
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the session usage of `session_signer`; put it before delegate in the
+/// same transaction
+pub fn delegate_session_usage(
+    counter: CounterKey,
+    payer: Pubkey,
+    session_signer: Pubkey,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let address = counter.address();
+    let session_usage = pda::session_usage(&address, &session_signer).0;
+    let ix = build(
+        accounts::DelegateSessionUsage {
+            payer,
+            counter: address,
+            buffer_session_usage: pda::delegate_buffer(&session_usage).0,
+            delegation_record_session_usage: pda::delegation_record(&session_usage).0,
+            delegation_metadata_session_usage: pda::delegation_metadata(&session_usage).0,
+            session_usage,
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateSessionUsage {
+            counter_id: counter.counter_id,
+            session_signer,
+        },
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the contribution of `payer` to the counter at `counter`
+pub fn delegate_contribution(
+    counter: &Pubkey,
+    payer: Pubkey,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let contribution = pda::contribution(counter, &payer).0;
+    let ix = build(
+        accounts::DelegateContribution {
+            payer,
+            counter: *counter,
+            buffer_contribution: pda::delegate_buffer(&contribution).0,
+            delegation_record_contribution: pda::delegation_record(&contribution).0,
+            delegation_metadata_contribution: pda::delegation_metadata(&contribution).0,
+            contribution,
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateContribution {},
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the leaderboard of `payer`, committed by the validator every
+/// `commit_frequency_ms` when set
+pub fn delegate_leaderboard(
+    payer: Pubkey,
+    commit_frequency_ms: Option<u32>,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let leaderboard = pda::leaderboard(&payer).0;
+    let ix = build(
+        accounts::DelegateLeaderboard {
+            payer,
+            buffer_leaderboard: pda::delegate_buffer(&leaderboard).0,
+            delegation_record_leaderboard: pda::delegation_record(&leaderboard).0,
+            delegation_metadata_leaderboard: pda::delegation_metadata(&leaderboard).0,
+            leaderboard,
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateLeaderboard {
+            commit_frequency_ms,
+        },
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the counter's treasury so fee escrows can be settled into it on the ER
+pub fn delegate_treasury(
+    counter: CounterKey,
+    payer: Pubkey,
+    multisig: bool,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let address = counter.address();
+    let treasury = pda::treasury(&address).0;
+    let ix = build(
+        accounts::DelegateTreasury {
+            payer,
+            counter: address,
+            buffer_treasury: pda::delegate_buffer(&treasury).0,
+            delegation_record_treasury: pda::delegation_record(&treasury).0,
+            delegation_metadata_treasury: pda::delegation_metadata(&treasury).0,
+            treasury,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateTreasury {
+            counter_id: counter.counter_id,
+        },
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegate the fee escrow of `payer` for the counter at `counter`
+pub fn delegate_fee_escrow(
+    counter: &Pubkey,
+    payer: Pubkey,
+    validator: Option<Pubkey>,
+) -> Instruction {
+    let fee_escrow = pda::fee_escrow(counter, &payer).0;
+    let ix = build(
+        accounts::DelegateFeeEscrow {
+            payer,
+            counter: *counter,
+            buffer_fee_escrow: pda::delegate_buffer(&fee_escrow).0,
+            delegation_record_fee_escrow: pda::delegation_record(&fee_escrow).0,
+            delegation_metadata_fee_escrow: pda::delegation_metadata(&fee_escrow).0,
+            fee_escrow,
+            owner_program: counter::ID,
+            delegation_program: pda::delegation_program(),
+            system_program: system_program::ID,
+        },
+        instruction::DelegateFeeEscrow {},
+    );
+    with_validator(ix, validator)
+}
+
+/// Delegated accounts committed or undelegated along with the counter
+#[derive(Clone, Debug, Default)]
+pub struct CommitAccounts {
+    /// The counter's history
+    pub history: bool,
+    /// The session signer whose usage was delegated
+    pub session_signer: Option<Pubkey>,
+    /// The counter's treasury, needed to settle fee escrows
+    pub treasury: bool,
+    /// Contributors whose contributions were delegated
+    pub contributors: Vec<Pubkey>,
+    /// Payers whose fee escrows were delegated, settled into the treasury on commit
+    pub fee_payers: Vec<Pubkey>,
+    /// The counter's multisig, needed when the payer is one of its members
+    pub multisig: bool,
+}
+
+fn commit_input(counter: CounterKey, payer: Pubkey, options: &CommitAccounts) -> Vec<AccountMeta> {
+    let address = counter.address();
+    let mut metas = accounts::CommitInput {
+        payer,
+        counter: address,
+        history: options.history.then(|| pda::history(&address).0),
+        session_usage: options
+            .session_signer
+            .map(|signer| pda::session_usage(&address, &signer).0),
+        treasury: options.treasury.then(|| pda::treasury(&address).0),
+        multisig: options.multisig.then(|| pda::multisig(&address).0),
+        magic_program: MAGIC_PROGRAM_ID,
+        magic_context: MAGIC_CONTEXT_ID,
+    }
+    .to_account_metas(None);
+    let contributions = options
+        .contributors
+        .iter()
+        .map(|contributor| pda::contribution(&address, contributor).0);
+    let escrows = options
+        .fee_payers
+        .iter()
+        .map(|payer| pda::fee_escrow(&address, payer).0);
+    metas.extend(
+        contributions
+            .chain(escrows)
+            .map(|key| AccountMeta::new(key, false)),
+    );
+    metas
+}
+
+/// Commit the counter and `options` from the ER, keeping them delegated
+pub fn commit(counter: CounterKey, payer: Pubkey, options: &CommitAccounts) -> Instruction {
+    build(
+        commit_input(counter, payer, options),
+        instruction::Commit {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Commit the counter and `options` from the ER and return them to the base layer
+pub fn undelegate(counter: CounterKey, payer: Pubkey, options: &CommitAccounts) -> Instruction {
+    build(
+        commit_input(counter, payer, options),
+        instruction::Undelegate {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+fn commit_leaderboard_input(payer: Pubkey) -> accounts::CommitLeaderboard {
+    accounts::CommitLeaderboard {
+        payer,
+        leaderboard: pda::leaderboard(&payer).0,
+        magic_program: MAGIC_PROGRAM_ID,
+        magic_context: MAGIC_CONTEXT_ID,
+    }
+}
+
+pub fn commit_leaderboard(payer: Pubkey) -> Instruction {
+    build(
+        commit_leaderboard_input(payer),
+        instruction::CommitLeaderboard {},
+    )
+}
+
+pub fn undelegate_leaderboard(payer: Pubkey) -> Instruction {
+    build(
+        commit_leaderboard_input(payer),
+        instruction::UndelegateLeaderboard {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn key() -> CounterKey {
+        CounterKey::new(Pubkey::new_unique(), 4)
+    }
+
+    /// Decode the arguments of `ix`, checking it carries the discriminator of `T`
+    fn args<T: InstructionData + AnchorDeserialize>(ix: &Instruction) -> T {
+        assert_eq!(ix.program_id, counter::ID);
+        assert_eq!(&ix.data[..8], T::DISCRIMINATOR);
+        T::deserialize(&mut &ix.data[8..]).unwrap()
+    }
+
+    /// Where the program expects an optional account that was left out
+    fn omitted() -> AccountMeta {
+        AccountMeta::new_readonly(counter::ID, false)
+    }
+
+    /// The meta of `key` in `ix`, which must pass it exactly once
+    fn meta(ix: &Instruction, key: Pubkey) -> AccountMeta {
+        let mut metas = ix.accounts.iter().filter(|meta| meta.pubkey == key);
+        let meta = metas.next().expect("account not passed").clone();
+        assert!(metas.next().is_none(), "account passed twice");
+        meta
+    }
+
+    #[test]
+    fn builders_carry_their_instructions_discriminator() {
+        let counter = key();
+        let address = counter.address();
+        let (signer, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let options = UpdateAccounts::default();
+        let commit_options = CommitAccounts::default();
+        let action = MultisigAction::Set { value: 1 };
+        let cases: &[(Instruction, &[u8])] = &[
+            (
+                initialize(signer, 1, 0, 10, OverflowPolicy::Wrap, None),
+                instruction::Initialize::DISCRIMINATOR,
+            ),
+            (
+                increment(counter, signer, &options),
+                instruction::Increment::DISCRIMINATOR,
+            ),
+            (
+                decrement(counter, signer, &options),
+                instruction::Decrement::DISCRIMINATOR,
+            ),
+            (
+                increment_by(counter, signer, &options, 2),
+                instruction::IncrementBy::DISCRIMINATOR,
+            ),
+            (
+                decrement_by(counter, signer, &options, 2),
+                instruction::DecrementBy::DISCRIMINATOR,
+            ),
+            (
+                set(counter, signer, &options, 2),
+                instruction::Set::DISCRIMINATOR,
+            ),
+            (
+                compare_and_set(counter, signer, &options, 1, 2),
+                instruction::CompareAndSet::DISCRIMINATOR,
+            ),
+            (
+                schedule_set(counter, signer, 2, 60),
+                instruction::ScheduleSet::DISCRIMINATOR,
+            ),
+            (
+                execute_set(counter, signer, other, &options),
+                instruction::ExecuteSet::DISCRIMINATOR,
+            ),
+            (
+                cancel_set(counter, signer),
+                instruction::CancelSet::DISCRIMINATOR,
+            ),
+            (
+                init_history(counter, signer, false),
+                instruction::InitHistory::DISCRIMINATOR,
+            ),
+            (
+                init_session_usage(counter, signer, other),
+                instruction::InitSessionUsage::DISCRIMINATOR,
+            ),
+            (
+                close(counter, signer, other, &CloseAccounts::default()),
+                instruction::Close::DISCRIMINATOR,
+            ),
+            (
+                migrate(counter, signer),
+                instruction::Migrate::DISCRIMINATOR,
+            ),
+            (
+                migrate_baseline(signer, 1),
+                instruction::Migrate::DISCRIMINATOR,
+            ),
+            (
+                propose_authority(counter, signer, other),
+                instruction::ProposeAuthority::DISCRIMINATOR,
+            ),
+            (
+                accept_authority(counter, other),
+                instruction::AcceptAuthority::DISCRIMINATOR,
+            ),
+            (
+                create_proposal(counter, signer, 0, action),
+                instruction::CreateProposal::DISCRIMINATOR,
+            ),
+            (
+                approve_proposal(counter, other, 0),
+                instruction::ApproveProposal::DISCRIMINATOR,
+            ),
+            (
+                execute_proposal(counter, signer, other, 0, &action, &options),
+                instruction::ExecuteProposal::DISCRIMINATOR,
+            ),
+            (
+                set_frozen(counter, signer, true),
+                instruction::SetFrozen::DISCRIMINATOR,
+            ),
+            (
+                set_rate_limit(counter, signer, RateLimit::default()),
+                instruction::SetRateLimit::DISCRIMINATOR,
+            ),
+            (
+                set_reset_period(counter, signer, ResetPeriod::Epoch),
+                instruction::SetResetPeriod::DISCRIMINATOR,
+            ),
+            (
+                set_public(counter, signer, true),
+                instruction::SetPublic::DISCRIMINATOR,
+            ),
+            (
+                set_fee(counter, signer, 5),
+                instruction::SetFee::DISCRIMINATOR,
+            ),
+            (
+                set_token_gate(counter, signer, Some(other), 1),
+                instruction::SetTokenGate::DISCRIMINATOR,
+            ),
+            (
+                init_reward_mint(counter, signer, false, 0),
+                instruction::InitRewardMint::DISCRIMINATOR,
+            ),
+            (
+                set_reward(counter, signer, 3),
+                instruction::SetReward::DISCRIMINATOR,
+            ),
+            (
+                mint_rewards(counter, None, other),
+                instruction::MintRewards::DISCRIMINATOR,
+            ),
+            (
+                init_contribution(counter, other, false),
+                instruction::InitContribution::DISCRIMINATOR,
+            ),
+            (
+                set_caller_program(counter, signer, Some(other)),
+                instruction::SetCallerProgram::DISCRIMINATOR,
+            ),
+            (
+                init_leaderboard(signer),
+                instruction::InitLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                submit_score(signer, counter),
+                instruction::SubmitScore::DISCRIMINATOR,
+            ),
+            (
+                prune_leaderboard(signer, &[other]),
+                instruction::PruneLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                init_treasury(counter, signer, false),
+                instruction::InitTreasury::DISCRIMINATOR,
+            ),
+            (
+                withdraw(counter, signer, other, 5),
+                instruction::Withdraw::DISCRIMINATOR,
+            ),
+            (
+                init_fee_escrow(counter, signer, 5),
+                instruction::InitFeeEscrow::DISCRIMINATOR,
+            ),
+            (
+                close_fee_escrow(&address, signer),
+                instruction::CloseFeeEscrow::DISCRIMINATOR,
+            ),
+            (
+                set_session_scope(counter, signer, SessionScope::default()),
+                instruction::SetSessionScope::DISCRIMINATOR,
+            ),
+            (
+                init_config(signer, other),
+                instruction::InitConfig::DISCRIMINATOR,
+            ),
+            (
+                set_paused(signer, true),
+                instruction::SetPaused::DISCRIMINATOR,
+            ),
+            (
+                set_admin(signer, other),
+                instruction::SetAdmin::DISCRIMINATOR,
+            ),
+            (
+                delegate(counter, signer, false, None),
+                instruction::Delegate::DISCRIMINATOR,
+            ),
+            (
+                delegate_history(counter, signer, false, None),
+                instruction::DelegateHistory::DISCRIMINATOR,
+            ),
+            (
+                delegate_session_usage(counter, signer, other, None),
+                instruction::DelegateSessionUsage::DISCRIMINATOR,
+            ),
+            (
+                delegate_contribution(&address, signer, None),
+                instruction::DelegateContribution::DISCRIMINATOR,
+            ),
+            (
+                delegate_leaderboard(signer, None, None),
+                instruction::DelegateLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                delegate_treasury(counter, signer, false, None),
+                instruction::DelegateTreasury::DISCRIMINATOR,
+            ),
+            (
+                delegate_fee_escrow(&address, signer, None),
+                instruction::DelegateFeeEscrow::DISCRIMINATOR,
+            ),
+            (
+                commit(counter, signer, &commit_options),
+                instruction::Commit::DISCRIMINATOR,
+            ),
+            (
+                undelegate(counter, signer, &commit_options),
+                instruction::Undelegate::DISCRIMINATOR,
+            ),
+            (
+                commit_leaderboard(signer),
+                instruction::CommitLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                undelegate_leaderboard(signer),
+                instruction::UndelegateLeaderboard::DISCRIMINATOR,
+            ),
+        ];
+        for (ix, discriminator) in cases {
+            assert_eq!(ix.program_id, counter::ID);
+            assert_eq!(&ix.data[..8], *discriminator);
+        }
+        // migrate_baseline shares migrate's instruction, every other builder has its own
+        let mut distinct: Vec<_> = cases
+            .iter()
+            .map(|(_, discriminator)| *discriminator)
+            .collect();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), cases.len() - 1);
+    }
+
+    #[test]
+    fn encodes_instruction_arguments() {
+        let counter = key();
+        let signer = Pubkey::new_unique();
+        let options = UpdateAccounts::default();
+
+        let decoded: instruction::CompareAndSet =
+            args(&compare_and_set(counter, signer, &options, 5, 9));
+        assert_eq!(
+            (decoded.counter_id, decoded.expected, decoded.new),
+            (4, 5, 9)
+        );
+
+        let scope = SessionScope {
+            restricted: true,
+            permissions: SessionScope::INCREMENT,
+            set_min: 1,
+            set_max: 50,
+            max_uses: 3,
+        };
+        let decoded: instruction::SetSessionScope =
+            args(&set_session_scope(counter, signer, scope));
+        assert!(decoded.scope == scope);
+
+        let config = MultisigConfig {
+            signers: vec![signer, Pubkey::new_unique()],
+            threshold: 2,
+            update_policy: counter::MultisigUpdatePolicy::Proposal,
+        };
+        let decoded: instruction::Initialize = args(&initialize(
+            signer,
+            7,
+            1,
+            99,
+            OverflowPolicy::Saturate,
+            Some(config.clone()),
+        ));
+        assert_eq!((decoded.counter_id, decoded.min, decoded.max), (7, 1, 99));
+        assert!(matches!(decoded.overflow_policy, OverflowPolicy::Saturate));
+        assert!(decoded.multisig == Some(config));
+    }
+
+    #[test]
+    fn update_builders_pass_only_the_requested_accounts() {
+        let counter = key();
+        let address = counter.address();
+        let signer = Pubkey::new_unique();
+
+        let ix = increment(counter, signer, &UpdateAccounts::default());
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(signer, true),
+                omitted(),
+                AccountMeta::new_readonly(pda::config().0, false),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+
+        let session_token = Pubkey::new_unique();
+        let gate_token_account = Pubkey::new_unique();
+        let ix = increment(
+            counter,
+            signer,
+            &UpdateAccounts {
+                session_token: Some(session_token),
+                without_config: true,
+                history: true,
+                multisig: true,
+                session_usage: true,
+                contribution: true,
+                treasury: true,
+                fee_escrow: true,
+                gate_token_account: Some(gate_token_account),
+            },
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(signer, true),
+                AccountMeta::new_readonly(session_token, false),
+                omitted(),
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new_readonly(pda::multisig(&address).0, false),
+                AccountMeta::new(pda::session_usage(&address, &signer).0, false),
+                AccountMeta::new(pda::contribution(&address, &signer).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                AccountMeta::new(pda::fee_escrow(&address, &signer).0, false),
+                AccountMeta::new_readonly(gate_token_account, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_proposal_passes_the_accounts_its_action_needs() {
+        let counter = key();
+        let address = counter.address();
+        let multisig = pda::multisig(&address).0;
+        let (proposer, executor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let recipient = Pubkey::new_unique();
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::Withdraw {
+                amount: 1,
+                recipient,
+            },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(multisig, false),
+                AccountMeta::new(pda::proposal(&multisig, 3).0, false),
+                AccountMeta::new(proposer, false),
+                AccountMeta::new_readonly(pda::config().0, false),
+                AccountMeta::new(executor, true),
+                omitted(),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                omitted(),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+        let decoded: instruction::ExecuteProposal = args(&ix);
+        assert_eq!((decoded.counter_id, decoded.index), (4, 3));
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::SetFrozen { frozen: true },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(ix.accounts[7], omitted());
+        assert_eq!(ix.accounts[8], omitted());
+        assert_eq!(ix.accounts[9], omitted());
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::ScheduleSet { value: 1, delay: 2 },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(
+            ix.accounts[8],
+            AccountMeta::new(pda::pending_set(&address).0, false)
+        );
+        assert_eq!(ix.accounts[9], omitted());
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::Close { recipient },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(ix.accounts[7], omitted());
+        assert_eq!(ix.accounts[9], AccountMeta::new(recipient, false));
+        assert_eq!(
+            ix.accounts[11..],
+            [
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                AccountMeta::new(pda::pending_set(&address).0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn close_appends_the_accounts_closed_with_the_counter() {
+        let counter = key();
+        let address = counter.address();
+        let (authority, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (session_signer, contributor, fee_payer) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        let ix = close(
+            counter,
+            authority,
+            recipient,
+            &CloseAccounts {
+                session_signers: vec![session_signer],
+                contributors: vec![contributor],
+                fee_payers: vec![fee_payer],
+            },
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                AccountMeta::new(pda::pending_set(&address).0, false),
+                AccountMeta::new(pda::multisig(&address).0, false),
+                AccountMeta::new(pda::session_usage(&address, &session_signer).0, false),
+                AccountMeta::new(pda::contribution(&address, &contributor).0, false),
+                AccountMeta::new(contributor, false),
+                AccountMeta::new(pda::fee_escrow(&address, &fee_payer).0, false),
+                AccountMeta::new(fee_payer, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn mint_rewards_passes_the_contribution_minted_from() {
+        let counter = key();
+        let address = counter.address();
+        let (contributor, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix = mint_rewards(counter, Some(contributor), recipient);
+        assert_eq!(
+            meta(&ix, pda::contribution(&address, &contributor).0),
+            AccountMeta::new(pda::contribution(&address, &contributor).0, false)
+        );
+        assert_eq!(meta(&ix, recipient), AccountMeta::new(recipient, false));
+
+        let ix = mint_rewards(counter, None, recipient);
+        assert!(ix.accounts.contains(&omitted()));
+    }
+
+    #[test]
+    fn delegate_passes_the_delegation_accounts_and_validator() {
+        let counter = key();
+        let address = counter.address();
+        let (payer, validator) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix = delegate(counter, payer, true, Some(validator));
+        assert_eq!(meta(&ix, payer), AccountMeta::new(payer, true));
+        assert_eq!(meta(&ix, address), AccountMeta::new(address, false));
+        for (pda, _) in [
+            pda::delegate_buffer(&address),
+            pda::delegation_record(&address),
+            pda::delegation_metadata(&address),
+        ] {
+            assert_eq!(meta(&ix, pda), AccountMeta::new(pda, false));
+        }
+        assert_eq!(
+            meta(&ix, pda::multisig(&address).0),
+            AccountMeta::new_readonly(pda::multisig(&address).0, false)
+        );
+        assert_eq!(
+            meta(&ix, pda::delegation_program()),
+            AccountMeta::new_readonly(pda::delegation_program(), false)
+        );
+        // The validator goes last, after the accounts the program declares
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new_readonly(validator, false))
+        );
+
+        let without_validator = delegate(counter, payer, true, None);
+        assert_eq!(without_validator.accounts.len(), ix.accounts.len() - 1);
+    }
+
+    #[test]
+    fn commit_passes_the_magic_accounts_and_delegated_accounts() {
+        let counter = key();
+        let address = counter.address();
+        let payer = Pubkey::new_unique();
+        let (contributor, fee_payer) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let options = CommitAccounts {
+            treasury: true,
+            contributors: vec![contributor],
+            fee_payers: vec![fee_payer],
+            ..Default::default()
+        };
+        for ix in [
+            commit(counter, payer, &options),
+            undelegate(counter, payer, &options),
+        ] {
+            assert_eq!(meta(&ix, payer), AccountMeta::new(payer, true));
+            assert_eq!(meta(&ix, address), AccountMeta::new(address, false));
+            assert_eq!(
+                meta(&ix, MAGIC_CONTEXT_ID),
+                AccountMeta::new(MAGIC_CONTEXT_ID, false)
+            );
+            assert_eq!(
+                meta(&ix, MAGIC_PROGRAM_ID),
+                AccountMeta::new_readonly(MAGIC_PROGRAM_ID, false)
+            );
+            assert_eq!(
+                ix.accounts[ix.accounts.len() - 2..],
+                [
+                    AccountMeta::new(pda::contribution(&address, &contributor).0, false),
+                    AccountMeta::new(pda::fee_escrow(&address, &fee_payer).0, false),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn leaderboard_builders_sign_submissions_and_append_pruned_counters() {
+        let counter = key();
+        let authority = Pubkey::new_unique();
+        let leaderboard = pda::leaderboard(&authority).0;
+
+        assert_eq!(
+            submit_score(authority, counter).accounts,
+            vec![
+                AccountMeta::new(leaderboard, false),
+                AccountMeta::new_readonly(counter.address(), false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(
+            prune_leaderboard(authority, &[counter.address()]).accounts,
+            vec![
+                AccountMeta::new(leaderboard, false),
+                AccountMeta::new_readonly(counter.address(), false),
+            ]
+        );
+    }
+}
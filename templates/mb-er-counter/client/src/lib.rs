@@ -0,0 +1,256 @@
+//! Client for the counter program: typed instruction builders, PDA helpers, account
+//! decoding and error mapping
+//! Everything is built on the program crate itself, so addresses, seeds, account
+//! order and discriminators always match the deployed `counter::ID`
+
+use anchor_lang::prelude::*;
+
+pub mod instructions;
+pub mod pda;
+
+pub use counter::{
+    Contribution, Counter, CounterError, CounterHistory, FeeEscrow, Leaderboard, Multisig,
+    MultisigAction, MultisigConfig, OverflowPolicy, PendingSet, ProgramConfig, Proposal, RateLimit,
+    ResetPeriod, SessionScope, SessionUsage, Treasury, ID,
+};
+
+/// Identifies a counter by the seeds of its address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CounterKey {
+    /// The key that created the counter, kept even after an authority handover
+    pub seed_authority: Pubkey,
+    pub counter_id: u64,
+}
+
+impl CounterKey {
+    pub fn new(seed_authority: Pubkey, counter_id: u64) -> Self {
+        Self {
+            seed_authority,
+            counter_id,
+        }
+    }
+
+    /// The counter's address
+    pub fn address(&self) -> Pubkey {
+        pda::counter(&self.seed_authority, self.counter_id).0
+    }
+}
+
+/// Decode a counter from its account data
+/// Counters still in an older layout fail with AccountNotMigrated, see migrate
+pub fn decode_counter(data: &[u8]) -> Result<Counter> {
+    decode(data)
+}
+
+/// Decode a counter stored in any layout up to the current one, returning the stored
+/// version along with the counter in the current layout
+/// Counters in the original layout lack their counter_id and bump until migrated
+pub fn decode_stored_counter(data: &[u8]) -> Result<(u8, Counter)> {
+    Counter::upgrade(data)
+}
+
+/// Decode any of the program's accounts from its data, checking the discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+macro_rules! counter_errors {
+    ($($variant:ident),* $(,)?) => {
+        /// Every CounterError, in declaration order
+        pub const COUNTER_ERRORS: &[CounterError] = &[$(CounterError::$variant),*];
+
+        // Stops compiling when a variant is missing from the list
+        const _: fn(CounterError) = |error| match error {
+            $(CounterError::$variant => {})*
+        };
+    };
+}
+
+counter_errors!(
+    CounterUnderflow,
+    InvalidAuth,
+    CounterDelegated,
+    NotPendingAuthority,
+    Overflow,
+    ValueOutOfRange,
+    InvalidBounds,
+    AccountNotMigrated,
+    Paused,
+    CountMismatch,
+    InvalidPermissions,
+    SessionScopeDenied,
+    SessionUsageRequired,
+    SessionQuotaExceeded,
+    SetNotReady,
+    SetAlreadyPending,
+    RateLimited,
+    InvalidRateLimit,
+    InvalidResetPeriod,
+    CounterNotPublic,
+    ContributionRequired,
+    InvalidContribution,
+    TreasuryRequired,
+    InsufficientFunds,
+    InvalidFeeEscrow,
+    FeesUnsettled,
+    GateTokenRequired,
+    InvalidGateToken,
+    GateBalanceTooLow,
+    RewardMintRequired,
+    InvalidRewardAccount,
+    NoPendingRewards,
+    RelocationMismatch,
+    InvalidSessionUsage,
+    InvalidMultisig,
+    NotMultisigSigner,
+    MultisigProposalRequired,
+    ThresholdNotMet,
+    ProposalAccountsRequired,
+    StaleProposal,
+    RewardContributionRequired,
+    CounterNotClosed,
+);
+
+/// The CounterError behind a custom program error code, None for codes outside the
+/// program's range such as Anchor's own
+pub fn counter_error(code: u32) -> Option<CounterError> {
+    COUNTER_ERRORS
+        .iter()
+        .copied()
+        .find(|&error| u32::from(error) == code)
+}
+
+/// The CounterError behind a failed instruction's error, if it is one
+pub fn counter_error_from_program_error(error: &ProgramError) -> Option<CounterError> {
+    match error {
+        ProgramError::Custom(code) => counter_error(*code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter() -> Counter {
+        let mut data = Counter::DISCRIMINATOR.to_vec();
+        data.resize(8 + Counter::INIT_SPACE, 0);
+        let mut counter = Counter::try_deserialize_unchecked(&mut &data[..]).unwrap();
+        counter.version = Counter::VERSION;
+        counter.count = 7;
+        counter.authority = Pubkey::new_unique();
+        counter.seed_authority = counter.authority;
+        counter.counter_id = 3;
+        counter.max = 1000;
+        counter.sequence = 12;
+        counter.pending_rewards = 6;
+        counter.caller_program = Pubkey::new_unique();
+        counter
+    }
+
+    fn serialize(counter: &Counter) -> Vec<u8> {
+        let mut data = Vec::new();
+        counter.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn decodes_a_serialized_counter() {
+        let data = serialize(&counter());
+        assert_eq!(serialize(&decode_counter(&data).unwrap()), data);
+
+        let (version, stored) = decode_stored_counter(&data).unwrap();
+        assert_eq!(version, Counter::VERSION);
+        assert_eq!(serialize(&stored), data);
+    }
+
+    #[test]
+    fn upgrades_a_counter_in_the_previous_layout() {
+        // The previous version ends before caller_program
+        let counter = counter();
+        let mut data = serialize(&counter);
+        data.truncate(data.len() - 32);
+        data[8] = Counter::VERSION - 1;
+        assert_eq!(
+            decode_counter(&data).err(),
+            Some(CounterError::AccountNotMigrated.into())
+        );
+
+        let (version, upgraded) = decode_stored_counter(&data).unwrap();
+        assert_eq!(version, Counter::VERSION - 1);
+        assert_eq!(upgraded.version, Counter::VERSION);
+        assert_eq!(upgraded.caller_program, Pubkey::default());
+        let expected = Counter {
+            caller_program: Pubkey::default(),
+            ..counter
+        };
+        assert_eq!(serialize(&upgraded), serialize(&expected));
+    }
+
+    #[test]
+    fn upgrades_a_counter_in_the_original_layout() {
+        let authority = Pubkey::new_unique();
+        let data = [
+            Counter::DISCRIMINATOR,
+            &5u64.to_le_bytes(),
+            authority.as_ref(),
+        ]
+        .concat();
+        assert_eq!(
+            decode_counter(&data).err(),
+            Some(CounterError::AccountNotMigrated.into())
+        );
+
+        let (version, upgraded) = decode_stored_counter(&data).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(upgraded.version, Counter::VERSION);
+        assert_eq!(upgraded.count, 5);
+        assert_eq!(upgraded.authority, authority);
+        assert_eq!(upgraded.seed_authority, authority);
+        assert_eq!(upgraded.min, 0);
+        assert_eq!(upgraded.max, u64::MAX);
+        assert!(matches!(upgraded.overflow_policy, OverflowPolicy::Error));
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let data = serialize(&counter());
+        assert_eq!(
+            decode::<Treasury>(&data).err(),
+            Some(ErrorCode::AccountDiscriminatorMismatch.into())
+        );
+    }
+
+    #[test]
+    fn maps_error_codes_to_their_variants() {
+        for (index, &error) in COUNTER_ERRORS.iter().enumerate() {
+            let code = ERROR_CODE_OFFSET + index as u32;
+            assert_eq!(u32::from(error), code);
+            assert_eq!(counter_error(code).map(u32::from), Some(code));
+        }
+        assert!(matches!(
+            counter_error(6000),
+            Some(CounterError::CounterUnderflow)
+        ));
+        assert!(matches!(
+            counter_error(6002),
+            Some(CounterError::CounterDelegated)
+        ));
+        assert!(matches!(
+            counter_error_from_program_error(&ProgramError::Custom(6008)),
+            Some(CounterError::Paused)
+        ));
+        assert!(matches!(
+            counter_error(u32::from(CounterError::CounterNotClosed)),
+            Some(CounterError::CounterNotClosed)
+        ));
+    }
+
+    #[test]
+    fn leaves_other_error_codes_unmapped() {
+        let past_last = ERROR_CODE_OFFSET + COUNTER_ERRORS.len() as u32;
+        assert!(counter_error(past_last).is_none());
+        assert!(counter_error(ErrorCode::AccountNotInitialized.into()).is_none());
+        assert!(counter_error_from_program_error(&ProgramError::InvalidArgument).is_none());
+    }
+}
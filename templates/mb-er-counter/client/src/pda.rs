@@ -0,0 +1,239 @@
+//! Addresses of the program's PDAs, each returned with its bump like
+//! `Pubkey::find_program_address`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use counter::{
+    CALLER_SIGNER_SEED, CONFIG_SEED, CONTRIBUTION_SEED, FEE_ESCROW_SEED, HISTORY_SEED,
+    LEADERBOARD_SEED, MULTISIG_SEED, PENDING_SET_SEED, PROPOSAL_SEED, REWARD_MINT_SEED,
+    SESSION_USAGE_SEED, TREASURY_SEED,
+};
+use ephemeral_rollups_sdk::pda::{
+    DELEGATE_BUFFER_TAG, DELEGATION_METADATA_TAG, DELEGATION_RECORD_TAG,
+};
+
+/// The counter `seed_authority` created with `counter_id`
+/// The seeds keep the creator's key even after the authority is handed over
+pub fn counter(seed_authority: &Pubkey, counter_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        &counter::ID,
+    )
+}
+
+/// A counter still in the original layout, which lived at the PDA of its authority
+/// alone until migrate moves it to `counter`
+pub fn baseline_counter(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[authority.as_ref()], &counter::ID)
+}
+
+/// The singleton ProgramConfig
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &counter::ID)
+}
+
+/// The program's ProgramData account, holding its upgrade authority
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[counter::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// The counter's CounterHistory
+pub fn history(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[HISTORY_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The counter's PendingSet, while a set is scheduled
+pub fn pending_set(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PENDING_SET_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The SessionUsage of `session_signer` on the counter
+pub fn session_usage(counter: &Pubkey, session_signer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SESSION_USAGE_SEED,
+            counter.as_ref(),
+            session_signer.as_ref(),
+        ],
+        &counter::ID,
+    )
+}
+
+/// The counter's Multisig
+pub fn multisig(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The multisig's proposal number `index`
+pub fn proposal(multisig: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROPOSAL_SEED, multisig.as_ref(), &index.to_le_bytes()],
+        &counter::ID,
+    )
+}
+
+/// The Contribution of `contributor` to a public counter
+pub fn contribution(counter: &Pubkey, contributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CONTRIBUTION_SEED, counter.as_ref(), contributor.as_ref()],
+        &counter::ID,
+    )
+}
+
+/// The Leaderboard created by `authority`
+pub fn leaderboard(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LEADERBOARD_SEED, authority.as_ref()], &counter::ID)
+}
+
+/// The counter's Treasury
+pub fn treasury(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The FeeEscrow `payer` funds the counter's fees from on the ER
+pub fn fee_escrow(counter: &Pubkey, payer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FEE_ESCROW_SEED, counter.as_ref(), payer.as_ref()],
+        &counter::ID,
+    )
+}
+
+/// The counter's Token-2022 reward mint
+pub fn reward_mint(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_MINT_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The PDA `caller_program` signs increments of the counter with, derived under
+/// the caller program rather than this one
+pub fn caller_signer(counter: &Pubkey, caller_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CALLER_SIGNER_SEED, counter.as_ref()], caller_program)
+}
+
+/// The delegation program
+pub fn delegation_program() -> Pubkey {
+    ephemeral_rollups_sdk::id()
+}
+
+/// The buffer a delegated account's data goes through, derived under this program
+pub fn delegate_buffer(delegated: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DELEGATE_BUFFER_TAG, delegated.as_ref()], &counter::ID)
+}
+
+/// The delegation record of a delegated account, derived under the delegation program
+pub fn delegation_record(delegated: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DELEGATION_RECORD_TAG, delegated.as_ref()],
+        &delegation_program(),
+    )
+}
+
+/// The delegation metadata of a delegated account, derived under the delegation program
+pub fn delegation_metadata(delegated: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DELEGATION_METADATA_TAG, delegated.as_ref()],
+        &delegation_program(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seeds are spelled out as the TS clients do rather than taken from the program
+    fn derive(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &counter::ID)
+    }
+
+    #[test]
+    fn derives_counters_at_the_program_seeds() {
+        let authority = Pubkey::new_unique();
+        let (address, bump) = counter(&authority, 7);
+        assert_eq!(
+            (address, bump),
+            derive(&[authority.as_ref(), &7u64.to_le_bytes()])
+        );
+        assert_ne!(address, counter(&authority, 8).0);
+        assert_eq!(baseline_counter(&authority), derive(&[authority.as_ref()]));
+        assert_eq!(
+            baseline_counter(&authority).0,
+            counter::Counter::baseline_address(&authority)
+        );
+    }
+
+    #[test]
+    fn derives_counter_accounts_at_the_program_seeds() {
+        let counter = counter(&Pubkey::new_unique(), 0).0;
+        let member = Pubkey::new_unique();
+        assert_eq!(config(), derive(&[b"config"]));
+        assert_eq!(history(&counter), derive(&[b"history", counter.as_ref()]));
+        assert_eq!(
+            pending_set(&counter),
+            derive(&[b"pending_set", counter.as_ref()])
+        );
+        assert_eq!(
+            session_usage(&counter, &member),
+            derive(&[b"session_usage", counter.as_ref(), member.as_ref()])
+        );
+        assert_eq!(treasury(&counter), derive(&[b"treasury", counter.as_ref()]));
+        assert_eq!(
+            reward_mint(&counter),
+            derive(&[b"reward_mint", counter.as_ref()])
+        );
+        assert_eq!(
+            contribution(&counter, &member),
+            derive(&[b"contribution", counter.as_ref(), member.as_ref()])
+        );
+        assert_eq!(
+            fee_escrow(&counter, &member),
+            derive(&[b"fee_escrow", counter.as_ref(), member.as_ref()])
+        );
+        assert_eq!(
+            leaderboard(&member),
+            derive(&[b"leaderboard", member.as_ref()])
+        );
+
+        let multisig = multisig(&counter);
+        assert_eq!(multisig, derive(&[b"multisig", counter.as_ref()]));
+        assert_eq!(
+            proposal(&multisig.0, 2),
+            derive(&[b"proposal", multisig.0.as_ref(), &2u64.to_le_bytes()])
+        );
+    }
+
+    #[test]
+    fn derives_other_programs_pdas() {
+        let counter = counter(&Pubkey::new_unique(), 0).0;
+        let caller = Pubkey::new_unique();
+        assert_eq!(
+            caller_signer(&counter, &caller),
+            Pubkey::find_program_address(&[b"counter_signer", counter.as_ref()], &caller)
+        );
+        assert_eq!(
+            program_data().0,
+            bpf_loader_upgradeable::get_program_data_address(&counter::ID)
+        );
+    }
+
+    #[test]
+    fn derives_the_delegation_programs_pdas() {
+        let delegation_program = pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+        assert_eq!(super::delegation_program(), delegation_program);
+
+        let counter = counter(&Pubkey::new_unique(), 0).0;
+        assert_eq!(
+            delegate_buffer(&counter),
+            derive(&[b"buffer", counter.as_ref()])
+        );
+        assert_eq!(
+            delegation_record(&counter),
+            Pubkey::find_program_address(&[b"delegation", counter.as_ref()], &delegation_program)
+        );
+        assert_eq!(
+            delegation_metadata(&counter),
+            Pubkey::find_program_address(
+                &[b"delegation-metadata", counter.as_ref()],
+                &delegation_program
+            )
+        );
+    }
+}
@@ -3,10 +3,26 @@
 // configured from the workspace's Anchor.toml.
 
 import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Counter } from "../target/types/counter";
 
 module.exports = async function (provider: anchor.AnchorProvider) {
   // Configure client to use the provider.
   anchor.setProvider(provider);
 
-  // Add your deploy script here.
+  // Counter updates read the program config, so create it once with the
+  // deployer (the upgrade authority) as admin
+  const program = anchor.workspace.Counter as Program<Counter>;
+  const [config] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  if (!(await provider.connection.getAccountInfo(config))) {
+    await program.methods
+      .initConfig(provider.wallet.publicKey)
+      .accounts({
+        payer: provider.wallet.publicKey,
+      })
+      .rpc();
+  }
 };
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, MintTo, Token2022, TokenAccount};
 use ephemeral_rollups_sdk::anchor::{commit, delegate, ephemeral};
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
@@ -6,63 +9,1390 @@ use session_keys::{session_auth_or, Session, SessionError, SessionToken};
 
 declare_id!("49NcALUBrB68LN1QpgfHB4G4TP6UJuyb7EG9QuwxcTVy");
 
+/// Seed prefix of the CounterHistory PDA, followed by the counter's address
+pub const HISTORY_SEED: &[u8] = b"history";
+
+/// Seed of the singleton ProgramConfig PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed prefix of the PendingSet PDA, followed by the counter's address
+pub const PENDING_SET_SEED: &[u8] = b"pending_set";
+
+/// Seed prefix of the SessionUsage PDA, followed by the counter's address and the
+/// session signer
+pub const SESSION_USAGE_SEED: &[u8] = b"session_usage";
+
+/// Seed of the per-contributor Contribution PDA, followed by the counter and contributor keys
+pub const CONTRIBUTION_SEED: &[u8] = b"contribution";
+
+/// Seed of the Leaderboard PDA, followed by the key of the authority that created it
+pub const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+
+/// Entries kept on a leaderboard
+pub const MAX_LEADERBOARD_ENTRIES: usize = 10;
+
+/// Seed prefix of the per-counter Treasury PDA, followed by the counter's address
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Seed prefix of the per-counter reward mint, followed by the counter's address
+/// The mint signs as its own mint authority
+pub const REWARD_MINT_SEED: &[u8] = b"reward_mint";
+
+/// Seed prefix of the PDA a caller program signs increments with, followed by the
+/// counter's address and derived under the caller program's id
+pub const CALLER_SIGNER_SEED: &[u8] = b"counter_signer";
+
+/// Seed prefix of the FeeEscrow PDA, followed by the counter and payer keys
+pub const FEE_ESCROW_SEED: &[u8] = b"fee_escrow";
+
+/// Seed prefix of the Multisig PDA, followed by the counter's address
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+
+/// Seed prefix of a Proposal PDA, followed by the multisig's address and the
+/// proposal index
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Most signers a multisig can have, bounded by the width of Proposal::approvals
+pub const MAX_MULTISIG_SIGNERS: usize = 8;
+
+/// Emit an event with `emit_cpi!` when built with the `event-cpi` feature, so it
+/// can't be lost to log truncation, and with `emit!` otherwise
+macro_rules! emit_event {
+    ($ctx:ident, $event:expr) => {{
+        #[cfg(feature = "event-cpi")]
+        {
+            let ctx = &$ctx;
+            emit_cpi!($event);
+        }
+        #[cfg(not(feature = "event-cpi"))]
+        emit!($event);
+    }};
+}
+
 #[ephemeral]
 #[program]
 pub mod counter {
     use super::*;
 
-    /// Initialize a new counter account with count set to 0
-    /// Uses PDA derivation with user's public key for deterministic addresses
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    /// Initialize a new counter account with count set to `min`
+    /// Uses PDA derivation with user's public key and a counter id for deterministic addresses,
+    /// so a single authority can own many independent counters
+    /// `min`, `max` and `overflow_policy` bound every later increment, decrement and set
+    /// Passing `multisig` (along with the multisig account) makes an M-of-N multisig the
+    /// counter's authority instead of the signer
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        counter_id: u64,
+        min: u64,
+        max: u64,
+        overflow_policy: OverflowPolicy,
+        multisig: Option<MultisigConfig>,
+    ) -> Result<()> {
+        require!(min <= max, CounterError::InvalidBounds);
+        let multisig_created = match (multisig, &mut ctx.accounts.multisig) {
+            (Some(config), Some(account)) => {
+                config.validate()?;
+                account.set_inner(Multisig {
+                    counter: ctx.accounts.counter.key(),
+                    config,
+                    proposal_count: 0,
+                    bump: ctx.bumps.multisig.ok_or(CounterError::InvalidMultisig)?,
+                });
+                Some(MultisigCreated {
+                    counter: ctx.accounts.counter.key(),
+                    multisig: account.key(),
+                    signers: account.config.signers.clone(),
+                    threshold: account.config.threshold,
+                })
+            }
+            (None, None) => None,
+            _ => return err!(CounterError::InvalidMultisig),
+        };
         let counter = &mut ctx.accounts.counter;
-        counter.count = 0;
-        counter.authority = ctx.accounts.authority.key();
+        counter.version = Counter::VERSION;
+        counter.count = min;
+        counter.authority = multisig_created
+            .as_ref()
+            .map_or(ctx.accounts.authority.key(), |created| created.multisig);
+        counter.counter_id = counter_id;
+        counter.seed_authority = ctx.accounts.authority.key();
+        counter.bump = ctx.bumps.counter;
+        counter.pending_authority = Pubkey::default();
+        counter.min = min;
+        counter.max = max;
+        counter.overflow_policy = overflow_policy;
+        counter.frozen = false;
         msg!(
-            "PDA {} initialized with count: {}",
+            "PDA {} (id {}) initialized with count: {}",
             counter.key(),
+            counter_id,
             counter.count
         );
+        let event = CounterInitialized {
+            counter: counter.key(),
+            authority: counter.authority,
+            counter_id,
+            count: counter.count,
+        };
+        emit_event!(ctx, event);
+        if let Some(event) = multisig_created {
+            emit_event!(ctx, event);
+        }
         Ok(())
     }
 
     /// Increment the counter by 1
-    /// Wraps around to 0 if count exceeds 1000 (for demo purposes)
-    #[session_auth_or(
-        ctx.accounts.counter.authority.key() == ctx.accounts.signer.key(),
-        CounterError::InvalidAuth
-    )]
-    pub fn increment(ctx: Context<Update>) -> Result<()> {
+    /// Going above `max` wraps, saturates or fails depending on the overflow policy
+    #[session_auth_or(ctx.accounts.may_increment(), CounterError::InvalidAuth)]
+    pub fn increment(ctx: Context<Update>, counter_id: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Increment, |counter| counter.add(1))?;
+        ctx.accounts.contribute(1)?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Decrement the counter by 1
+    /// Going below `min` wraps, saturates or fails depending on the overflow policy
+    #[session_auth_or(ctx.accounts.may_update(), CounterError::InvalidAuth)]
+    pub fn decrement(ctx: Context<Update>, counter_id: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Decrement, |counter| counter.sub(1))?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Increment the counter by `amount` in a single instruction
+    /// Follows the same bounds and overflow policy as increment
+    #[session_auth_or(ctx.accounts.may_increment(), CounterError::InvalidAuth)]
+    pub fn increment_by(ctx: Context<Update>, counter_id: u64, amount: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Increment, |counter| {
+                counter.add(amount)
+            })?;
+        ctx.accounts.contribute(amount)?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Decrement the counter by `amount` in a single instruction
+    /// Follows the same bounds and overflow policy as decrement
+    #[session_auth_or(ctx.accounts.may_update(), CounterError::InvalidAuth)]
+    pub fn decrement_by(ctx: Context<Update>, counter_id: u64, amount: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Decrement, |counter| {
+                counter.sub(amount)
+            })?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Set the counter to a specific value
+    /// The value must lie within the counter's `min..=max` bounds
+    #[session_auth_or(ctx.accounts.may_update(), CounterError::InvalidAuth)]
+    pub fn set(ctx: Context<Update>, counter_id: u64, value: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Set, |counter| counter.set(value))?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Set the counter to `new` only if it currently holds `expected`
+    /// Fails with CountMismatch otherwise, so concurrent writers can't silently
+    /// overwrite each other; `new` must lie within the counter's bounds
+    #[session_auth_or(ctx.accounts.may_update(), CounterError::InvalidAuth)]
+    pub fn compare_and_set(
+        ctx: Context<Update>,
+        counter_id: u64,
+        expected: u64,
+        new: u64,
+    ) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::CompareAndSet, |counter| {
+                counter.compare_and_set(expected, new)
+            })?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Schedule a set to `value` that anyone can execute once `delay` seconds have passed
+    /// The authority can cancel it until then; only one set can be pending per counter
+    /// Scheduling, executing and cancelling all run on the base layer, while the
+    /// counter is not delegated
+    pub fn schedule_set(
+        ctx: Context<ScheduleSet>,
+        counter_id: u64,
+        value: u64,
+        delay: u64,
+    ) -> Result<()> {
+        let counter = &ctx.accounts.counter;
+        let execute_after =
+            ctx.accounts
+                .pending_set
+                .schedule(counter, value, delay, ctx.bumps.pending_set)?;
+        msg!(
+            "PDA {} (id {}) set to {} scheduled for: {}",
+            counter.key(),
+            counter_id,
+            value,
+            execute_after
+        );
+        let event = SetScheduled {
+            counter: counter.key(),
+            value,
+            execute_after,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Carry out a scheduled set once its delay has passed
+    /// Anyone may execute it; the rent of the pending set goes back to the authority
+    /// that scheduled it
+    /// The set is held to the rate limit and recorded like any other, with the executor
+    /// paying the counter's fee
+    pub fn execute_set(ctx: Context<ExecuteSet>, counter_id: u64) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        require!(
+            Clock::get()?.unix_timestamp >= accounts.pending_set.execute_after,
+            CounterError::SetNotReady
+        );
+        charge_fee(
+            &accounts.counter,
+            CounterOp::Set,
+            &accounts.executor,
+            accounts.treasury.as_mut(),
+            None,
+            &accounts.system_program,
+        )?;
+        let value = accounts.pending_set.value;
+        let event = write_count(
+            &mut accounts.counter,
+            accounts.history.as_ref(),
+            counter_id,
+            CounterOp::Set,
+            accounts.executor.key(),
+            |counter| counter.set(value),
+        )?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Drop a scheduled set before it is executed, returning its rent to the authority
+    pub fn cancel_set(ctx: Context<CancelSet>, counter_id: u64) -> Result<()> {
+        let pending_set = &ctx.accounts.pending_set;
+        msg!(
+            "PDA {} (id {}) scheduled set to {} cancelled",
+            ctx.accounts.counter.key(),
+            counter_id,
+            pending_set.value
+        );
+        let event = SetCancelled {
+            counter: ctx.accounts.counter.key(),
+            value: pending_set.value,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the optional change history of a counter
+    /// Once it exists, pass it to increment, decrement and set to record every change
+    pub fn init_history(ctx: Context<InitHistory>, counter_id: u64) -> Result<()> {
+        let mut history = ctx.accounts.history.load_init()?;
+        history.counter = ctx.accounts.counter.key();
+        history.bump = ctx.bumps.history;
+        msg!(
+            "PDA {} (id {}) history created at {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            ctx.accounts.history.key()
+        );
+        let event = HistoryInitialized {
+            counter: ctx.accounts.counter.key(),
+            history: ctx.accounts.history.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the account counting the updates `session_signer` performs on a counter
+    /// Session signers must pass it to increment, decrement and set once the
+    /// counter's session scope caps their uses
+    pub fn init_session_usage(
+        ctx: Context<InitSessionUsage>,
+        counter_id: u64,
+        session_signer: Pubkey,
+    ) -> Result<()> {
+        let usage = &mut ctx.accounts.session_usage;
+        usage.counter = ctx.accounts.counter.key();
+        usage.session_signer = session_signer;
+        usage.valid_until = 0;
+        usage.used = 0;
+        usage.bump = ctx.bumps.session_usage;
+        msg!(
+            "PDA {} (id {}) session usage of {} created at {}",
+            usage.counter,
+            counter_id,
+            session_signer,
+            usage.key()
+        );
+        let event = SessionUsageInitialized {
+            counter: usage.counter,
+            session_signer,
+            session_usage: usage.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Close the counter account and return its rent to the recipient
+    /// Pass the authority as recipient to reclaim the lamports to the owner's wallet
+    /// The counter's history, treasury, pending set and multisig are closed along with it
+    /// whenever they exist, their rent and any fees left in the treasury going to the
+    /// recipient, so a counter created again at the same address starts from scratch
+    /// A counter held by a multisig is closed through a Close proposal instead
+    /// Pass its session usages, contributions and fee escrows as remaining accounts to
+    /// close them too, each contribution followed by its contributor and each escrow by
+    /// its payer, who get the rent back
+    /// Fails with CounterDelegated while the counter or any of them is delegated -
+    /// undelegate it first
+    pub fn close<'info>(
+        ctx: Context<'_, '_, '_, 'info, Close<'info>>,
+        counter_id: u64,
+    ) -> Result<()> {
+        let counter_info = ctx.accounts.counter.to_account_info();
+        let counter = Counter::try_deserialize(&mut &counter_info.try_borrow_data()?[..])?;
+        counter.verify_address(counter_info.key, counter_id)?;
+        require_keys_eq!(
+            counter.authority,
+            ctx.accounts.authority.key(),
+            CounterError::InvalidAuth
+        );
+
+        let recipient = ctx.accounts.recipient.to_account_info();
+        close_account(&counter_info, &recipient)?;
+        let accounts = &ctx.accounts;
+        for account in [
+            &accounts.history,
+            &accounts.treasury,
+            &accounts.pending_set,
+            &accounts.multisig,
+        ] {
+            if account.owner == &crate::ID {
+                close_account(account, &recipient)?;
+            }
+        }
+        close_dependents(counter_info.key, ctx.remaining_accounts, &recipient)?;
+
+        msg!(
+            "PDA {} (id {}) closed, rent returned to {}",
+            counter_info.key(),
+            counter_id,
+            recipient.key()
+        );
+        let event = CounterClosed {
+            counter: counter_info.key(),
+            recipient: recipient.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Bring a counter created by an earlier program version up to the current layout
+    /// Reallocs the account to the current size, topping up its rent from `payer`, and
+    /// fills the fields the old layout lacked with their defaults
+    /// Anyone may pay for a migration since it never changes how the counter behaves
+    /// Counters in the original layout lived at the PDA of their authority alone; their
+    /// authority moves them to `relocated`, the address of `[authority, counter_id]`,
+    /// and gets the old account's rent back
+    /// Fails with CounterDelegated while the counter is delegated - undelegate it first
+    pub fn migrate(ctx: Context<Migrate>, counter_id: u64) -> Result<()> {
+        let counter_info = ctx.accounts.counter.to_account_info();
+        let (from_version, mut counter) = Counter::upgrade(&counter_info.try_borrow_data()?)?;
+        match (from_version, &mut ctx.accounts.relocated) {
+            (0, Some(relocated)) => {
+                require_keys_eq!(
+                    Counter::baseline_address(&counter.authority),
+                    counter_info.key(),
+                    ErrorCode::ConstraintSeeds
+                );
+                require_keys_eq!(
+                    counter.authority,
+                    ctx.accounts.payer.key(),
+                    CounterError::InvalidAuth
+                );
+                counter.counter_id = counter_id;
+                counter.bump = ctx
+                    .bumps
+                    .relocated
+                    .ok_or(CounterError::RelocationMismatch)?;
+                relocated.set_inner(counter);
+                close_account(&counter_info, &ctx.accounts.payer)?;
+                msg!(
+                    "PDA {} moved to {} (id {}) and migrated from version 0 to {}",
+                    counter_info.key(),
+                    relocated.key(),
+                    counter_id,
+                    Counter::VERSION
+                );
+                let event = CounterMigrated {
+                    counter: relocated.key(),
+                    from_version,
+                    to_version: Counter::VERSION,
+                };
+                emit_event!(ctx, event);
+                return Ok(());
+            }
+            (0, None) | (_, Some(_)) => return err!(CounterError::RelocationMismatch),
+            _ => counter.verify_address(counter_info.key, counter_id)?,
+        }
+        if from_version == Counter::VERSION {
+            msg!(
+                "PDA {} (id {}) already at version {}",
+                counter_info.key(),
+                counter_id,
+                from_version
+            );
+            return Ok(());
+        }
+
+        let space = 8 + Counter::INIT_SPACE;
+        let rent = Rent::get()?.minimum_balance(space);
+        let missing = rent.saturating_sub(counter_info.lamports());
+        if missing > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: counter_info.clone(),
+                    },
+                ),
+                missing,
+            )?;
+        }
+        counter_info.resize(space)?;
+        counter.try_serialize(&mut &mut counter_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "PDA {} (id {}) migrated from version {} to {}",
+            counter_info.key(),
+            counter_id,
+            from_version,
+            Counter::VERSION
+        );
+        let event = CounterMigrated {
+            counter: counter_info.key(),
+            from_version,
+            to_version: Counter::VERSION,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Propose a new authority for the counter (step 1 of 2)
+    /// Proposing again replaces the pending authority; the PDA address never changes
+    /// Propose Pubkey::default() to cancel a pending transfer
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        counter_id: u64,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.pending_authority = new_authority;
+        msg!(
+            "PDA {} (id {}) proposed authority: {}",
+            counter.key(),
+            counter_id,
+            new_authority
+        );
+        let event = AuthorityProposed {
+            counter: counter.key(),
+            authority: counter.authority,
+            pending_authority: new_authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Accept a pending authority transfer (step 2 of 2)
+    /// Must be signed by the proposed authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>, counter_id: u64) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
-        counter.count = counter.count.checked_add(1).unwrap();
-        if counter.count > 1000 {
-            counter.count = 0;
+        let old_authority = counter.authority;
+        counter.authority = ctx.accounts.new_authority.key();
+        counter.pending_authority = Pubkey::default();
+        msg!(
+            "PDA {} (id {}) authority transferred to: {}",
+            counter.key(),
+            counter_id,
+            counter.authority
+        );
+        let event = AuthorityTransferred {
+            counter: counter.key(),
+            old_authority,
+            new_authority: counter.authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Propose `action` on a counter whose authority is a multisig
+    /// Must be signed by one of its signers, which counts as the first approval
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        counter_id: u64,
+        action: MultisigAction,
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposer = ctx.accounts.proposer.key();
+        let index = multisig.proposal_count;
+        multisig.proposal_count += 1;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.index = index;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        proposal.approvals = 0;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.approve(multisig, &proposer)?;
+        msg!(
+            "PDA {} (id {}) proposal {} created by {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index,
+            proposer
+        );
+        let event = ProposalCreated {
+            counter: ctx.accounts.counter.key(),
+            proposal: proposal.key(),
+            index,
+            proposer,
+            action,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Approve a pending proposal, signed by one of the multisig's signers
+    /// Approving twice has no further effect
+    pub fn approve_proposal(
+        ctx: Context<ApproveProposal>,
+        counter_id: u64,
+        index: u64,
+    ) -> Result<()> {
+        let member = ctx.accounts.member.key();
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.approve(&ctx.accounts.multisig, &member)?;
+        msg!(
+            "PDA {} (id {}) proposal {} approved by {} ({} of {})",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index,
+            member,
+            proposal.approval_count(),
+            ctx.accounts.multisig.config.threshold
+        );
+        let event = ProposalApproved {
+            proposal: proposal.key(),
+            member,
+            approvals: proposal.approval_count(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Carry out a proposal once the multisig's threshold of signers approved it
+    /// Anyone may execute; the proposal is closed and its rent returned to the proposer
+    /// Count changes go through the same pause, rate limit, fee and history as an
+    /// update, the executor paying the fee; the other actions stand in for the
+    /// instructions only the authority may sign
+    /// Runs on the base layer, while the counter is not delegated
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+        counter_id: u64,
+        index: u64,
+    ) -> Result<()> {
+        let action = ctx.accounts.proposal.action;
+        require!(
+            ctx.accounts.proposal.approval_count() >= ctx.accounts.multisig.config.threshold,
+            CounterError::ThresholdNotMet
+        );
+        msg!(
+            "PDA {} (id {}) executing proposal {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index
+        );
+        let event = ProposalExecuted {
+            counter: ctx.accounts.counter.key(),
+            proposal: ctx.accounts.proposal.key(),
+            index,
+            action,
+        };
+        emit_event!(ctx, event);
+
+        let multisig = ctx.accounts.multisig.key();
+        let counter_key = ctx.accounts.counter.key();
+        match action {
+            MultisigAction::Set { value } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Set, |counter| counter.set(value))?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Increment { amount } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Increment, |counter| {
+                        counter.add(amount)
+                    })?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Decrement { amount } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Decrement, |counter| {
+                        counter.sub(amount)
+                    })?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::ProposeAuthority { new_authority } => {
+                ctx.accounts.counter.pending_authority = new_authority;
+                msg!(
+                    "PDA {} (id {}) proposed authority: {}",
+                    counter_key,
+                    counter_id,
+                    new_authority
+                );
+                let event = AuthorityProposed {
+                    counter: counter_key,
+                    authority: multisig,
+                    pending_authority: new_authority,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetFrozen { frozen } => {
+                ctx.accounts.counter.frozen = frozen;
+                let event = CounterFrozen {
+                    counter: counter_key,
+                    frozen,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetRateLimit { rate_limit } => {
+                ctx.accounts.counter.update_rate_limit(rate_limit)?;
+                let event = RateLimitChanged {
+                    counter: counter_key,
+                    rate_limit,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetResetPeriod { reset_period } => {
+                let counter = &mut ctx.accounts.counter;
+                counter.update_reset_period(reset_period, &Clock::get()?)?;
+                let event = ResetPeriodChanged {
+                    counter: counter_key,
+                    reset_period,
+                    period_start: counter.period_start,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetPublic { public } => {
+                ctx.accounts.counter.public = public;
+                let event = CounterPublicChanged {
+                    counter: counter_key,
+                    public,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetFee { fee } => {
+                ctx.accounts.counter.fee = fee;
+                let event = FeeChanged {
+                    counter: counter_key,
+                    fee,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetTokenGate {
+                gate_mint,
+                min_balance,
+            } => {
+                let counter = &mut ctx.accounts.counter;
+                counter.gate_mint = gate_mint.unwrap_or_default();
+                counter.min_balance = min_balance;
+                let event = TokenGateChanged {
+                    counter: counter_key,
+                    gate_mint,
+                    min_balance,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetReward { amount } => {
+                ctx.accounts.counter.update_reward(amount)?;
+                let event = RewardChanged {
+                    counter: counter_key,
+                    amount,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetCallerProgram { program } => {
+                ctx.accounts.counter.caller_program = program.unwrap_or_default();
+                let event = CallerProgramChanged {
+                    counter: counter_key,
+                    program,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Withdraw { amount, recipient } => {
+                let (Some(treasury), Some(account)) =
+                    (&ctx.accounts.treasury, &ctx.accounts.recipient)
+                else {
+                    return err!(CounterError::ProposalAccountsRequired);
+                };
+                require_keys_eq!(
+                    account.key(),
+                    recipient,
+                    CounterError::ProposalAccountsRequired
+                );
+                withdraw_fees(&treasury.to_account_info(), account, amount)?;
+                let event = FeesWithdrawn {
+                    counter: counter_key,
+                    recipient,
+                    amount,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::ScheduleSet { value, delay } => {
+                let accounts = &mut *ctx.accounts;
+                let (Some(pending_set), Some(bump)) =
+                    (accounts.pending_set.as_mut(), ctx.bumps.pending_set)
+                else {
+                    return err!(CounterError::ProposalAccountsRequired);
+                };
+                let execute_after = pending_set.schedule(&accounts.counter, value, delay, bump)?;
+                msg!(
+                    "PDA {} (id {}) set to {} scheduled for: {}",
+                    counter_key,
+                    counter_id,
+                    value,
+                    execute_after
+                );
+                let event = SetScheduled {
+                    counter: counter_key,
+                    value,
+                    execute_after,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::CancelSet => {
+                let accounts = &mut *ctx.accounts;
+                let pending_set = accounts
+                    .pending_set
+                    .as_mut()
+                    .ok_or(CounterError::ProposalAccountsRequired)?;
+                // init_if_needed: an empty pending set was only just created
+                require_keys_eq!(
+                    pending_set.counter,
+                    counter_key,
+                    ErrorCode::AccountNotInitialized
+                );
+                let value = pending_set.value;
+                pending_set.close(accounts.multisig.to_account_info())?;
+                msg!(
+                    "PDA {} (id {}) scheduled set to {} cancelled",
+                    counter_key,
+                    counter_id,
+                    value
+                );
+                let event = SetCancelled {
+                    counter: counter_key,
+                    value,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Close { recipient } => {
+                let accounts = &ctx.accounts;
+                let account = accounts
+                    .recipient
+                    .as_ref()
+                    .filter(|account| account.key() == recipient)
+                    .ok_or(CounterError::ProposalAccountsRequired)?;
+                let (pdas, dependents) = ctx
+                    .remaining_accounts
+                    .split_at_checked(3)
+                    .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+                for (info, seed) in pdas
+                    .iter()
+                    .zip([HISTORY_SEED, TREASURY_SEED, PENDING_SET_SEED])
+                {
+                    let (address, _) =
+                        Pubkey::find_program_address(&[seed, counter_key.as_ref()], &crate::ID);
+                    require_keys_eq!(info.key(), address, ErrorCode::ConstraintSeeds);
+                    require!(
+                        info.owner != &ephemeral_rollups_sdk::id(),
+                        CounterError::CounterDelegated
+                    );
+                    if info.owner == &crate::ID {
+                        close_account(info, account)?;
+                    }
+                }
+                close_dependents(&counter_key, dependents, account)?;
+                close_account(&accounts.multisig.to_account_info(), account)?;
+                close_account(&accounts.counter.to_account_info(), account)?;
+                msg!(
+                    "PDA {} (id {}) closed, rent returned to {}",
+                    counter_key,
+                    counter_id,
+                    recipient
+                );
+                let event = CounterClosed {
+                    counter: counter_key,
+                    recipient,
+                };
+                emit_event!(ctx, event);
+            }
         }
-        msg!("PDA {} count: {}", counter.key(), counter.count);
         Ok(())
     }
 
-    /// Decrement the counter by 1
-    #[session_auth_or(
-        ctx.accounts.counter.authority.key() == ctx.accounts.signer.key(),
-        CounterError::InvalidAuth
-    )]
-    pub fn decrement(ctx: Context<Update>) -> Result<()> {
+    /// Freeze or unfreeze the counter
+    /// While frozen, increment, decrement and set fail with Paused
+    pub fn set_frozen(ctx: Context<SetFrozen>, counter_id: u64, frozen: bool) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
-        require!(counter.count > 0, CounterError::CounterUnderflow);
-        counter.count = counter.count.checked_sub(1).unwrap();
-        msg!("PDA {} count: {}", counter.key(), counter.count);
+        counter.frozen = frozen;
+        msg!(
+            "PDA {} (id {}) frozen: {}",
+            counter.key(),
+            counter_id,
+            frozen
+        );
+        let event = CounterFrozen {
+            counter: counter.key(),
+            frozen,
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 
-    /// Set the counter to a specific value
-    #[session_auth_or(
-        ctx.accounts.counter.authority.key() == ctx.accounts.signer.key(),
-        CounterError::InvalidAuth
-    )]
-    pub fn set(ctx: Context<Update>, value: u64) -> Result<()> {
+    /// Limit how often increment, decrement and set may be called on the counter
+    /// Every signer is held to it, the authority included; see RateLimit for the fields
+    pub fn set_rate_limit(
+        ctx: Context<SetRateLimit>,
+        counter_id: u64,
+        rate_limit: RateLimit,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_rate_limit(rate_limit)?;
+        msg!(
+            "PDA {} (id {}) rate limit: {} slots, {} seconds, {} per {} seconds",
+            counter.key(),
+            counter_id,
+            rate_limit.min_slots,
+            rate_limit.min_seconds,
+            rate_limit.max_per_window,
+            rate_limit.window_seconds
+        );
+        let event = RateLimitChanged {
+            counter: counter.key(),
+            rate_limit,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Reset the counter at every boundary of `reset_period`
+    /// The reset happens lazily, on the first update in a new period
+    pub fn set_reset_period(
+        ctx: Context<SetResetPeriod>,
+        counter_id: u64,
+        reset_period: ResetPeriod,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_reset_period(reset_period, &Clock::get()?)?;
+        msg!(
+            "PDA {} (id {}) reset period changed, current period started at {}",
+            counter.key(),
+            counter_id,
+            counter.period_start
+        );
+        let event = ResetPeriodChanged {
+            counter: counter.key(),
+            reset_period,
+            period_start: counter.period_start,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Open the counter to increments from any signer, or close it again
+    /// Signers with no other rights on the counter must pass their Contribution,
+    /// created via init_contribution, which records their share
+    pub fn set_public(ctx: Context<SetPublic>, counter_id: u64, public: bool) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.public = public;
+        msg!(
+            "PDA {} (id {}) public: {}",
+            counter.key(),
+            counter_id,
+            public
+        );
+        let event = CounterPublicChanged {
+            counter: counter.key(),
+            public,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Charge `fee` lamports for every increment and set on the counter, 0 to make
+    /// them free again; decrements are never charged
+    /// The signer pays into the counter's Treasury, created via init_treasury
+    /// On the ER the fee is taken from the signer's delegated FeeEscrow instead and
+    /// settled into the treasury on commit and undelegate
+    pub fn set_fee(ctx: Context<SetFee>, counter_id: u64, fee: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.fee = fee;
+        msg!("PDA {} (id {}) fee: {}", counter.key(), counter_id, fee);
+        let event = FeeChanged {
+            counter: counter.key(),
+            fee,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Only let holders of `gate_mint` increment the counter, None to lift the gate
+    /// Incrementing signers must pass a token account of the mint they own with a
+    /// balance of at least `min_balance`, or of 1 when it is 0
+    /// Accounts of both the SPL Token and the Token-2022 program are accepted
+    pub fn set_token_gate(
+        ctx: Context<SetTokenGate>,
+        counter_id: u64,
+        gate_mint: Option<Pubkey>,
+        min_balance: u64,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.gate_mint = gate_mint.unwrap_or_default();
+        counter.min_balance = min_balance;
+        msg!(
+            "PDA {} (id {}) gate mint: {}, min balance: {}",
+            counter.key(),
+            counter_id,
+            counter.gate_mint,
+            min_balance
+        );
+        let event = TokenGateChanged {
+            counter: counter.key(),
+            gate_mint,
+            min_balance,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the counter's Token-2022 reward mint, a PDA that is its own mint authority
+    /// Nothing is minted until an amount is set via set_reward
+    pub fn init_reward_mint(
+        ctx: Context<InitRewardMint>,
+        counter_id: u64,
+        decimals: u8,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.reward_mint = ctx.accounts.reward_mint.key();
+        counter.reward_bump = ctx.bumps.reward_mint;
+        msg!(
+            "PDA {} (id {}) reward mint created at {}",
+            counter.key(),
+            counter_id,
+            counter.reward_mint
+        );
+        let event = RewardMintInitialized {
+            counter: counter.key(),
+            mint: counter.reward_mint,
+            decimals,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Reward every increment with `amount` tokens of the reward mint, 0 to stop
+    /// Increments only accrue the rewards, in the signer's contribution or else the
+    /// counter, as the token program can't be called on the ER; mint_rewards pays them
+    /// out once they are back on Solana
+    /// The counter only accrues those of the authority and its session keys, anyone else
+    /// has to pass a contribution while a reward is set
+    pub fn set_reward(ctx: Context<SetReward>, counter_id: u64, amount: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_reward(amount)?;
+        msg!(
+            "PDA {} (id {}) reward: {}",
+            counter.key(),
+            counter_id,
+            amount
+        );
+        let event = RewardChanged {
+            counter: counter.key(),
+            amount,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Mint the rewards accrued in `contribution` to its contributor, or without one
+    /// those accrued in the counter to its authority
+    /// Anyone may call this once undelegate has returned the accounts to Solana
+    pub fn mint_rewards(ctx: Context<MintRewards>, counter_id: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        let pending = match ctx.accounts.contribution.as_mut() {
+            Some(contribution) => &mut contribution.pending_rewards,
+            None => &mut counter.pending_rewards,
+        };
+        let amount = std::mem::take(pending);
+        require!(amount > 0, CounterError::NoPendingRewards);
+        let counter_key = counter.key();
+        let seeds: &[&[u8]] = &[
+            REWARD_MINT_SEED,
+            counter_key.as_ref(),
+            &[counter.reward_bump],
+        ];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.recipient.to_account_info(),
+                    authority: ctx.accounts.reward_mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+        msg!(
+            "PDA {} (id {}) minted {} reward tokens",
+            counter_key,
+            counter_id,
+            amount
+        );
+        let event = RewardsMinted {
+            counter: counter_key,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Let `program` increment the counter through CPI, None to revoke it
+    /// The program signs with its PDA of [CALLER_SIGNER_SEED, counter address]
+    pub fn set_caller_program(
+        ctx: Context<SetCallerProgram>,
+        counter_id: u64,
+        program: Option<Pubkey>,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.caller_program = program.unwrap_or_default();
+        msg!(
+            "PDA {} (id {}) caller program: {}",
+            counter.key(),
+            counter_id,
+            counter.caller_program
+        );
+        let event = CallerProgramChanged {
+            counter: counter.key(),
+            program,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the account tracking the contributor's increments to a public counter
+    /// Members of the multisig holding the authority and the caller program's PDA may
+    /// create one on any counter, to accrue the rewards of their increments
+    pub fn init_contribution(ctx: Context<InitContribution>, counter_id: u64) -> Result<()> {
+        // The counter may already be delegated, so it is read from the data the base
+        // layer keeps for it rather than as an account owned by this program
+        let counter_info = &ctx.accounts.counter;
+        let counter = Counter::try_deserialize(&mut &counter_info.try_borrow_data()?[..])?;
+        counter.verify_address(counter_info.key, counter_id)?;
+        let contributor = ctx.accounts.contributor.key();
+        require!(
+            counter.public
+                || counter.is_authority_or_member(&contributor, ctx.accounts.multisig.as_ref())
+                || counter.is_caller_signer(counter_info.key, &contributor),
+            CounterError::CounterNotPublic
+        );
+        let contribution = &mut ctx.accounts.contribution;
+        contribution.counter = ctx.accounts.counter.key();
+        contribution.contributor = ctx.accounts.contributor.key();
+        contribution.amount = 0;
+        contribution.updates = 0;
+        contribution.pending_rewards = 0;
+        contribution.bump = ctx.bumps.contribution;
+        msg!(
+            "PDA {} (id {}) contribution of {} created at {}",
+            contribution.counter,
+            counter_id,
+            contribution.contributor,
+            contribution.key()
+        );
+        let event = ContributionInitialized {
+            counter: contribution.counter,
+            contributor: contribution.contributor,
+            contribution: contribution.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the authority's leaderboard, ranking the counters submitted to it by count
+    pub fn init_leaderboard(ctx: Context<InitLeaderboard>) -> Result<()> {
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.authority = ctx.accounts.authority.key();
+        leaderboard.entries = Vec::new();
+        leaderboard.bump = ctx.bumps.leaderboard;
+        msg!(
+            "Leaderboard of {} created at {}",
+            leaderboard.authority,
+            leaderboard.key()
+        );
+        let event = LeaderboardInitialized {
+            leaderboard: leaderboard.key(),
+            authority: leaderboard.authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Rank a counter on a leaderboard by its current count
+    /// Only the leaderboard's authority may submit, the count is read from the counter
+    /// itself; a counter already on the board is moved to its new place
+    /// Works on the ER once the leaderboard is delegated, for counters delegated to
+    /// the same validator
+    pub fn submit_score(ctx: Context<SubmitScore>, counter_id: u64) -> Result<()> {
+        let counter = &ctx.accounts.counter;
+        let entry = LeaderboardEntry {
+            counter: counter.key(),
+            authority: counter.authority,
+            count: counter.count,
+        };
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        let rank = leaderboard.submit(entry);
+        msg!(
+            "PDA {} (id {}) submitted {} to leaderboard {}, rank {:?}",
+            entry.counter,
+            counter_id,
+            entry.count,
+            leaderboard.key(),
+            rank
+        );
+        let event = ScoreSubmitted {
+            leaderboard: leaderboard.key(),
+            counter: entry.counter,
+            authority: entry.authority,
+            count: entry.count,
+            rank,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Drop the entries of closed counters, passed as remaining accounts
+    /// Anyone may prune; a counter that still exists keeps its place, delegated or not
+    pub fn prune_leaderboard(ctx: Context<PruneLeaderboard>) -> Result<()> {
+        let leaderboard = ctx.accounts.leaderboard.key();
+        for account in ctx.remaining_accounts {
+            require!(
+                account.owner == &system_program::ID && account.data_is_empty(),
+                CounterError::CounterNotClosed
+            );
+            if !ctx.accounts.leaderboard.remove(account.key) {
+                continue;
+            }
+            msg!(
+                "PDA {} pruned from leaderboard {}",
+                account.key(),
+                leaderboard
+            );
+            let event = ScorePruned {
+                leaderboard,
+                counter: account.key(),
+            };
+            emit_event!(ctx, event);
+        }
+        Ok(())
+    }
+
+    /// Create the treasury collecting the counter's fees
+    pub fn init_treasury(ctx: Context<InitTreasury>, counter_id: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.counter = ctx.accounts.counter.key();
+        treasury.collected = 0;
+        treasury.bump = ctx.bumps.treasury;
+        msg!(
+            "PDA {} (id {}) treasury created at {}",
+            treasury.counter,
+            counter_id,
+            treasury.key()
+        );
+        let event = TreasuryInitialized {
+            counter: treasury.counter,
+            treasury: treasury.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Move `amount` of the collected fees to the recipient
+    /// Either the counter's authority or the program admin may withdraw; the treasury
+    /// keeps enough lamports to stay rent-exempt
+    pub fn withdraw(ctx: Context<Withdraw>, counter_id: u64, amount: u64) -> Result<()> {
+        let recipient = ctx.accounts.recipient.to_account_info();
+        withdraw_fees(&ctx.accounts.treasury.to_account_info(), &recipient, amount)?;
+        msg!(
+            "PDA {} (id {}) withdrew {} to {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            amount,
+            recipient.key()
+        );
+        let event = FeesWithdrawn {
+            counter: ctx.accounts.counter.key(),
+            recipient: recipient.key(),
+            amount,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the payer's fee escrow for a counter and deposit `deposit` lamports in it
+    /// Fees for updates on the ER accrue against the deposit and are settled into the
+    /// treasury on commit and undelegate; like contributions, escrows can be opened
+    /// while the counter is delegated
+    pub fn init_fee_escrow(
+        ctx: Context<InitFeeEscrow>,
+        counter_id: u64,
+        deposit: u64,
+    ) -> Result<()> {
+        let counter_info = &ctx.accounts.counter;
+        let counter = Counter::try_deserialize(&mut &counter_info.try_borrow_data()?[..])?;
+        counter.verify_address(counter_info.key, counter_id)?;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.fee_escrow.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+        let escrow = &mut ctx.accounts.fee_escrow;
+        escrow.counter = counter_info.key();
+        escrow.payer = ctx.accounts.payer.key();
+        escrow.pending = 0;
+        escrow.delegated = false;
+        escrow.bump = ctx.bumps.fee_escrow;
+        msg!(
+            "PDA {} (id {}) fee escrow of {} created with {} lamports",
+            escrow.counter,
+            counter_id,
+            escrow.payer,
+            deposit
+        );
+        let event = FeeEscrowInitialized {
+            counter: escrow.counter,
+            payer: escrow.payer,
+            fee_escrow: escrow.key(),
+            deposit,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Close a settled fee escrow on the base layer, refunding what is left of the deposit
+    pub fn close_fee_escrow(ctx: Context<CloseFeeEscrow>) -> Result<()> {
+        msg!(
+            "Fee escrow {} closed, refunded to {}",
+            ctx.accounts.fee_escrow.key(),
+            ctx.accounts.payer.key()
+        );
+        Ok(())
+    }
+
+    /// Limit what session signers may do with the counter
+    /// The authority itself is never restricted; see SessionScope for the fields
+    pub fn set_session_scope(
+        ctx: Context<SetSessionScope>,
+        counter_id: u64,
+        scope: SessionScope,
+    ) -> Result<()> {
+        scope.validate()?;
         let counter = &mut ctx.accounts.counter;
-        counter.count = value;
-        msg!("PDA {} count: {}", counter.key(), counter.count);
+        counter.session_scope = scope;
+        msg!(
+            "PDA {} (id {}) session scope: restricted {}, permissions {:#05b}, set {}..={}, max uses {}",
+            counter.key(),
+            counter_id,
+            scope.restricted,
+            scope.permissions,
+            scope.set_min,
+            scope.set_max,
+            scope.max_uses
+        );
+        let event = SessionScopeChanged {
+            counter: counter.key(),
+            scope,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the program-wide config with `admin` as the key allowed to pause
+    /// Can only be called once, by the program's upgrade authority
+    pub fn init_config(ctx: Context<InitConfig>, admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = admin;
+        config.paused = false;
+        config.bump = ctx.bumps.config;
+        msg!("Config {} created with admin: {}", config.key(), admin);
+        let event = ConfigUpdated {
+            admin,
+            paused: false,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Pause or resume increment, decrement and set on every counter
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+        msg!("Program paused: {}", paused);
+        let event = ConfigUpdated {
+            admin: config.admin,
+            paused,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Hand the config over to a new admin
+    pub fn set_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = new_admin;
+        msg!("Config admin set to: {}", new_admin);
+        let event = ConfigUpdated {
+            admin: new_admin,
+            paused: config.paused,
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 
@@ -72,72 +1402,359 @@ pub mod counter {
 
     /// Delegate the counter account to the delegation program
     /// Optionally set a specific validator from the first remaining account
+    /// The slot of the last update is dropped, as it is again on undelegate; the rest of
+    /// the rate limit state carries over
+    /// Signed by the authority, or by any one of the signers of the multisig holding it,
+    /// who may then commit and undelegate it as well
     /// See: https://docs.magicblock.gg/pages/get-started/how-integrate-your-program/local-setup
-    pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
+    pub fn delegate(ctx: Context<DelegateInput>, counter_id: u64) -> Result<()> {
+        // The seeds come from the stored counter, so delegation keeps working after a handover
+        let mut counter = Counter::try_deserialize(&mut &ctx.accounts.pda.try_borrow_data()?[..])?;
+        counter.verify_address(ctx.accounts.pda.key, counter_id)?;
+        require!(
+            counter.is_authority_or_member(ctx.accounts.payer.key, ctx.accounts.multisig.as_ref()),
+            CounterError::InvalidAuth
+        );
+        counter.rebase_throttle();
+        counter.try_serialize(&mut &mut ctx.accounts.pda.try_borrow_mut_data()?[..])?;
+        // Optionally set a specific validator from the first remaining account
+        let validator = ctx.remaining_accounts.first().map(|acc| acc.key());
         ctx.accounts.delegate_pda(
             &ctx.accounts.payer,
-            &[ctx.accounts.payer.key().as_ref()],
+            &[counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
             DelegateConfig {
-                // Optionally set a specific validator from the first remaining account
-                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                validator,
                 ..Default::default()
             },
         )?;
+        let event = CounterDelegated {
+            counter: ctx.accounts.pda.key(),
+            authority: counter.authority,
+            validator,
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 
-    /// Manual commit the counter account in the Ephemeral Rollup
-    /// This persists the current state to the base layer
-    pub fn commit(ctx: Context<CommitInput>) -> Result<()> {
-        commit_accounts(
+    /// Delegate the counter's history so the ER can append to it
+    /// Must run while the counter is still on the base layer, e.g. right before delegate
+    /// in the same transaction; the history is then committed and undelegated with it
+    pub fn delegate_history(ctx: Context<DelegateHistory>, counter_id: u64) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        ctx.accounts.delegate_history(
             &ctx.accounts.payer,
-            vec![&ctx.accounts.counter.to_account_info()],
-            &ctx.accounts.magic_context,
-            &ctx.accounts.magic_program,
+            &[HISTORY_SEED, counter.as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
         )?;
+        msg!("PDA {} (id {}) history delegated", counter, counter_id);
+        let event = HistoryDelegated {
+            counter,
+            history: ctx.accounts.history.key(),
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 
-    /// Undelegate the counter account from the delegation program
-    /// This commits and removes the account from the Ephemeral Rollup
-    pub fn undelegate(ctx: Context<CommitInput>) -> Result<()> {
-        commit_and_undelegate_accounts(
+    /// Delegate a session signer's usage account so the ER can count its updates
+    /// Like delegate_history, it must run while the counter is still on the base layer,
+    /// and the usage is then committed and undelegated with the counter
+    pub fn delegate_session_usage(
+        ctx: Context<DelegateSessionUsage>,
+        counter_id: u64,
+        session_signer: Pubkey,
+    ) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        ctx.accounts.delegate_session_usage(
             &ctx.accounts.payer,
-            vec![&ctx.accounts.counter.to_account_info()],
-            &ctx.accounts.magic_context,
-            &ctx.accounts.magic_program,
+            &[
+                SESSION_USAGE_SEED,
+                counter.as_ref(),
+                session_signer.as_ref(),
+            ],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
         )?;
+        msg!(
+            "PDA {} (id {}) session usage of {} delegated",
+            counter,
+            counter_id,
+            session_signer
+        );
+        let event = SessionUsageDelegated {
+            counter,
+            session_usage: ctx.accounts.session_usage.key(),
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
-}
 
-// ========================================
+    /// Delegate the payer's contribution so they can increment a public counter on the ER
+    /// Unlike the history and session usage this works after the counter is delegated,
+    /// so contributors can join at any time; the authority commits and undelegates
+    /// contributions with the counter
+    pub fn delegate_contribution(ctx: Context<DelegateContribution>) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        let contributor = ctx.accounts.payer.key();
+        ctx.accounts.delegate_contribution(
+            &ctx.accounts.payer,
+            &[CONTRIBUTION_SEED, counter.as_ref(), contributor.as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        msg!("PDA {} contribution of {} delegated", counter, contributor);
+        let event = ContributionDelegated {
+            counter,
+            contribution: ctx.accounts.contribution.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Delegate the payer's leaderboard so scores can be submitted on the ER
+    /// With `commit_frequency_ms` the validator commits it on that interval by itself,
+    /// otherwise it is committed through commit_leaderboard
+    pub fn delegate_leaderboard(
+        ctx: Context<DelegateLeaderboard>,
+        commit_frequency_ms: Option<u32>,
+    ) -> Result<()> {
+        let authority = ctx.accounts.payer.key();
+        let defaults = DelegateConfig::default();
+        ctx.accounts.delegate_leaderboard(
+            &ctx.accounts.payer,
+            &[LEADERBOARD_SEED, authority.as_ref()],
+            DelegateConfig {
+                commit_frequency_ms: commit_frequency_ms.unwrap_or(defaults.commit_frequency_ms),
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+            },
+        )?;
+        msg!("Leaderboard of {} delegated", authority);
+        let event = LeaderboardDelegated {
+            leaderboard: ctx.accounts.leaderboard.key(),
+            commit_frequency_ms,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Delegate the counter's treasury so fee escrows can be settled into it on the ER
+    /// Like delegate_history, it must run while the counter is still on the base layer,
+    /// and the treasury is then committed and undelegated with the counter
+    pub fn delegate_treasury(ctx: Context<DelegateTreasury>, counter_id: u64) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        ctx.accounts.delegate_treasury(
+            &ctx.accounts.payer,
+            &[TREASURY_SEED, counter.as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        msg!("PDA {} (id {}) treasury delegated", counter, counter_id);
+        let event = TreasuryDelegated {
+            counter,
+            treasury: ctx.accounts.treasury.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Delegate the payer's fee escrow so their updates on the ER can pay the counter's fee
+    /// Works after the counter is delegated; the authority settles, commits and
+    /// undelegates escrows with the counter
+    pub fn delegate_fee_escrow(ctx: Context<DelegateFeeEscrow>) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        let payer = ctx.accounts.payer.key();
+        {
+            let info = &ctx.accounts.fee_escrow;
+            let mut escrow = FeeEscrow::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            escrow.delegated = true;
+            escrow.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+        ctx.accounts.delegate_fee_escrow(
+            &ctx.accounts.payer,
+            &[FEE_ESCROW_SEED, counter.as_ref(), payer.as_ref()],
+            DelegateConfig {
+                validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
+                ..Default::default()
+            },
+        )?;
+        msg!("PDA {} fee escrow of {} delegated", counter, payer);
+        let event = FeeEscrowDelegated {
+            counter,
+            fee_escrow: ctx.accounts.fee_escrow.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Manual commit the counter account in the Ephemeral Rollup
+    /// This persists the current state to the base layer
+    /// Pass the delegated history, session usage and treasury to commit them alongside
+    /// the counter, and any delegated contributions and fee escrows as remaining accounts
+    /// Fees accrued in the escrows are settled into the treasury first
+    pub fn commit<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitInput<'info>>,
+        counter_id: u64,
+    ) -> Result<()> {
+        let counter = ctx.accounts.load_counter(counter_id)?;
+        let settled = ctx.accounts.settle_fees(ctx.remaining_accounts, false)?;
+        msg!(
+            "Committing PDA {} (id {})",
+            ctx.accounts.counter.key(),
+            counter_id
+        );
+        commit_accounts(
+            &ctx.accounts.payer,
+            ctx.accounts.committed_accounts(ctx.remaining_accounts)?,
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        if settled > 0 {
+            let event = FeesSettled {
+                counter: ctx.accounts.counter.key(),
+                amount: settled,
+            };
+            emit_event!(ctx, event);
+        }
+        let event = CounterCommitted {
+            counter: ctx.accounts.counter.key(),
+            count: counter.count,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Undelegate the counter account from the delegation program
+    /// This commits and removes the account from the Ephemeral Rollup
+    /// Pass the delegated history, session usage and treasury to undelegate them alongside
+    /// the counter, and any delegated contributions and fee escrows as remaining accounts
+    /// Fees accrued in the escrows are settled into the treasury first, and the slot of
+    /// the counter's last update is dropped
+    pub fn undelegate<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitInput<'info>>,
+        counter_id: u64,
+    ) -> Result<()> {
+        let counter = ctx.accounts.load_counter(counter_id)?;
+        ctx.accounts.rebase_throttle()?;
+        let settled = ctx.accounts.settle_fees(ctx.remaining_accounts, true)?;
+        msg!(
+            "Undelegating PDA {} (id {})",
+            ctx.accounts.counter.key(),
+            counter_id
+        );
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            ctx.accounts.committed_accounts(ctx.remaining_accounts)?,
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        if settled > 0 {
+            let event = FeesSettled {
+                counter: ctx.accounts.counter.key(),
+                amount: settled,
+            };
+            emit_event!(ctx, event);
+        }
+        let event = CounterUndelegated {
+            counter: ctx.accounts.counter.key(),
+            count: counter.count,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Commit the leaderboard's ER state to the base layer
+    /// Anyone may commit, it only publishes scores the ER already holds
+    pub fn commit_leaderboard(ctx: Context<CommitLeaderboard>) -> Result<()> {
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![ctx.accounts.leaderboard.as_ref()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Committing leaderboard {}", ctx.accounts.leaderboard.key());
+        let event = LeaderboardCommitted {
+            leaderboard: ctx.accounts.leaderboard.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Commit the leaderboard and return it to the base layer, for its authority only
+    pub fn undelegate_leaderboard(ctx: Context<CommitLeaderboard>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.leaderboard.authority,
+            ctx.accounts.payer.key(),
+            CounterError::InvalidAuth
+        );
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![ctx.accounts.leaderboard.as_ref()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!(
+            "Undelegating leaderboard {}",
+            ctx.accounts.leaderboard.key()
+        );
+        let event = LeaderboardUndelegated {
+            leaderboard: ctx.accounts.leaderboard.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+}
+
+// ========================================
 // Account Structs
 // ========================================
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
+#[instruction(counter_id: u64)]
 pub struct Initialize<'info> {
     #[account(
-        init_if_needed,
+        init,
         payer = authority,
         space = 8 + Counter::INIT_SPACE,
-        seeds = [authority.key().as_ref()],
+        seeds = [authority.key().as_ref(), &counter_id.to_le_bytes()],
         bump
     )]
     pub counter: Account<'info, Counter>,
 
+    /// Pays for the counter, and becomes its authority unless a multisig is created
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// Created, and made the counter's authority, when initialize is passed a multisig
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts, Session)]
+#[instruction(counter_id: u64)]
 pub struct Update<'info> {
     #[account(
         mut,
-        seeds = [counter.authority.key().as_ref()],
-        bump
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = !counter.frozen @ CounterError::Paused
     )]
     pub counter: Account<'info, Counter>,
 
@@ -146,41 +1763,2835 @@ pub struct Update<'info> {
 
     #[session(signer = signer, authority = counter.authority.key())]
     pub session_token: Option<Account<'info, SessionToken>>,
+
+    /// Holds the program-wide pause, counted as unpaused when left out
+    /// (as before init_config has run)
+    /// Read-only here, so the ER keeps following the base layer copy
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ CounterError::Paused
+    )]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Appended to on every change when present
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    /// Read-only, so the ER clones it from the base layer without delegation
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    /// Required from session signers once the counter's session scope caps their uses
+    #[account(
+        mut,
+        seeds = [SESSION_USAGE_SEED, counter.key().as_ref(), signer.key().as_ref()],
+        bump = session_usage.bump
+    )]
+    pub session_usage: Option<Account<'info, SessionUsage>>,
+
+    /// Required from signers incrementing a public counter they hold no other rights on
+    #[account(
+        mut,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), signer.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Option<Account<'info, Contribution>>,
+
+    /// Receives the fee of increments and sets while the counter charges one
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Pays the fee in the signer's place; on the ER, where the signer's own lamports
+    /// can't be moved, the fee accrues against it until the escrow is settled
+    #[account(
+        mut,
+        seeds = [FEE_ESCROW_SEED, counter.key().as_ref(), signer.key().as_ref()],
+        bump = fee_escrow.bump
+    )]
+    pub fee_escrow: Option<Account<'info, FeeEscrow>>,
+
+    /// Required for increments while the counter is token gated, an SPL Token or
+    /// Token-2022 account of the gate mint owned by the signer
+    /// Read-only, so the ER clones it from the base layer without delegation
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
 }
 
-/// Account context for delegating the counter PDA
-/// The #[delegate] macro adds necessary accounts for delegation
-#[delegate]
+impl Update<'_> {
+    /// Check that an incrementing signer holds enough of the counter's gate mint,
+    /// through the authority's token account when acting with a session key
+    fn check_gate(&self, op: CounterOp) -> Result<()> {
+        let counter = &self.counter;
+        if counter.gate_mint == Pubkey::default() || op != CounterOp::Increment {
+            return Ok(());
+        }
+        let token = self
+            .gate_token_account
+            .as_ref()
+            .ok_or(CounterError::GateTokenRequired)?;
+        let holder = if self.session_token.is_some() {
+            counter.authority
+        } else {
+            self.signer.key()
+        };
+        require!(
+            token.mint == counter.gate_mint && token.owner == holder,
+            CounterError::InvalidGateToken
+        );
+        require!(
+            token.amount >= counter.min_balance.max(1),
+            CounterError::GateBalanceTooLow
+        );
+        Ok(())
+    }
+
+    /// Whether the signer may increment without a session token: the authority, a
+    /// member of its multisig, the caller program's PDA, or anyone on a public counter
+    fn may_increment(&self) -> bool {
+        self.may_update() || self.counter.public || self.is_caller_signer()
+    }
+
+    /// Whether the signer may decrement and set without a session token: the authority
+    /// or a member of its multisig, held to the update policy in apply
+    fn may_update(&self) -> bool {
+        self.counter
+            .is_authority_or_member(&self.signer.key(), self.multisig.as_ref())
+    }
+
+    /// Check that a multisig member, rather than the authority itself, may `op`
+    /// without a proposal
+    fn check_multisig(&self, op: CounterOp) -> Result<()> {
+        let signer = self.signer.key();
+        if let Some(multisig) = self.multisig.as_ref().filter(|multisig| {
+            multisig.key() == self.counter.authority && multisig.is_signer(&signer)
+        }) {
+            require!(
+                multisig.config.allows_direct(op),
+                CounterError::MultisigProposalRequired
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the signer is the caller program's PDA for this counter, which can
+    /// only sign through CPI from that program
+    fn is_caller_signer(&self) -> bool {
+        self.counter
+            .is_caller_signer(&self.counter.key(), &self.signer.key())
+    }
+
+    /// Credit `amount` to the signer's contribution when one was passed
+    /// Signers who only got in because the counter is public have to pass one
+    fn contribute(&mut self, amount: u64) -> Result<()> {
+        match &mut self.contribution {
+            Some(contribution) => contribution.record(amount),
+            None => require!(
+                self.may_update() || self.session_token.is_some() || self.is_caller_signer(),
+                CounterError::ContributionRequired
+            ),
+        }
+        Ok(())
+    }
+
+    /// Run `update` on the counter, log the new value and describe the change as an event
+    fn apply(
+        &mut self,
+        counter_id: u64,
+        op: CounterOp,
+        update: impl FnOnce(&mut Counter) -> Result<()>,
+    ) -> Result<CounterChanged> {
+        if self.session_token.is_none() {
+            self.check_multisig(op)?;
+        }
+        self.check_gate(op)?;
+        charge_fee(
+            &self.counter,
+            op,
+            &self.signer,
+            self.treasury.as_mut(),
+            self.fee_escrow.as_mut(),
+            &self.system_program,
+        )?;
+        let session_valid_until = self.session_token.as_ref().map(|token| token.valid_until);
+        // Session tokens are issued by the authority, so their signers accrue for it
+        let accrues_to_counter =
+            self.signer.key() == self.counter.authority || session_valid_until.is_some();
+        let session_usage = &mut self.session_usage;
+        let contribution = &mut self.contribution;
+        let event = write_count(
+            &mut self.counter,
+            self.history.as_ref(),
+            counter_id,
+            op,
+            self.signer.key(),
+            |counter| {
+                update(counter)?;
+                if op == CounterOp::Increment && counter.reward_amount > 0 {
+                    // Rewards go to the signer's contribution, the authority's pool
+                    // when the authority increments without one
+                    let pending = match contribution.as_mut() {
+                        Some(contribution) => &mut contribution.pending_rewards,
+                        None if accrues_to_counter => &mut counter.pending_rewards,
+                        None => return err!(CounterError::RewardContributionRequired),
+                    };
+                    *pending = pending.saturating_add(counter.reward_amount);
+                }
+                if let Some(valid_until) = session_valid_until {
+                    let scope = counter.session_scope;
+                    scope.check(op, counter.count)?;
+                    if scope.caps_uses() {
+                        session_usage
+                            .as_mut()
+                            .ok_or(CounterError::SessionUsageRequired)?
+                            .record(valid_until, scope.max_uses)?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(CounterChanged {
+            via_session: session_valid_until.is_some(),
+            ..event
+        })
+    }
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct DelegateInput<'info> {
+#[instruction(counter_id: u64)]
+pub struct InitHistory<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CounterHistory>(),
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, CounterHistory>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, session_signer: Pubkey)]
+pub struct InitSessionUsage<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SessionUsage::INIT_SPACE,
+        seeds = [SESSION_USAGE_SEED, counter.key().as_ref(), session_signer.as_ref()],
+        bump
+    )]
+    pub session_usage: Account<'info, SessionUsage>,
+
+    /// Anyone may pay, the usage starts out empty either way
+    #[account(mut)]
     pub payer: Signer<'info>,
-    /// CHECK: The PDA to delegate - validated by seeds constraint
-    #[account(mut, del, seeds = [payer.key().as_ref()], bump)]
-    pub pda: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
-/// Account context for commit and undelegate operations
-/// The #[commit] macro adds magic_context and magic_program accounts
-#[commit]
+/// Account context for closing the counter PDA
+/// The counter is taken unchecked so a delegated account (owned by the delegation
+/// program) is reported as CounterDelegated instead of an owner mismatch
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-pub struct CommitInput<'info> {
+#[instruction(counter_id: u64)]
+pub struct Close<'info> {
+    /// CHECK: Owner is validated by the constraints below, seeds and data in the handler
+    #[account(
+        mut,
+        constraint = counter.owner != &ephemeral_rollups_sdk::id() @ CounterError::CounterDelegated,
+        owner = crate::ID
+    )]
+    pub counter: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Any account may receive the reclaimed rent
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's history PDA, closed in the handler if it was created
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump,
+        constraint = history.owner != &ephemeral_rollups_sdk::id() @ CounterError::CounterDelegated
+    )]
+    pub history: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's treasury PDA, closed in the handler if it was created
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump,
+        constraint = treasury.owner != &ephemeral_rollups_sdk::id() @ CounterError::CounterDelegated
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's pending set PDA, closed in the handler if a set is pending
+    #[account(mut, seeds = [PENDING_SET_SEED, counter.key().as_ref()], bump)]
+    pub pending_set: UncheckedAccount<'info>,
+
+    /// CHECK: The multisig PDA of a counter it once held, closed in the handler if it was
+    /// created
+    #[account(mut, seeds = [MULTISIG_SEED, counter.key().as_ref()], bump)]
+    pub multisig: UncheckedAccount<'info>,
+}
+
+/// Move all lamports of `account` to `recipient` and hand it back to the system program
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> Result<()> {
+    let lamports = account.lamports();
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Close the session usages, contributions and fee escrows of `counter` among `accounts`
+/// Session usages return their rent to `recipient`, contributions to their
+/// contributor and escrows to their payer, whose account follows each of them
+/// Fees an escrow still owes the treasury go to `recipient`
+fn close_dependents<'info>(
+    counter: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    let mut accounts = accounts.iter();
+    while let Some(info) = accounts.next() {
+        require!(
+            info.owner != &ephemeral_rollups_sdk::id(),
+            CounterError::CounterDelegated
+        );
+        require_keys_eq!(
+            *info.owner,
+            crate::ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let data = info.try_borrow_data()?;
+        let refund = if data.starts_with(Contribution::DISCRIMINATOR) {
+            let contribution = Contribution::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(
+                contribution.counter,
+                *counter,
+                CounterError::InvalidContribution
+            );
+            let contributor = accounts.next().ok_or(ErrorCode::AccountNotEnoughKeys)?;
+            require_keys_eq!(
+                contribution.contributor,
+                contributor.key(),
+                CounterError::InvalidContribution
+            );
+            contributor
+        } else if data.starts_with(FeeEscrow::DISCRIMINATOR) {
+            let escrow = FeeEscrow::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(escrow.counter, *counter, CounterError::InvalidFeeEscrow);
+            let payer = accounts.next().ok_or(ErrorCode::AccountNotEnoughKeys)?;
+            require_keys_eq!(escrow.payer, payer.key(), CounterError::InvalidFeeEscrow);
+            move_lamports(info, recipient, escrow.pending)?;
+            payer
+        } else {
+            let usage = SessionUsage::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(usage.counter, *counter, CounterError::InvalidSessionUsage);
+            recipient
+        };
+        drop(data);
+        close_account(info, refund)?;
+    }
+    Ok(())
+}
+
+/// Account context for migrating a counter to the current layout
+/// The counter is taken unchecked since older layouts don't deserialize as Counter
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct Migrate<'info> {
+    /// CHECK: Owner is validated by the constraints below, discriminator and seeds in the handler
+    #[account(
+        mut,
+        constraint = counter.owner != &ephemeral_rollups_sdk::id() @ CounterError::CounterDelegated,
+        owner = crate::ID
+    )]
+    pub counter: UncheckedAccount<'info>,
+
+    /// The new address of a counter in the original layout, which is closed into it
+    /// Only passed for such counters, by their authority
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [payer.key().as_ref(), &counter_id.to_le_bytes()],
+        bump
+    )]
+    pub relocated: Option<Account<'info, Counter>>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(mut, seeds = [payer.key().as_ref()], bump)]
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
     pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
 }
 
-// ========================================
-// Account Data
-// ========================================
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.pending_authority == new_authority.key() @ CounterError::NotPendingAuthority
+    )]
+    pub counter: Account<'info, Counter>,
 
-#[account]
-#[derive(InitSpace)]
-pub struct Counter {
-    /// The current count value
-    pub count: u64,
-    /// The authority who can update the counter
-    pub authority: Pubkey,
+    pub new_authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct CreateProposal<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// A proposal left at this index by a multisig closed with an earlier counter at the
+    /// same address is overwritten
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [
+            PROPOSAL_SEED,
+            multisig.key().as_ref(),
+            &multisig.proposal_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// One of the multisig's signers
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, index: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), &index.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = index < multisig.proposal_count @ CounterError::StaleProposal
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// One of the multisig's signers
+    pub member: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, index: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    /// Must still hold the authority, so proposals die with a handover
+    /// Receives the rent of a cancelled set and is closed by Close
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), &index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        constraint = index < multisig.proposal_count @ CounterError::StaleProposal
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal's rent, checked against the proposal
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Counted as unpaused when left out, as in Update
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Pays the counter's fee on count changes, as the signer of an update does
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// Appended to by count changes when present, as in Update
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Receives the fee of count changes while the counter charges one, and pays out
+    /// Withdraw
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Required by ScheduleSet, which creates it if needed, and CancelSet
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = 8 + PendingSet::INIT_SPACE,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub pending_set: Option<Account<'info, PendingSet>>,
+
+    /// CHECK: Required by Withdraw and Close, checked against the proposal's recipient
+    #[account(mut)]
+    pub recipient: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl ExecuteProposal<'_> {
+    /// Apply a count change the way Update does, held back by a freeze or pause
+    fn apply(
+        &mut self,
+        counter_id: u64,
+        op: CounterOp,
+        update: impl FnOnce(&mut Counter) -> Result<()>,
+    ) -> Result<CounterChanged> {
+        require!(
+            !self.counter.frozen && !self.config.as_ref().is_some_and(|config| config.paused),
+            CounterError::Paused
+        );
+        charge_fee(
+            &self.counter,
+            op,
+            &self.executor,
+            self.treasury.as_mut(),
+            None,
+            &self.system_program,
+        )?;
+        write_count(
+            &mut self.counter,
+            self.history.as_ref(),
+            counter_id,
+            op,
+            self.multisig.key(),
+            update,
+        )
+    }
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ScheduleSet<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingSet::INIT_SPACE,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ExecuteSet<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = !counter.frozen @ CounterError::Paused
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump = pending_set.bump,
+        has_one = authority
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    /// CHECK: Receives the pending set's rent, checked against the pending set
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Counted as unpaused when left out, as in Update
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ CounterError::Paused
+    )]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Pays the counter's fee, as the signer of an update does
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// Appended to when present, as in Update
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Receives the fee while the counter charges one, as in Update
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct CancelSet<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump = pending_set.bump
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetFrozen<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetRateLimit<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetResetPeriod<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetPublic<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetTokenGate<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetCallerProgram<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, decimals: u8)]
+pub struct InitRewardMint<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [REWARD_MINT_SEED, counter.key().as_ref()],
+        bump,
+        mint::decimals = decimals,
+        mint::authority = reward_mint,
+        mint::token_program = token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub token_program: Program<'info, Token2022>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetReward<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct MintRewards<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut, address = counter.reward_mint @ CounterError::InvalidRewardAccount)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Whose rewards to mint, the counter's own when omitted
+    #[account(
+        mut,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), contribution.contributor.as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Option<Account<'info, Contribution>>,
+
+    /// The contributor's Token-2022 account of the reward mint, or the counter
+    /// authority's without a contribution
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::token_program = token_program,
+        constraint = recipient.owner
+            == contribution.as_ref().map_or(counter.authority, |contribution| contribution.contributor)
+            @ CounterError::InvalidRewardAccount
+    )]
+    pub recipient: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+/// The counter is taken unchecked so contributors can join one already delegated to
+/// the ER; the handler checks its address and that it is public
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitContribution<'info> {
+    /// CHECK: Seeds and data are validated in the handler
+    #[account(
+        constraint = counter.owner == &crate::ID
+            || counter.owner == &ephemeral_rollups_sdk::id() @ ErrorCode::AccountOwnedByWrongProgram
+    )]
+    pub counter: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = contributor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), contributor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    /// Required when the contributor is a member of the multisig holding the authority
+    #[account(seeds = [MULTISIG_SEED, counter.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitLeaderboard<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account<Counter> checks the counter is owned by this program, its seeds that it
+/// is the counter it claims to be; the leaderboard's authority picks what gets ranked
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SubmitScore<'info> {
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, leaderboard.authority.as_ref()],
+        bump = leaderboard.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct PruneLeaderboard<'info> {
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, leaderboard.authority.as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitTreasury<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct Withdraw<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Only needed for the admin to withdraw
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// The counter's authority or the program admin
+    #[account(
+        constraint = signer.key() == counter.authority
+            || config.as_ref().is_some_and(|config| signer.key() == config.admin)
+            @ CounterError::InvalidAuth
+    )]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Any account may receive the withdrawn lamports
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Check the rate limit, run `update` on the counter, bump its sequence and record
+/// the change in `history` when passed: the path every write to the count takes,
+/// from Update and execute_set alike
+fn write_count(
+    counter: &mut Account<Counter>,
+    history: Option<&AccountLoader<CounterHistory>>,
+    counter_id: u64,
+    op: CounterOp,
+    signer: Pubkey,
+    update: impl FnOnce(&mut Counter) -> Result<()>,
+) -> Result<CounterChanged> {
+    let clock = Clock::get()?;
+    counter.throttle(&clock)?;
+    let old = counter.count;
+    counter.roll_period(&clock);
+    update(counter)?;
+    counter.bump_sequence();
+    msg!(
+        "PDA {} (id {}) count: {}",
+        counter.key(),
+        counter_id,
+        counter.count
+    );
+    if let Some(history) = history {
+        history.load_mut()?.push(HistoryEntry {
+            slot: clock.slot,
+            unix_ts: clock.unix_timestamp,
+            signer,
+            old,
+            new: counter.count,
+            op: op as u8,
+            _padding: [0; 7],
+        });
+    }
+    Ok(CounterChanged {
+        counter: counter.key(),
+        old,
+        new: counter.count,
+        sequence: counter.sequence,
+        op,
+        signer,
+        via_session: false,
+    })
+}
+
+/// Take the counter's fee for `op` from `payer` into its treasury, or from their fee
+/// escrow when passed
+/// A delegated escrow only accrues the fee, to be settled on commit; on the base
+/// layer the escrow pays the treasury right away
+fn charge_fee<'info>(
+    counter: &Counter,
+    op: CounterOp,
+    payer: &Signer<'info>,
+    treasury: Option<&mut Account<'info, Treasury>>,
+    fee_escrow: Option<&mut Account<'info, FeeEscrow>>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let fee = counter.fee;
+    if fee == 0 || op == CounterOp::Decrement {
+        return Ok(());
+    }
+    let escrow = match fee_escrow {
+        Some(escrow) => {
+            let info = escrow.to_account_info();
+            let reserve = Rent::get()?.minimum_balance(info.data_len());
+            let available = info.lamports().saturating_sub(reserve);
+            if escrow.delegated {
+                return escrow.accrue(fee, available);
+            }
+            require!(
+                escrow.pending.saturating_add(fee) <= available,
+                CounterError::InsufficientFunds
+            );
+            Some(info)
+        }
+        None => None,
+    };
+    let treasury = treasury.ok_or(CounterError::TreasuryRequired)?;
+    match escrow {
+        Some(escrow) => move_lamports(&escrow, &treasury.to_account_info(), fee)?,
+        None => system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )?,
+    }
+    treasury.collected = treasury.collected.saturating_add(fee);
+    Ok(())
+}
+
+/// Move `amount` of the fees collected in `treasury` to `recipient`, keeping the
+/// treasury rent-exempt
+fn withdraw_fees(treasury: &AccountInfo, recipient: &AccountInfo, amount: u64) -> Result<()> {
+    let reserve = Rent::get()?.minimum_balance(treasury.data_len());
+    require!(
+        treasury.lamports().saturating_sub(reserve) >= amount,
+        CounterError::InsufficientFunds
+    );
+    move_lamports(treasury, recipient, amount)
+}
+
+/// Move lamports out of an account owned by this program
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// The counter is taken unchecked so escrows can be opened for one already delegated
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitFeeEscrow<'info> {
+    /// CHECK: Seeds and data are validated in the handler
+    #[account(
+        constraint = counter.owner == &crate::ID
+            || counter.owner == &ephemeral_rollups_sdk::id() @ ErrorCode::AccountOwnedByWrongProgram
+    )]
+    pub counter: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + FeeEscrow::INIT_SPACE,
+        seeds = [FEE_ESCROW_SEED, counter.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub fee_escrow: Account<'info, FeeEscrow>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CloseFeeEscrow<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ CounterError::InvalidAuth,
+        constraint = fee_escrow.pending == 0 @ CounterError::FeesUnsettled
+    )]
+    pub fee_escrow: Account<'info, FeeEscrow>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetSessionScope<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Account context for creating the program config
+/// The upgrade authority is read from the program's ProgramData account
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ CounterError::InvalidAuth
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ CounterError::InvalidAuth
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Account context for delegating the counter PDA
+/// The #[delegate] macro adds necessary accounts for delegation
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct DelegateInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The PDA to delegate - validated against its stored seeds in the handler
+    #[account(mut, del, owner = crate::ID)]
+    pub pda: AccountInfo<'info>,
+    /// Required when the payer is a member of the multisig holding the authority
+    #[account(seeds = [MULTISIG_SEED, pda.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Option<Account<'info, Multisig>>,
+}
+
+/// Account context for delegating a counter's history PDA
+/// The counter must still be owned by this program, so run it before delegate
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct DelegateHistory<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&payer.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+    /// CHECK: The history PDA to delegate - validated by its seeds
+    #[account(mut, del, seeds = [HISTORY_SEED, counter.key().as_ref()], bump, owner = crate::ID)]
+    pub history: AccountInfo<'info>,
+    /// Required when the payer is a member of the multisig holding the authority
+    #[account(seeds = [MULTISIG_SEED, counter.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Option<Account<'info, Multisig>>,
+}
+
+/// Account context for delegating a session signer's usage PDA
+/// The counter must still be owned by this program, so run it before delegate
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, session_signer: Pubkey)]
+pub struct DelegateSessionUsage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == payer.key() @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+    /// CHECK: The session usage PDA to delegate - validated by its seeds
+    #[account(
+        mut,
+        del,
+        seeds = [SESSION_USAGE_SEED, counter.key().as_ref(), session_signer.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub session_usage: AccountInfo<'info>,
+}
+
+/// Account context for delegating a contributor's Contribution PDA
+/// The counter only serves as a seed, so it may already be delegated itself
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DelegateContribution<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Only used as a seed of the contribution, which was tied to it at creation
+    pub counter: UncheckedAccount<'info>,
+    /// CHECK: The payer's contribution PDA to delegate - validated by its seeds
+    #[account(
+        mut,
+        del,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub contribution: AccountInfo<'info>,
+}
+
+/// Account context for delegating an authority's Leaderboard PDA
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DelegateLeaderboard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The payer's leaderboard PDA to delegate - validated by its seeds
+    #[account(
+        mut,
+        del,
+        seeds = [LEADERBOARD_SEED, payer.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub leaderboard: AccountInfo<'info>,
+}
+
+/// Account context for delegating a counter's treasury PDA
+/// The counter must still be owned by this program, so run it before delegate
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct DelegateTreasury<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&payer.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+    /// CHECK: The treasury PDA to delegate - validated by its seeds
+    #[account(mut, del, seeds = [TREASURY_SEED, counter.key().as_ref()], bump, owner = crate::ID)]
+    pub treasury: AccountInfo<'info>,
+    /// Required when the payer is a member of the multisig holding the authority
+    #[account(seeds = [MULTISIG_SEED, counter.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Option<Account<'info, Multisig>>,
+}
+
+/// Account context for delegating a payer's FeeEscrow PDA
+/// The counter only serves as a seed, so it may already be delegated itself
+#[delegate]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct DelegateFeeEscrow<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Only used as a seed of the escrow, which was tied to it at creation
+    pub counter: UncheckedAccount<'info>,
+    /// CHECK: The payer's fee escrow PDA to delegate - validated by its seeds
+    #[account(
+        mut,
+        del,
+        seeds = [FEE_ESCROW_SEED, counter.key().as_ref(), payer.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub fee_escrow: AccountInfo<'info>,
+}
+
+/// Account context for commit and undelegate operations
+/// The #[commit] macro adds magic_context and magic_program accounts
+/// The counter is taken unchecked so one delegated before an upgrade, still in an
+/// older layout, can be brought back to the base layer and migrated there
+#[commit]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct CommitInput<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Owner is validated below, seeds and authority by load_counter
+    #[account(mut, owner = crate::ID)]
+    pub counter: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+    #[account(
+        mut,
+        seeds = [
+            SESSION_USAGE_SEED,
+            counter.key().as_ref(),
+            session_usage.session_signer.as_ref()
+        ],
+        bump = session_usage.bump
+    )]
+    pub session_usage: Option<Account<'info, SessionUsage>>,
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+    /// Required when the payer is a member of the multisig holding the authority
+    #[account(seeds = [MULTISIG_SEED, counter.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Option<Account<'info, Multisig>>,
+}
+
+impl<'info> CommitInput<'info> {
+    /// Decode the counter in whichever layout it is stored and check it against
+    /// its seeds and the payer
+    /// A counter in the original layout, delegated by an earlier program version, is
+    /// checked against the seeds of its authority alone
+    fn load_counter(&self, counter_id: u64) -> Result<Counter> {
+        let (version, counter) = Counter::upgrade(&self.counter.try_borrow_data()?)?;
+        if version == 0 {
+            require_keys_eq!(
+                Counter::baseline_address(&counter.authority),
+                self.counter.key(),
+                ErrorCode::ConstraintSeeds
+            );
+        } else {
+            counter.verify_address(self.counter.key, counter_id)?;
+        }
+        require!(
+            counter.is_authority_or_member(self.payer.key, self.multisig.as_ref()),
+            CounterError::InvalidAuth
+        );
+        Ok(counter)
+    }
+
+    /// Drop the slot of the counter's last update for the base layer, whose slots don't
+    /// line up with the ER's
+    /// A counter still in an older layout is left as is, migrate fills the state in
+    fn rebase_throttle(&self) -> Result<()> {
+        let mut data = self.counter.try_borrow_mut_data()?;
+        if Counter::stored_version(&data) == Counter::VERSION {
+            let mut counter = Counter::try_deserialize(&mut &data[..])?;
+            counter.rebase_throttle();
+            counter.try_serialize(&mut &mut data[..])?;
+        }
+        Ok(())
+    }
+
+    /// Move the fees accrued in the `escrows` among the remaining accounts into the
+    /// treasury, returning the amount settled
+    /// The treasury is written back right away so the commit picks up its new total;
+    /// when `undelegating`, the escrows are marked as back on the base layer
+    fn settle_fees(&mut self, escrows: &[AccountInfo<'info>], undelegating: bool) -> Result<u64> {
+        let mut settled = 0u64;
+        for info in escrows {
+            if !info
+                .try_borrow_data()?
+                .starts_with(FeeEscrow::DISCRIMINATOR)
+            {
+                continue;
+            }
+            require_keys_eq!(*info.owner, crate::ID, CounterError::InvalidFeeEscrow);
+            let mut escrow = FeeEscrow::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(
+                escrow.counter,
+                self.counter.key(),
+                CounterError::InvalidFeeEscrow
+            );
+            if escrow.pending == 0 && !undelegating {
+                continue;
+            }
+            if escrow.pending > 0 {
+                let treasury = self
+                    .treasury
+                    .as_mut()
+                    .ok_or(CounterError::TreasuryRequired)?;
+                move_lamports(info, &treasury.to_account_info(), escrow.pending)?;
+                treasury.collected = treasury.collected.saturating_add(escrow.pending);
+                settled = settled.saturating_add(escrow.pending);
+                escrow.pending = 0;
+            }
+            escrow.delegated &= !undelegating;
+            escrow.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+        if let Some(treasury) = &self.treasury {
+            treasury.exit(&crate::ID)?;
+        }
+        Ok(settled)
+    }
+
+    /// The counter, plus its history, session usage and treasury when they were passed
+    /// and the contributions and fee escrows among the `remaining` accounts
+    fn committed_accounts<'a>(
+        &'a self,
+        remaining: &'a [AccountInfo<'info>],
+    ) -> Result<Vec<&'a AccountInfo<'info>>> {
+        let mut accounts = vec![self.counter.as_ref()];
+        if let Some(history) = &self.history {
+            accounts.push(history.as_ref());
+        }
+        if let Some(session_usage) = &self.session_usage {
+            accounts.push(session_usage.as_ref());
+        }
+        if let Some(treasury) = &self.treasury {
+            accounts.push(treasury.as_ref());
+        }
+        for info in remaining {
+            let data = info.try_borrow_data()?;
+            let (counter, error) = if data.starts_with(FeeEscrow::DISCRIMINATOR) {
+                let escrow = FeeEscrow::try_deserialize(&mut &data[..])?;
+                (escrow.counter, CounterError::InvalidFeeEscrow)
+            } else {
+                let contribution = Contribution::try_deserialize(&mut &data[..])?;
+                (contribution.counter, CounterError::InvalidContribution)
+            };
+            require_keys_eq!(*info.owner, crate::ID, error);
+            require_keys_eq!(counter, self.counter.key(), error);
+            accounts.push(info);
+        }
+        Ok(accounts)
+    }
+}
+
+/// Account context for committing or undelegating a leaderboard
+/// The #[commit] macro adds magic_context and magic_program accounts
+#[commit]
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CommitLeaderboard<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, leaderboard.authority.as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+// ========================================
+// Account Data
+// ========================================
+
+// The account traits are implemented by hand rather than with `#[account]` so that
+// a counter still in an older layout fails with AccountNotMigrated instead of a
+// generic deserialization error
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Counter {
+    /// Layout version of the account, see Counter::VERSION
+    pub version: u8,
+    /// The current count value
+    pub count: u64,
+    /// The authority who can update the counter
+    pub authority: Pubkey,
+    /// Id distinguishing this counter among the authority's counters (part of the PDA seeds)
+    pub counter_id: u64,
+    /// The original authority whose key is part of the PDA seeds
+    /// Kept separately so the address stays stable after an authority handover
+    pub seed_authority: Pubkey,
+    /// The canonical bump of the counter PDA
+    pub bump: u8,
+    /// Authority proposed via propose_authority, waiting for accept_authority
+    /// Pubkey::default() when no transfer is pending
+    pub pending_authority: Pubkey,
+    /// Lowest value the counter may hold
+    pub min: u64,
+    /// Highest value the counter may hold
+    pub max: u64,
+    /// What happens when an update would leave `min..=max`
+    pub overflow_policy: OverflowPolicy,
+    /// Set by the authority to reject increment, decrement and set
+    pub frozen: bool,
+    /// Bumped by every write to `count`, so clients can tell whether the value they
+    /// read is still current
+    pub sequence: u64,
+    /// What session signers may do with the counter
+    pub session_scope: SessionScope,
+    /// How often the counter may be updated, all zeroes for no limit
+    pub rate_limit: RateLimit,
+    /// Slot of the last increment, decrement or set
+    pub last_update_slot: u64,
+    /// Unix timestamp of the last increment, decrement or set
+    pub last_update_ts: i64,
+    /// Unix timestamp the current rate limit window started at
+    pub window_start: i64,
+    /// Updates made in the current rate limit window
+    pub window_count: u64,
+    /// How often the count resets, Never unless set via set_reset_period
+    pub reset_period: ResetPeriod,
+    /// Start of the reset period the count belongs to, see ResetPeriod::period_start
+    pub period_start: i64,
+    /// Final count of the previous reset period
+    pub last_period_count: u64,
+    /// Whether any signer may increment, see set_public
+    pub public: bool,
+    /// Lamports charged for every increment and set, see set_fee
+    pub fee: u64,
+    /// Mint whose holders may increment, Pubkey::default() for none, see set_token_gate
+    pub gate_mint: Pubkey,
+    /// Balance of gate_mint an incrementing signer must hold, at least 1
+    pub min_balance: u64,
+    /// Token-2022 mint increments are rewarded in, Pubkey::default() for none,
+    /// see init_reward_mint
+    pub reward_mint: Pubkey,
+    /// Reward tokens accrued on every increment, see set_reward
+    pub reward_amount: u64,
+    /// Bump of the reward mint, which signs as its own mint authority
+    pub reward_bump: u8,
+    /// Rewards accrued by increments made without a contribution and not minted yet,
+    /// due to the authority, see mint_rewards
+    pub pending_rewards: u64,
+    /// Program whose caller signer PDA may increment, Pubkey::default() for none,
+    /// see set_caller_program
+    pub caller_program: Pubkey,
+}
+
+/// Instructions and values open to session signers, set via set_session_scope
+/// All zeroes (the default) leaves sessions unrestricted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct SessionScope {
+    /// Whether session signers are limited to the rest of this scope
+    pub restricted: bool,
+    /// Mask of SessionScope::INCREMENT, SessionScope::DECREMENT and SessionScope::SET
+    pub permissions: u8,
+    /// Lowest value set and compare_and_set may write through a session
+    pub set_min: u64,
+    /// Highest value set and compare_and_set may write through a session
+    pub set_max: u64,
+    /// Most updates a single session may perform before it has to be renewed,
+    /// counted in its SessionUsage account; 0 for no cap
+    pub max_uses: u64,
+}
+
+impl SessionScope {
+    /// Allows increment and increment_by
+    pub const INCREMENT: u8 = 1 << 0;
+    /// Allows decrement and decrement_by
+    pub const DECREMENT: u8 = 1 << 1;
+    /// Allows set and compare_and_set within `set_min..=set_max`
+    pub const SET: u8 = 1 << 2;
+    /// Every permission
+    pub const ALL: u8 = Self::INCREMENT | Self::DECREMENT | Self::SET;
+
+    /// Reject unknown permission bits and an empty set range
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.permissions & !Self::ALL == 0,
+            CounterError::InvalidPermissions
+        );
+        require!(self.set_min <= self.set_max, CounterError::InvalidBounds);
+        Ok(())
+    }
+
+    /// Whether session signers have to count their updates in a SessionUsage account
+    pub fn caps_uses(&self) -> bool {
+        self.restricted && self.max_uses != 0
+    }
+
+    /// Check that a session signer may perform `op`, leaving the counter at `new`
+    pub fn check(&self, op: CounterOp, new: u64) -> Result<()> {
+        if !self.restricted {
+            return Ok(());
+        }
+        let permission = op.permission();
+        require!(
+            self.permissions & permission != 0,
+            CounterError::SessionScopeDenied
+        );
+        if permission == Self::SET {
+            require!(
+                (self.set_min..=self.set_max).contains(&new),
+                CounterError::SessionScopeDenied
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Fixed-size ring buffer of the most recent changes to a counter
+#[account(zero_copy)]
+pub struct CounterHistory {
+    /// The counter whose changes are recorded
+    pub counter: Pubkey,
+    /// Number of entries ever appended; the oldest are overwritten once `entries` is full
+    pub total: u64,
+    /// The canonical bump of the history PDA
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    /// Entry `i` of the ring lives at `entries[i % entries.len()]`
+    pub entries: [HistoryEntry; 32],
+}
+
+impl CounterHistory {
+    /// Append `entry`, overwriting the oldest one when the buffer is full
+    pub fn push(&mut self, entry: HistoryEntry) {
+        let index = (self.total % self.entries.len() as u64) as usize;
+        self.entries[index] = entry;
+        self.total += 1;
+    }
+}
+
+/// Updates performed by one session signer on a counter, a PDA per (counter, signer)
+#[account]
+#[derive(InitSpace)]
+pub struct SessionUsage {
+    /// The counter the updates were made to
+    pub counter: Pubkey,
+    /// The session signer whose updates are counted
+    pub session_signer: Pubkey,
+    /// Expiry of the session token the count belongs to
+    pub valid_until: i64,
+    /// Updates performed with that session token
+    pub used: u64,
+    /// The canonical bump of the session usage PDA
+    pub bump: u8,
+}
+
+impl SessionUsage {
+    /// Count one update by the session token expiring at `valid_until`, failing once
+    /// `max_uses` is reached
+    /// A renewed token carries a new expiry, which starts the count over
+    pub fn record(&mut self, valid_until: i64, max_uses: u64) -> Result<()> {
+        if self.valid_until != valid_until {
+            self.valid_until = valid_until;
+            self.used = 0;
+        }
+        require!(self.used < max_uses, CounterError::SessionQuotaExceeded);
+        self.used += 1;
+        Ok(())
+    }
+}
+
+/// A contributor's share of a public counter, a PDA per (counter, contributor)
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    /// The counter contributed to
+    pub counter: Pubkey,
+    /// The signer whose increments are tracked
+    pub contributor: Pubkey,
+    /// Sum of the amounts the contributor incremented by
+    pub amount: u64,
+    /// Number of increments the contributor made
+    pub updates: u64,
+    /// Rewards accrued by the contributor's increments and not minted yet, see
+    /// mint_rewards
+    pub pending_rewards: u64,
+    /// The canonical bump of the contribution PDA
+    pub bump: u8,
+}
+
+impl Contribution {
+    /// Credit one increment by `amount`
+    pub fn record(&mut self, amount: u64) {
+        self.amount = self.amount.saturating_add(amount);
+        self.updates += 1;
+    }
+}
+
+/// Top counters by count, a PDA per authority
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    /// The key that created the leaderboard (part of the PDA seeds)
+    pub authority: Pubkey,
+    /// Highest counts first, ties in order of submission
+    #[max_len(MAX_LEADERBOARD_ENTRIES)]
+    pub entries: Vec<LeaderboardEntry>,
+    /// The canonical bump of the leaderboard PDA
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    /// Put `entry` in its place by count, replacing any earlier entry of its counter
+    /// Returns its zero-based rank, or None if it didn't make the board
+    pub fn submit(&mut self, entry: LeaderboardEntry) -> Option<u8> {
+        self.entries
+            .retain(|existing| existing.counter != entry.counter);
+        let rank = self
+            .entries
+            .partition_point(|existing| existing.count >= entry.count);
+        if rank >= MAX_LEADERBOARD_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_LEADERBOARD_ENTRIES);
+        Some(rank as u8)
+    }
+
+    /// Drop the entry of `counter`, returning whether it was on the board
+    pub fn remove(&mut self, counter: &Pubkey) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.counter != *counter);
+        self.entries.len() < len
+    }
+}
+
+/// A counter's place on a leaderboard
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct LeaderboardEntry {
+    pub counter: Pubkey,
+    /// The counter's authority at the time of submission
+    pub authority: Pubkey,
+    pub count: u64,
+}
+
+/// Fees collected for a counter, a PDA per counter holding them as lamports
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    /// The counter whose fees are collected
+    pub counter: Pubkey,
+    /// Total fees ever paid in, withdrawals aside
+    pub collected: u64,
+    /// The canonical bump of the treasury PDA
+    pub bump: u8,
+}
+
+/// Lamports a payer set aside for the fees of a counter on the ER, a PDA per
+/// (counter, payer)
+#[account]
+#[derive(InitSpace)]
+pub struct FeeEscrow {
+    /// The counter the fees are paid to
+    pub counter: Pubkey,
+    /// The signer whose fees are taken from the escrow
+    pub payer: Pubkey,
+    /// Fees accrued on the ER and not yet moved to the treasury
+    pub pending: u64,
+    /// Whether the escrow is delegated, where fees accrue against it; on the base
+    /// layer they are paid to the treasury right away
+    pub delegated: bool,
+    /// The canonical bump of the fee escrow PDA
+    pub bump: u8,
+}
+
+impl FeeEscrow {
+    /// Accrue `fee`, failing if it would exceed the `available` lamports
+    pub fn accrue(&mut self, fee: u64, available: u64) -> Result<()> {
+        let pending = self
+            .pending
+            .checked_add(fee)
+            .filter(|pending| *pending <= available)
+            .ok_or(CounterError::InsufficientFunds)?;
+        self.pending = pending;
+        Ok(())
+    }
+}
+
+/// A single recorded change
+#[zero_copy]
+pub struct HistoryEntry {
+    pub slot: u64,
+    pub unix_ts: i64,
+    /// The key that signed the update, either the authority or a session signer
+    pub signer: Pubkey,
+    pub old: u64,
+    pub new: u64,
+    /// CounterOp as its discriminant (0 increment, 1 decrement, 2 set, 3 compare_and_set)
+    pub op: u8,
+    pub _padding: [u8; 7],
+}
+
+impl Discriminator for Counter {
+    /// The discriminator `#[account]` derived, so existing counters keep matching
+    const DISCRIMINATOR: &'static [u8] = &[255, 176, 4, 245, 188, 253, 124, 25];
+}
+
+impl Owner for Counter {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+impl AccountSerialize for Counter {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer
+            .write_all(Self::DISCRIMINATOR)
+            .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        AnchorSerialize::serialize(self, writer).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        Ok(())
+    }
+}
+
+impl AccountDeserialize for Counter {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        require!(
+            buf.len() >= Self::DISCRIMINATOR.len(),
+            ErrorCode::AccountDiscriminatorNotFound
+        );
+        require!(
+            buf.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            Self::stored_version(buf) == Self::VERSION,
+            CounterError::AccountNotMigrated
+        );
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = &buf[Self::DISCRIMINATOR.len()..];
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+/// M-of-N signer set holding a counter's authority, a PDA per counter
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    /// The counter whose authority this is
+    pub counter: Pubkey,
+    pub config: MultisigConfig,
+    /// Number of proposals ever created, the index of the next one
+    pub proposal_count: u64,
+    /// The canonical bump of the multisig PDA
+    pub bump: u8,
+}
+
+impl Multisig {
+    /// Position of `key` among the signers, if it is one
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.config.signers.iter().position(|signer| signer == key)
+    }
+
+    /// Whether `key` is one of the signers
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signer_index(key).is_some()
+    }
+}
+
+/// Signers, threshold and update policy of a multisig, as passed to initialize
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct MultisigConfig {
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    /// Approvals a proposal needs before it can be executed
+    pub threshold: u8,
+    /// Who may increment and decrement the counter
+    pub update_policy: MultisigUpdatePolicy,
+}
+
+impl MultisigConfig {
+    /// Require 1..=MAX_MULTISIG_SIGNERS distinct signers and a threshold within their number
+    fn validate(&self) -> Result<()> {
+        let signers = &self.signers;
+        require!(
+            (1..=MAX_MULTISIG_SIGNERS).contains(&signers.len())
+                && (1..=signers.len()).contains(&usize::from(self.threshold))
+                && signers
+                    .iter()
+                    .enumerate()
+                    .all(|(i, signer)| !signers[..i].contains(signer)),
+            CounterError::InvalidMultisig
+        );
+        Ok(())
+    }
+
+    /// Whether a single signer may perform `op` without going through a proposal
+    pub fn allows_direct(&self, op: CounterOp) -> bool {
+        match op {
+            CounterOp::Increment | CounterOp::Decrement => {
+                self.update_policy == MultisigUpdatePolicy::AnySigner
+            }
+            CounterOp::Set | CounterOp::CompareAndSet => false,
+        }
+    }
+}
+
+/// How increment and decrement are authorized on a multisig counter
+/// Set and authority changes always go through proposals
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MultisigUpdatePolicy {
+    /// Any single signer may increment and decrement directly
+    AnySigner,
+    /// Increment and decrement need an executed proposal as well
+    Proposal,
+}
+
+/// A change to a multisig counter waiting for approvals, a PDA per proposal index
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    /// The multisig the proposal belongs to
+    pub multisig: Pubkey,
+    /// Position among the multisig's proposals (part of the PDA seeds)
+    pub index: u64,
+    /// The signer who created the proposal, refunded when it is executed
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    /// Bit `i` is set once the multisig's signer `i` approved
+    pub approvals: u8,
+    /// The canonical bump of the proposal PDA
+    pub bump: u8,
+}
+
+impl Proposal {
+    /// Record the approval of `key`, which must be one of the multisig's signers
+    pub fn approve(&mut self, multisig: &Multisig, key: &Pubkey) -> Result<()> {
+        let index = multisig
+            .signer_index(key)
+            .ok_or(CounterError::NotMultisigSigner)?;
+        self.approvals |= 1 << index;
+        Ok(())
+    }
+
+    /// Number of signers who approved
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+}
+
+/// What a proposal does once executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MultisigAction {
+    /// Set the counter to a value within its bounds
+    Set { value: u64 },
+    /// Increment the counter, following its overflow policy
+    Increment { amount: u64 },
+    /// Decrement the counter, following its overflow policy
+    Decrement { amount: u64 },
+    /// Propose a new authority, which then accepts with accept_authority
+    ProposeAuthority { new_authority: Pubkey },
+    /// As set_frozen
+    SetFrozen { frozen: bool },
+    /// As set_rate_limit
+    SetRateLimit { rate_limit: RateLimit },
+    /// As set_reset_period
+    SetResetPeriod { reset_period: ResetPeriod },
+    /// As set_public
+    SetPublic { public: bool },
+    /// As set_fee
+    SetFee { fee: u64 },
+    /// As set_token_gate
+    SetTokenGate {
+        gate_mint: Option<Pubkey>,
+        min_balance: u64,
+    },
+    /// As set_reward
+    SetReward { amount: u64 },
+    /// As set_caller_program
+    SetCallerProgram { program: Option<Pubkey> },
+    /// As withdraw, executed with the counter's Treasury and `recipient`
+    Withdraw { amount: u64, recipient: Pubkey },
+    /// As schedule_set, executed with the counter's PendingSet, whose rent the executor
+    /// pays and the multisig gets back
+    ScheduleSet { value: u64, delay: u64 },
+    /// As cancel_set, executed with the counter's PendingSet
+    CancelSet,
+    /// As close, executed with `recipient` and the counter's history, treasury and
+    /// pending set as remaining accounts, followed by any session usages, contributions
+    /// and fee escrows to close; the multisig is closed as well
+    Close { recipient: Pubkey },
+}
+
+/// A set waiting out its delay, a PDA per counter
+#[account]
+#[derive(InitSpace)]
+pub struct PendingSet {
+    /// The counter the set applies to
+    pub counter: Pubkey,
+    /// The authority that scheduled the set, refunded when it is executed or cancelled
+    pub authority: Pubkey,
+    /// The value the counter will be set to
+    pub value: u64,
+    /// Unix timestamp from which the set can be executed
+    pub execute_after: i64,
+    /// The canonical bump of the pending set PDA
+    pub bump: u8,
+}
+
+impl PendingSet {
+    /// Schedule a set of `counter` to `value` in `delay` seconds, refunded to its authority
+    /// Returns the timestamp from which it can be executed
+    fn schedule(
+        &mut self,
+        counter: &Account<Counter>,
+        value: u64,
+        delay: u64,
+        bump: u8,
+    ) -> Result<i64> {
+        require!(
+            (counter.min..=counter.max).contains(&value),
+            CounterError::ValueOutOfRange
+        );
+        // init_if_needed: a pending set that was neither executed nor cancelled is still live
+        require_keys_eq!(
+            self.counter,
+            Pubkey::default(),
+            CounterError::SetAlreadyPending
+        );
+        let execute_after = Clock::get()?
+            .unix_timestamp
+            .checked_add_unsigned(delay)
+            .ok_or(CounterError::Overflow)?;
+        self.counter = counter.key();
+        self.authority = counter.authority;
+        self.value = value;
+        self.execute_after = execute_after;
+        self.bump = bump;
+        Ok(execute_after)
+    }
+}
+
+/// Limits on how often a counter may be updated, set via set_rate_limit
+/// Each zero field disables its own check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct RateLimit {
+    /// Slots that must pass between two updates, the ER produces them far faster than Solana
+    /// Only counted on one side, the last update's slot is dropped on (un)delegation
+    pub min_slots: u64,
+    /// Seconds that must pass between two updates, at the Clock's whole-second resolution
+    pub min_seconds: u64,
+    /// Most updates within a window of `window_seconds`
+    pub max_per_window: u64,
+    /// Length of the window `max_per_window` applies to
+    pub window_seconds: u64,
+}
+
+impl RateLimit {
+    /// Reject a per-window cap without a window to apply it to, and durations that
+    /// don't fit a timestamp
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.max_per_window == 0 || self.window_seconds != 0,
+            CounterError::InvalidRateLimit
+        );
+        require!(
+            i64::try_from(self.min_seconds).is_ok() && i64::try_from(self.window_seconds).is_ok(),
+            CounterError::InvalidRateLimit
+        );
+        Ok(())
+    }
+}
+
+/// Boundaries at which a counter resets, set via set_reset_period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum ResetPeriod {
+    /// Keep counting forever
+    #[default]
+    Never,
+    /// Reset at midnight UTC
+    DailyUtc,
+    /// Reset when the Solana epoch changes
+    Epoch,
+    /// Reset every `seconds`, counted from the Unix epoch
+    Seconds { seconds: u64 },
+}
+
+impl ResetPeriod {
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+    /// Reject periods that never end or whose length doesn't fit a timestamp
+    fn validate(&self) -> Result<()> {
+        if let ResetPeriod::Seconds { seconds } = *self {
+            require!(
+                seconds != 0 && i64::try_from(seconds).is_ok(),
+                CounterError::InvalidResetPeriod
+            );
+        }
+        Ok(())
+    }
+
+    /// Start of the period `clock` falls in, None for Never
+    /// This is the epoch number for Epoch and a Unix timestamp otherwise
+    pub fn period_start(&self, clock: &Clock) -> Option<i64> {
+        let now = clock.unix_timestamp;
+        match *self {
+            ResetPeriod::Never => None,
+            ResetPeriod::DailyUtc => Some(now - now.rem_euclid(Self::SECONDS_PER_DAY)),
+            ResetPeriod::Epoch => Some(clock.epoch as i64),
+            ResetPeriod::Seconds { seconds } => Some(now - now.rem_euclid(seconds as i64)),
+        }
+    }
+}
+
+/// Program-wide settings, a singleton PDA
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    /// The key allowed to pause the program and hand over the config
+    pub admin: Pubkey,
+    /// While set, increment, decrement and set fail on every counter
+    pub paused: bool,
+    /// The canonical bump of the config PDA
+    pub bump: u8,
+}
+
+/// Behaviour of increment and decrement at the counter's bounds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OverflowPolicy {
+    /// Continue from the opposite bound
+    Wrap,
+    /// Stop at the bound
+    Saturate,
+    /// Fail with Overflow / CounterUnderflow
+    Error,
+}
+
+impl Counter {
+    /// Current layout version, bumped whenever a field is appended
+    /// Appended fields must treat all-zero bytes as their default, which migrate fills in
+    pub const VERSION: u8 = 12;
+
+    /// Data length of counters in the original layout, `{count, authority}` with no
+    /// version byte, which lived at the PDA of `[authority]` alone
+    const BASELINE_LEN: usize = 8 + 8 + 32;
+
+    /// Layout version of raw account data (discriminator included)
+    /// The original layout counts as version 0
+    fn stored_version(data: &[u8]) -> u8 {
+        if data.len() == Self::BASELINE_LEN {
+            0
+        } else {
+            data.get(8).copied().unwrap_or_default()
+        }
+    }
+
+    /// Decode account data stored in any layout up to the current one
+    /// Returns the stored version along with the counter in the current layout
+    pub fn upgrade(data: &[u8]) -> Result<(u8, Self)> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let version = Self::stored_version(data);
+        let body = &data[8..];
+        // Version 0 has no version byte but starts with the same fields, every later
+        // version only appends fields
+        let start = 8 + usize::from(version == 0);
+        let mut current = [0; 8 + Self::INIT_SPACE];
+        require!(
+            version <= Self::VERSION && start + body.len() <= current.len(),
+            ErrorCode::AccountDidNotDeserialize
+        );
+        current[start..start + body.len()].copy_from_slice(body);
+        current[8] = Self::VERSION;
+        let mut counter = Self::try_deserialize_unchecked(&mut &current[..])?;
+        if version == 0 {
+            // The original layout counted from 0 up to u64::MAX and failed past either
+            // end; counter_id and bump are only known once migrate moves it
+            counter.seed_authority = counter.authority;
+            counter.max = u64::MAX;
+            counter.overflow_policy = OverflowPolicy::Error;
+        }
+        Ok((version, counter))
+    }
+
+    /// Address of a counter in the original layout, the PDA of its authority alone
+    pub fn baseline_address(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[authority.as_ref()], &crate::ID).0
+    }
+
+    /// Add `amount` following the counter's bounds and overflow policy
+    pub fn add(&mut self, amount: u64) -> Result<()> {
+        let next = self.count as u128 + amount as u128;
+        self.count = if next <= self.max as u128 {
+            next as u64
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Wrap => self.wrap(next - self.min as u128),
+                OverflowPolicy::Saturate => self.max,
+                OverflowPolicy::Error => return err!(CounterError::Overflow),
+            }
+        };
+        Ok(())
+    }
+
+    /// Subtract `amount` following the counter's bounds and overflow policy
+    pub fn sub(&mut self, amount: u64) -> Result<()> {
+        let offset = self.count - self.min;
+        self.count = if amount <= offset {
+            self.count - amount
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Wrap => {
+                    // Step back by `amount` modulo the span to stay non-negative
+                    let span = self.span();
+                    self.wrap(offset as u128 + span - amount as u128 % span)
+                }
+                OverflowPolicy::Saturate => self.min,
+                OverflowPolicy::Error => return err!(CounterError::CounterUnderflow),
+            }
+        };
+        Ok(())
+    }
+
+    /// Set the count to `value`, which must lie within `min..=max`
+    pub fn set(&mut self, value: u64) -> Result<()> {
+        require!(
+            (self.min..=self.max).contains(&value),
+            CounterError::ValueOutOfRange
+        );
+        self.count = value;
+        Ok(())
+    }
+
+    /// Set the count to `new` if it is currently `expected`
+    pub fn compare_and_set(&mut self, expected: u64, new: u64) -> Result<()> {
+        require_eq!(self.count, expected, CounterError::CountMismatch);
+        self.set(new)
+    }
+
+    /// Forget the slot of the last update, for when the counter moves between Solana and
+    /// the ER, whose slots don't line up
+    /// The timestamps share the Clock's wall time on both, so `min_seconds` and the
+    /// window keep applying across delegation
+    pub fn rebase_throttle(&mut self) {
+        self.last_update_slot = 0;
+    }
+
+    /// Check the rate limit at `clock` and record an update against it
+    pub fn throttle(&mut self, clock: &Clock) -> Result<()> {
+        let limit = self.rate_limit;
+        let now = clock.unix_timestamp;
+        require!(
+            clock.slot.saturating_sub(self.last_update_slot) >= limit.min_slots
+                && now.saturating_sub(self.last_update_ts) >= limit.min_seconds as i64,
+            CounterError::RateLimited
+        );
+        if limit.max_per_window != 0 {
+            if now.saturating_sub(self.window_start) >= limit.window_seconds as i64 {
+                self.window_start = now;
+                self.window_count = 0;
+            }
+            require!(
+                self.window_count < limit.max_per_window,
+                CounterError::RateLimited
+            );
+            self.window_count += 1;
+        }
+        self.last_update_slot = clock.slot;
+        self.last_update_ts = now;
+        Ok(())
+    }
+
+    /// Reset the count to `min` if `clock` has moved into a new reset period
+    /// The count it had is kept in `last_period_count`
+    pub fn roll_period(&mut self, clock: &Clock) {
+        if let Some(start) = self.reset_period.period_start(clock) {
+            if start != self.period_start {
+                self.last_period_count = self.count;
+                self.count = self.min;
+                self.period_start = start;
+            }
+        }
+    }
+
+    /// Record a write to the counter
+    pub fn bump_sequence(&mut self) {
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+
+    /// Store a rate limit after validating it
+    pub fn update_rate_limit(&mut self, rate_limit: RateLimit) -> Result<()> {
+        rate_limit.validate()?;
+        self.rate_limit = rate_limit;
+        Ok(())
+    }
+
+    /// Store a reset period after validating it
+    /// The period in progress counts as started, so the current value is kept
+    pub fn update_reset_period(&mut self, reset_period: ResetPeriod, clock: &Clock) -> Result<()> {
+        reset_period.validate()?;
+        self.reset_period = reset_period;
+        self.period_start = reset_period.period_start(clock).unwrap_or_default();
+        Ok(())
+    }
+
+    /// Store the amount accrued per increment, which needs a reward mint
+    pub fn update_reward(&mut self, amount: u64) -> Result<()> {
+        require!(
+            self.reward_mint != Pubkey::default(),
+            CounterError::RewardMintRequired
+        );
+        self.reward_amount = amount;
+        Ok(())
+    }
+
+    /// Whether `key` is the caller program's PDA for the counter at `address`
+    pub fn is_caller_signer(&self, address: &Pubkey, key: &Pubkey) -> bool {
+        let program = self.caller_program;
+        program != Pubkey::default()
+            && *key
+                == Pubkey::find_program_address(&[CALLER_SIGNER_SEED, address.as_ref()], &program).0
+    }
+
+    /// Whether `key` is the authority, or one of the signers of `multisig` while it
+    /// holds the authority
+    pub fn is_authority_or_member(
+        &self,
+        key: &Pubkey,
+        multisig: Option<&Account<Multisig>>,
+    ) -> bool {
+        *key == self.authority
+            || multisig
+                .is_some_and(|multisig| multisig.key() == self.authority && multisig.is_signer(key))
+    }
+
+    /// Number of distinct values in `min..=max`
+    fn span(&self) -> u128 {
+        (self.max - self.min) as u128 + 1
+    }
+
+    /// Map an offset from `min` back into `min..=max`
+    fn wrap(&self, offset: u128) -> u64 {
+        (self.min as u128 + offset % self.span()) as u64
+    }
+
+    /// Check that `key` is the PDA derived from this counter's stored seeds
+    /// Used where the counter can't be validated by a seeds constraint
+    pub fn verify_address(&self, key: &Pubkey, counter_id: u64) -> Result<()> {
+        let expected = Pubkey::create_program_address(
+            &[
+                self.seed_authority.as_ref(),
+                &counter_id.to_le_bytes(),
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(expected, *key, ErrorCode::ConstraintSeeds);
+        Ok(())
+    }
+}
+
+// ========================================
+// Events
+// ========================================
+
+/// Kind of update recorded in CounterChanged and HistoryEntry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CounterOp {
+    Increment,
+    Decrement,
+    Set,
+    CompareAndSet,
+}
+
+impl CounterOp {
+    /// The session scope permission needed to perform this update
+    pub fn permission(self) -> u8 {
+        match self {
+            CounterOp::Increment => SessionScope::INCREMENT,
+            CounterOp::Decrement => SessionScope::DECREMENT,
+            CounterOp::Set | CounterOp::CompareAndSet => SessionScope::SET,
+        }
+    }
+}
+
+#[event]
+pub struct CounterInitialized {
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub counter_id: u64,
+    pub count: u64,
+}
+
+#[event]
+pub struct CounterChanged {
+    pub counter: Pubkey,
+    pub old: u64,
+    pub new: u64,
+    /// The counter's sequence after this change
+    pub sequence: u64,
+    pub op: CounterOp,
+    pub signer: Pubkey,
+    /// Whether the signer acted through a session token rather than as the authority
+    pub via_session: bool,
+}
+
+#[event]
+pub struct HistoryInitialized {
+    pub counter: Pubkey,
+    pub history: Pubkey,
+}
+
+#[event]
+pub struct SessionUsageInitialized {
+    pub counter: Pubkey,
+    pub session_signer: Pubkey,
+    pub session_usage: Pubkey,
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub counter: Pubkey,
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub counter: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    /// Number of signers who approved so far
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub counter: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct CounterClosed {
+    pub counter: Pubkey,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct CounterMigrated {
+    pub counter: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct SetScheduled {
+    pub counter: Pubkey,
+    pub value: u64,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct SetCancelled {
+    pub counter: Pubkey,
+    pub value: u64,
+}
+
+#[event]
+pub struct CounterFrozen {
+    pub counter: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct SessionScopeChanged {
+    pub counter: Pubkey,
+    pub scope: SessionScope,
+}
+
+#[event]
+pub struct RateLimitChanged {
+    pub counter: Pubkey,
+    pub rate_limit: RateLimit,
+}
+
+#[event]
+pub struct ResetPeriodChanged {
+    pub counter: Pubkey,
+    pub reset_period: ResetPeriod,
+    pub period_start: i64,
+}
+
+#[event]
+pub struct CounterPublicChanged {
+    pub counter: Pubkey,
+    pub public: bool,
+}
+
+#[event]
+pub struct ContributionInitialized {
+    pub counter: Pubkey,
+    pub contributor: Pubkey,
+    pub contribution: Pubkey,
+}
+
+#[event]
+pub struct ContributionDelegated {
+    pub counter: Pubkey,
+    pub contribution: Pubkey,
+}
+
+#[event]
+pub struct LeaderboardInitialized {
+    pub leaderboard: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ScoreSubmitted {
+    pub leaderboard: Pubkey,
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    /// Zero-based place on the board, None if the count didn't make it
+    pub rank: Option<u8>,
+}
+
+#[event]
+pub struct ScorePruned {
+    pub leaderboard: Pubkey,
+    pub counter: Pubkey,
+}
+
+#[event]
+pub struct LeaderboardDelegated {
+    pub leaderboard: Pubkey,
+    pub commit_frequency_ms: Option<u32>,
+}
+
+#[event]
+pub struct LeaderboardCommitted {
+    pub leaderboard: Pubkey,
+}
+
+#[event]
+pub struct LeaderboardUndelegated {
+    pub leaderboard: Pubkey,
+}
+
+#[event]
+pub struct FeeChanged {
+    pub counter: Pubkey,
+    pub fee: u64,
+}
+
+#[event]
+pub struct TreasuryInitialized {
+    pub counter: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub counter: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeEscrowInitialized {
+    pub counter: Pubkey,
+    pub payer: Pubkey,
+    pub fee_escrow: Pubkey,
+    pub deposit: u64,
+}
+
+#[event]
+pub struct TreasuryDelegated {
+    pub counter: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct FeeEscrowDelegated {
+    pub counter: Pubkey,
+    pub fee_escrow: Pubkey,
+}
+
+#[event]
+pub struct FeesSettled {
+    pub counter: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TokenGateChanged {
+    pub counter: Pubkey,
+    pub gate_mint: Option<Pubkey>,
+    pub min_balance: u64,
+}
+
+#[event]
+pub struct RewardMintInitialized {
+    pub counter: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct RewardChanged {
+    pub counter: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardsMinted {
+    pub counter: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CallerProgramChanged {
+    pub counter: Pubkey,
+    pub program: Option<Pubkey>,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub counter: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct CounterDelegated {
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    /// Validator the counter was pinned to, if any
+    pub validator: Option<Pubkey>,
+}
+
+#[event]
+pub struct HistoryDelegated {
+    pub counter: Pubkey,
+    pub history: Pubkey,
+}
+
+#[event]
+pub struct SessionUsageDelegated {
+    pub counter: Pubkey,
+    pub session_usage: Pubkey,
+}
+
+#[event]
+pub struct CounterCommitted {
+    pub counter: Pubkey,
+    pub count: u64,
+}
+
+#[event]
+pub struct CounterUndelegated {
+    pub counter: Pubkey,
+    pub count: u64,
 }
 
 // ========================================
@@ -189,8 +4600,88 @@ pub struct Counter {
 
 #[error_code]
 pub enum CounterError {
-    #[msg("Counter cannot go below zero")]
+    #[msg("Counter cannot go below its minimum")]
     CounterUnderflow,
     #[msg("Invalid authentication")]
     InvalidAuth,
+    #[msg("Counter is delegated to an Ephemeral Rollup, undelegate it first")]
+    CounterDelegated,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Counter cannot go above its maximum")]
+    Overflow,
+    #[msg("Value is outside the counter's min..=max bounds")]
+    ValueOutOfRange,
+    #[msg("Counter min must not exceed max")]
+    InvalidBounds,
+    #[msg("Counter uses an older account layout, run migrate first")]
+    AccountNotMigrated,
+    #[msg("Counter updates are paused")]
+    Paused,
+    #[msg("Counter does not hold the expected value")]
+    CountMismatch,
+    #[msg("Permissions must be a mask of increment, decrement and set")]
+    InvalidPermissions,
+    #[msg("Session signers are not permitted to perform this update")]
+    SessionScopeDenied,
+    #[msg("Session signers must pass their session usage account")]
+    SessionUsageRequired,
+    #[msg("Session has used up its quota, renew it to continue")]
+    SessionQuotaExceeded,
+    #[msg("Scheduled set cannot be executed before its delay has passed")]
+    SetNotReady,
+    #[msg("Counter already has a scheduled set, execute or cancel it first")]
+    SetAlreadyPending,
+    #[msg("Counter was updated too recently, try again later")]
+    RateLimited,
+    #[msg("A per-window cap needs a non-zero window")]
+    InvalidRateLimit,
+    #[msg("Reset period must be between 1 second and i64::MAX seconds")]
+    InvalidResetPeriod,
+    #[msg("Counter is not public")]
+    CounterNotPublic,
+    #[msg("Increments to a public counter must pass the signer's contribution")]
+    ContributionRequired,
+    #[msg("Contribution belongs to another counter")]
+    InvalidContribution,
+    #[msg("Counter charges a fee, pass its treasury")]
+    TreasuryRequired,
+    #[msg("Not enough lamports to cover the amount")]
+    InsufficientFunds,
+    #[msg("Fee escrow belongs to another counter")]
+    InvalidFeeEscrow,
+    #[msg("Fee escrow still holds fees to settle")]
+    FeesUnsettled,
+    #[msg("Counter is token gated, pass a token account of its gate mint")]
+    GateTokenRequired,
+    #[msg("Token account is not of the gate mint or not owned by the signer")]
+    InvalidGateToken,
+    #[msg("Token account holds less than the counter's minimum balance")]
+    GateBalanceTooLow,
+    #[msg("Counter has no reward mint, create one via init_reward_mint")]
+    RewardMintRequired,
+    #[msg("Account does not match the counter's reward mint")]
+    InvalidRewardAccount,
+    #[msg("Counter has no rewards to mint")]
+    NoPendingRewards,
+    #[msg("Only counters in the original layout, and all of them, move to a relocated address")]
+    RelocationMismatch,
+    #[msg("Session usage belongs to another counter")]
+    InvalidSessionUsage,
+    #[msg("Multisig needs 1 to 8 distinct signers and a threshold within their number")]
+    InvalidMultisig,
+    #[msg("Signer is not one of the multisig's signers")]
+    NotMultisigSigner,
+    #[msg("This update needs an executed multisig proposal")]
+    MultisigProposalRequired,
+    #[msg("Proposal does not have enough approvals yet")]
+    ThresholdNotMet,
+    #[msg("Proposal needs the counter's treasury or pending set, or the recipient")]
+    ProposalAccountsRequired,
+    #[msg("Proposal was created under a multisig that has since been closed")]
+    StaleProposal,
+    #[msg("Counter mints rewards, pass the signer's contribution to accrue them in")]
+    RewardContributionRequired,
+    #[msg("Only entries of closed counters can be pruned from a leaderboard")]
+    CounterNotClosed,
 }
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use counter::program::Counter as CounterProgram;
+use counter::{Counter, CALLER_SIGNER_SEED, CONFIG_SEED};
+
+declare_id!("3agnZxiYWYbmV8ZAEJiNjCJoyRzuqSy47MZdMAWWkH3L");
+
+/// Example of a program composing with counters: a scoreboard awards points by
+/// incrementing a counter through CPI
+/// The scoreboard lives at the counter's caller signer PDA of this program, so once
+/// the counter's authority allows this program via set_caller_program, the
+/// scoreboard signs the increments itself
+/// Scoreboards only run on Solana: awarding a point writes to the scoreboard, which
+/// would have to be delegated along with the counter to run on the ER
+#[program]
+pub mod scoreboard {
+    use super::*;
+
+    /// Create the scoreboard of `counter` with the signer as its admin
+    /// Only the counter's authority may, or anyone could take the scoreboard's PDA
+    /// first and award points once the program is allowed
+    pub fn init_scoreboard(ctx: Context<InitScoreboard>) -> Result<()> {
+        let scoreboard = &mut ctx.accounts.scoreboard;
+        scoreboard.admin = ctx.accounts.admin.key();
+        scoreboard.counter = ctx.accounts.counter.key();
+        scoreboard.points = 0;
+        scoreboard.bump = ctx.bumps.scoreboard;
+        msg!(
+            "Scoreboard {} created for counter {}",
+            scoreboard.key(),
+            scoreboard.counter
+        );
+        Ok(())
+    }
+
+    /// Award a point, incrementing the counter with the scoreboard as signer
+    pub fn award_point(ctx: Context<AwardPoint>, counter_id: u64) -> Result<()> {
+        let counter = ctx.accounts.counter.key();
+        // Before init_config has run there is no config to pass, and nothing is paused
+        let config = &ctx.accounts.config;
+        let seeds: &[&[u8]] = &[
+            CALLER_SIGNER_SEED,
+            counter.as_ref(),
+            &[ctx.accounts.scoreboard.bump],
+        ];
+        counter::cpi::increment(
+            CpiContext::new_with_signer(
+                ctx.accounts.counter_program.to_account_info(),
+                counter::cpi::accounts::Update {
+                    counter: ctx.accounts.counter.to_account_info(),
+                    signer: ctx.accounts.scoreboard.to_account_info(),
+                    config: (!config.data_is_empty()).then(|| config.to_account_info()),
+                    session_token: None,
+                    history: None,
+                    multisig: None,
+                    session_usage: None,
+                    contribution: None,
+                    treasury: None,
+                    fee_escrow: None,
+                    gate_token_account: None,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                &[seeds],
+            ),
+            counter_id,
+        )?;
+        let scoreboard = &mut ctx.accounts.scoreboard;
+        scoreboard.points = scoreboard.points.saturating_add(1);
+        msg!(
+            "Scoreboard {} points: {}",
+            scoreboard.key(),
+            scoreboard.points
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitScoreboard<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Scoreboard::INIT_SPACE,
+        seeds = [CALLER_SIGNER_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    #[account(
+        constraint = counter.authority == admin.key() @ ScoreboardError::InvalidAuthority
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AwardPoint<'info> {
+    #[account(
+        mut,
+        seeds = [CALLER_SIGNER_SEED, counter.key().as_ref()],
+        bump = scoreboard.bump,
+        has_one = admin @ ScoreboardError::InvalidAdmin
+    )]
+    pub scoreboard: Account<'info, Scoreboard>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: Checked by the counter program
+    #[account(mut)]
+    pub counter: UncheckedAccount<'info>,
+
+    /// CHECK: The counter program's config, checked by the counter program
+    #[account(seeds = [CONFIG_SEED], bump, seeds::program = counter_program.key())]
+    pub config: UncheckedAccount<'info>,
+
+    pub counter_program: Program<'info, CounterProgram>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Scoreboard {
+    /// Who may award points
+    pub admin: Pubkey,
+    /// The counter points are recorded on
+    pub counter: Pubkey,
+    /// Points awarded through this scoreboard
+    pub points: u64,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum ScoreboardError {
+    #[msg("Only the scoreboard's admin may award points")]
+    InvalidAdmin,
+    #[msg("Only the counter's authority may create its scoreboard")]
+    InvalidAuthority,
+}
@@ -1,4 +1,4 @@
 #!/bin/bash
 
-# run tests
-anchor deploy --provider.cluster devnet
\ No newline at end of file
+# deploy, then create the program config
+anchor deploy --provider.cluster devnet && anchor migrate --provider.cluster devnet
\ No newline at end of file
@@ -1,5 +1,14 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, web3 } from "@coral-xyz/anchor";
+import {
+  createAccount,
+  createMint,
+  getAccount,
+  getMint,
+  mintTo,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { expect } from "chai";
 import { Counter } from "../target/types/counter";
@@ -35,141 +44,4406 @@ describe("counter", () => {
   const program = anchor.workspace.Counter as Program<Counter>;
   const authority = provider.wallet;
 
-  // Derive PDA using user's public key
+  // Each authority can own many counters, distinguished by a u64 id
+  const counterId = new anchor.BN(0);
+
+  // min, max and overflow policy matching the demo behaviour (wrap above 1000)
+  const defaultBounds: [anchor.BN, anchor.BN, { wrap: {} }] = [
+    new anchor.BN(0),
+    new anchor.BN(1000),
+    { wrap: {} },
+  ];
+
+  // Derive PDA using user's public key and the counter id
   const [counterPDA] = anchor.web3.PublicKey.findProgramAddressSync(
-    [authority.publicKey.toBuffer()],
+    [authority.publicKey.toBuffer(), counterId.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+
+  // Optional change history of the counter, delegated and committed along with it
+  const [historyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("history"), counterPDA.toBuffer()],
+    program.programId
+  );
+
+  // Singleton program config holding the global pause switch
+  const [configPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
     program.programId
   );
 
-  console.log("Program ID: ", program.programId.toString());
-  console.log("Counter PDA: ", counterPDA.toString());
+  // Session tokens are issued by the session-keys program
+  const SESSION_KEYS_PROGRAM_ID = new anchor.web3.PublicKey(
+    "KeyspM2ssCJbqUhQ4k7sveSiY4WjnYsrXkC8oDbwde5"
+  );
+
+  const deriveSessionToken = (sessionSigner: web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("session_token"),
+        program.programId.toBuffer(),
+        sessionSigner.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      SESSION_KEYS_PROGRAM_ID
+    )[0];
+
+  // Usage of a session signer on a counter, counted once the scope caps uses
+  const deriveSessionUsage = (
+    counter: web3.PublicKey,
+    sessionSigner: web3.PublicKey
+  ) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("session_usage"), counter.toBuffer(), sessionSigner.toBuffer()],
+      program.programId
+    )[0];
+
+  // Issue a session token valid for an hour through create_session
+  const createSession = async (sessionSigner: web3.Keypair) => {
+    const sessionToken = deriveSessionToken(sessionSigner.publicKey);
+    // create_session(top_up: None, valid_until: Some(now + 1h), lamports: None)
+    const validUntil = Math.floor(Date.now() / 1000) + 60 * 60;
+    const data = Buffer.concat([
+      Buffer.from([242, 193, 143, 179, 150, 25, 122, 227]),
+      Buffer.from([0, 1]),
+      new anchor.BN(validUntil).toArrayLike(Buffer, "le", 8),
+      Buffer.from([0]),
+    ]);
+    await provider.sendAndConfirm(
+      new web3.Transaction().add(
+        new web3.TransactionInstruction({
+          programId: SESSION_KEYS_PROGRAM_ID,
+          keys: [
+            { pubkey: sessionToken, isSigner: false, isWritable: true },
+            {
+              pubkey: sessionSigner.publicKey,
+              isSigner: true,
+              isWritable: true,
+            },
+            { pubkey: authority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: program.programId, isSigner: false, isWritable: false },
+            {
+              pubkey: web3.SystemProgram.programId,
+              isSigner: false,
+              isWritable: false,
+            },
+          ],
+          data,
+        })
+      ),
+      [sessionSigner]
+    );
+    return sessionToken;
+  };
+
+  // Close a session token through revoke_session
+  const revokeSession = (sessionSigner: web3.PublicKey) =>
+    provider.sendAndConfirm(
+      new web3.Transaction().add(
+        new web3.TransactionInstruction({
+          programId: SESSION_KEYS_PROGRAM_ID,
+          keys: [
+            {
+              pubkey: deriveSessionToken(sessionSigner),
+              isSigner: false,
+              isWritable: true,
+            },
+            { pubkey: authority.publicKey, isSigner: false, isWritable: true },
+            {
+              pubkey: web3.SystemProgram.programId,
+              isSigner: false,
+              isWritable: false,
+            },
+          ],
+          data: Buffer.from([86, 92, 198, 120, 144, 2, 7, 194]),
+        })
+      )
+    );
+
+  // Create a mint of `tokenProgram` and a token account of `owner` holding `amount`
+  const createTokenHolding = async (
+    tokenProgram: web3.PublicKey,
+    amount: number,
+    owner = authority.publicKey
+  ) => {
+    const payer = provider.wallet.payer;
+    const mint = await createMint(
+      provider.connection,
+      payer,
+      authority.publicKey,
+      null,
+      0,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    const account = await createAccount(
+      provider.connection,
+      payer,
+      mint,
+      owner,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    if (amount > 0) {
+      await mintTo(
+        provider.connection,
+        payer,
+        mint,
+        account,
+        payer,
+        amount,
+        [],
+        undefined,
+        tokenProgram
+      );
+    }
+    return [mint, account];
+  };
+
+  console.log("Program ID: ", program.programId.toString());
+  console.log("Counter PDA: ", counterPDA.toString());
+
+  before(async function () {
+    const balance = await provider.connection.getBalance(
+      anchor.Wallet.local().publicKey
+    );
+    console.log("Current balance is", balance / LAMPORTS_PER_SOL, " SOL", "\n");
+  });
+
+  // Updates read the program config, so make sure it exists and isn't paused
+  before(async () => {
+    const config = await program.account.programConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initConfig(authority.publicKey)
+        .accounts({
+          payer: authority.publicKey,
+        })
+        .rpc();
+    } else if (config.paused) {
+      await program.methods
+        .setPaused(false)
+        .accounts({
+          admin: authority.publicKey,
+        })
+        .rpc();
+    }
+  });
+
+  // ========================================
+  // Base Layer Tests
+  // ========================================
+
+  describe("initialize", () => {
+    it("initializes a counter with count 0", async () => {
+      const start = Date.now();
+      let tx = await program.methods
+        .initialize(counterId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .transaction();
+
+      const txHash = await provider.sendAndConfirm(
+        tx,
+        [provider.wallet.payer],
+        {
+          skipPreflight: true,
+          commitment: "confirmed",
+        }
+      );
+      const duration = Date.now() - start;
+      console.log(`${duration}ms (Base Layer) Initialize txHash: ${txHash}`);
+
+      const counterAccount = await program.account.counter.fetch(counterPDA);
+      expect(counterAccount.version).to.equal(12);
+      expect(counterAccount.count.toNumber()).to.equal(0);
+      expect(counterAccount.authority.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+    });
+
+    it("rejects initializing an existing counter", async () => {
+      try {
+        await program.methods
+          .initialize(counterId, ...defaultBounds, null)
+          .accounts({
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("already in use");
+      }
+    });
+  });
+
+  describe("increment", () => {
+    it("increments the counter by 1", async () => {
+      const start = Date.now();
+      let tx = await program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+
+      const txHash = await provider.sendAndConfirm(
+        tx,
+        [provider.wallet.payer],
+        {
+          skipPreflight: true,
+          commitment: "confirmed",
+        }
+      );
+      const duration = Date.now() - start;
+      console.log(`${duration}ms (Base Layer) Increment txHash: ${txHash}`);
+
+      const counterAccount = await program.account.counter.fetch(counterPDA);
+      expect(counterAccount.count.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("increments multiple times", async () => {
+      const counterBefore = await program.account.counter.fetch(counterPDA);
+      const initialCount = counterBefore.count.toNumber();
+
+      // Increment 3 times
+      for (let i = 0; i < 3; i++) {
+        await program.methods
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .rpc();
+      }
+
+      const counterAccount = await program.account.counter.fetch(counterPDA);
+      expect(counterAccount.count.toNumber()).to.equal(initialCount + 3);
+    });
+  });
+
+  describe("decrement", () => {
+    it("decrements the counter by 1", async () => {
+      const counterBefore = await program.account.counter.fetch(counterPDA);
+      const initialCount = counterBefore.count.toNumber();
+
+      await program.methods
+        .decrement(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(counterPDA);
+      expect(counterAccount.count.toNumber()).to.equal(initialCount - 1);
+    });
+  });
+
+  describe("set", () => {
+    it("sets the counter to a specific value", async () => {
+      await program.methods
+        .set(counterId, new anchor.BN(42))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(counterPDA);
+      expect(counterAccount.count.toNumber()).to.equal(42);
+    });
+  });
+
+  describe("events", () => {
+    it("emits CounterChanged with the old and new count", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+      const tx = await program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl)
+      );
+      const events = [...parser.parseLogs(txDetails.meta.logMessages)];
+      const changed = events.find((e) => e.name === "counterChanged");
+
+      expect(changed).to.not.be.undefined;
+      expect(changed.data.old.toNumber()).to.equal(before.count.toNumber());
+      expect(changed.data.new.toNumber()).to.equal(
+        before.count.toNumber() + 1
+      );
+      expect(changed.data.op).to.deep.equal({ increment: {} });
+      expect(changed.data.signer.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+    });
+  });
+
+  describe("compare_and_set", () => {
+    it("sets the counter when it holds the expected value", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      await program.methods
+        .compareAndSet(counterId, before.count, new anchor.BN(7))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.count.toNumber()).to.equal(7);
+      expect(after.sequence.toNumber()).to.equal(before.sequence.toNumber() + 1);
+    });
+
+    it("fails with CountMismatch when the counter has moved on", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      try {
+        await program.methods
+          .compareAndSet(counterId, before.count.addn(1), new anchor.BN(0))
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CountMismatch");
+      }
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.count.toNumber()).to.equal(before.count.toNumber());
+      expect(after.sequence.toNumber()).to.equal(before.sequence.toNumber());
+    });
+  });
+
+  describe("increment_by / decrement_by", () => {
+    it("moves the counter by an arbitrary amount", async () => {
+      await program.methods
+        .set(counterId, new anchor.BN(100))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+      await program.methods
+        .incrementBy(counterId, new anchor.BN(25))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(125);
+
+      await program.methods
+        .decrementBy(counterId, new anchor.BN(20))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(105);
+    });
+
+    it("wraps large amounts around the bounds", async () => {
+      // 105 + 1000 on a 0..=1000 wrapping counter lands on 104
+      await program.methods
+        .incrementBy(counterId, new anchor.BN(1000))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(104);
+    });
+  });
+
+  describe("bounds", () => {
+    // Creates a counter limited to 10..=12 with the given overflow policy
+    const initBounded = async (id: anchor.BN, overflowPolicy: any) => {
+      const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .initialize(id, new anchor.BN(10), new anchor.BN(12), overflowPolicy, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      return pda;
+    };
+
+    const step = (
+      method: "increment" | "decrement",
+      id: anchor.BN,
+      pda: anchor.web3.PublicKey
+    ) =>
+      program.methods[method](id)
+        .accountsPartial({
+          counter: pda,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+    const count = (account: { count: anchor.BN }) => account.count.toNumber();
+
+    it("starts at min and wraps at the bounds", async () => {
+      const id = new anchor.BN(10);
+      const pda = await initBounded(id, { wrap: {} });
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      for (let i = 0; i < 3; i++) {
+        await step("increment", id, pda);
+      }
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      await step("decrement", id, pda);
+      expect(count(await program.account.counter.fetch(pda))).to.equal(12);
+    });
+
+    it("saturates at the bounds", async () => {
+      const id = new anchor.BN(11);
+      const pda = await initBounded(id, { saturate: {} });
+
+      await step("decrement", id, pda);
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      for (let i = 0; i < 3; i++) {
+        await step("increment", id, pda);
+      }
+      expect(count(await program.account.counter.fetch(pda))).to.equal(12);
+    });
+
+    it("fails at the bounds with the error policy", async () => {
+      const id = new anchor.BN(12);
+      const pda = await initBounded(id, { error: {} });
+
+      try {
+        await step("decrement", id, pda);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterUnderflow");
+      }
+
+      await step("increment", id, pda);
+      await step("increment", id, pda);
+      try {
+        await step("increment", id, pda);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Overflow");
+      }
+    });
+
+    it("rejects set outside the bounds", async () => {
+      const id = new anchor.BN(13);
+      const pda = await initBounded(id, { wrap: {} });
+
+      try {
+        await program.methods
+          .set(id, new anchor.BN(13))
+          .accountsPartial({
+            counter: pda,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ValueOutOfRange");
+      }
+    });
+
+    it("rejects min greater than max", async () => {
+      try {
+        await program.methods
+          .initialize(new anchor.BN(14), new anchor.BN(5), new anchor.BN(4), { wrap: {} }, null)
+          .accounts({
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidBounds");
+      }
+    });
+  });
+
+  describe("close", () => {
+    it("closes a counter and returns the rent to the recipient", async () => {
+      const closeId = new anchor.BN(2);
+      const [closePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      const rent = await provider.connection.getBalance(closePDA);
+      const recipient = web3.Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accounts({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .rpc();
+
+      expect(await program.account.counter.fetchNullable(closePDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("closes the counter's session usages along with it", async () => {
+      const closeId = new anchor.BN(22);
+      const [closePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const sessionSigner = web3.Keypair.generate().publicKey;
+      const [usagePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("session_usage"), closePDA.toBuffer(), sessionSigner.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .initSessionUsage(closeId, sessionSigner)
+        .accountsPartial({
+          counter: closePDA,
+          payer: authority.publicKey,
+        })
+        .rpc();
+
+      const rent =
+        (await provider.connection.getBalance(closePDA)) +
+        (await provider.connection.getBalance(usagePDA));
+      const recipient = web3.Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accounts({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .remainingAccounts([
+          { pubkey: usagePDA, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(usagePDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("refunds the contributions closed along with the counter", async () => {
+      const closeId = new anchor.BN(31);
+      const [closePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const contributor = web3.Keypair.generate();
+      const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("contribution"),
+          closePDA.toBuffer(),
+          contributor.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setPublic(closeId, true)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+      await program.methods
+        .initContribution(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          contributor: contributor.publicKey,
+          multisig: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+      const contributionRent =
+        await provider.connection.getBalance(contributionPDA);
+      const before = await provider.connection.getBalance(contributor.publicKey);
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: contributionPDA, isSigner: false, isWritable: true },
+          { pubkey: contributor.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(contributionPDA)).to.be.null;
+      expect(await provider.connection.getBalance(contributor.publicKey)).to.equal(
+        before + contributionRent
+      );
+    });
+
+    it("drops a pending set along with the counter", async () => {
+      const closeId = new anchor.BN(34);
+      const [closePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [pendingSetPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pending_set"), closePDA.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .scheduleSet(closeId, new anchor.BN(5), new anchor.BN(60 * 60))
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const rent =
+        (await provider.connection.getBalance(closePDA)) +
+        (await provider.connection.getBalance(pendingSetPDA));
+      const recipient = web3.Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accounts({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(pendingSetPDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("closes the treasury and refunds the fee escrows along with the counter", async () => {
+      const closeId = new anchor.BN(32);
+      const [closePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), closePDA.toBuffer()],
+        program.programId
+      );
+      const feePayer = web3.Keypair.generate();
+      const [feeEscrowPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("fee_escrow"),
+          closePDA.toBuffer(),
+          feePayer.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .initTreasury(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: feePayer.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+      await program.methods
+        .initFeeEscrow(closeId, new anchor.BN(100_000))
+        .accountsPartial({
+          counter: closePDA,
+          payer: feePayer.publicKey,
+        })
+        .signers([feePayer])
+        .rpc();
+
+      const rent =
+        (await provider.connection.getBalance(closePDA)) +
+        (await provider.connection.getBalance(treasuryPDA));
+      const escrowBalance = await provider.connection.getBalance(feeEscrowPDA);
+      const before = await provider.connection.getBalance(feePayer.publicKey);
+      const recipient = web3.Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accounts({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .remainingAccounts([
+          { pubkey: feeEscrowPDA, isSigner: false, isWritable: true },
+          { pubkey: feePayer.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(treasuryPDA)).to.be.null;
+      expect(await provider.connection.getAccountInfo(feeEscrowPDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+      expect(await provider.connection.getBalance(feePayer.publicKey)).to.equal(
+        before + escrowBalance
+      );
+    });
+  });
+
+  describe("migrate", () => {
+    it("leaves a counter already in the current layout unchanged", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      await program.methods
+        .migrate(counterId)
+        .accounts({
+          counter: counterPDA,
+          relocated: null,
+          payer: authority.publicKey,
+        })
+        .rpc();
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.version).to.equal(12);
+      expect(after.count.toNumber()).to.equal(before.count.toNumber());
+    });
+
+    it("rejects a counter id that does not match the address", async () => {
+      try {
+        await program.methods
+          .migrate(new anchor.BN(999))
+          .accounts({
+            counter: counterPDA,
+            relocated: null,
+            payer: authority.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    describe("from the original layout", () => {
+      // Preloaded from tests/fixtures by the local validator (see Anchor.toml): a
+      // 48-byte {count: 42, authority} counter at the PDA of its authority alone
+      const baselineAuthority = web3.Keypair.fromSecretKey(
+        Uint8Array.from(require("./fixtures/baseline-authority.json"))
+      );
+      const [baselinePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [baselineAuthority.publicKey.toBuffer()],
+        program.programId
+      );
+      const relocatedId = new anchor.BN(0);
+      const [relocatedPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          baselineAuthority.publicKey.toBuffer(),
+          relocatedId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      before(async function () {
+        const info = await provider.connection.getAccountInfo(baselinePDA);
+        if (!info) {
+          // Only the local validator loads the fixture
+          this.skip();
+        }
+        expect(info.data.length).to.equal(48);
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: authority.publicKey,
+              toPubkey: baselineAuthority.publicKey,
+              lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+            })
+          )
+        );
+      });
+
+      it("requires the relocated address", async () => {
+        try {
+          await program.methods
+            .migrate(relocatedId)
+            .accounts({
+              counter: baselinePDA,
+              relocated: null,
+              payer: baselineAuthority.publicKey,
+            })
+            .signers([baselineAuthority])
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("RelocationMismatch");
+        }
+      });
+
+      it("only lets its authority move it", async () => {
+        const otherId = new anchor.BN(77);
+        const [otherPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+          [authority.publicKey.toBuffer(), otherId.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        try {
+          await program.methods
+            .migrate(otherId)
+            .accountsPartial({
+              counter: baselinePDA,
+              relocated: otherPDA,
+              payer: authority.publicKey,
+            })
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidAuth");
+        }
+      });
+
+      it("moves it to the counter id's address in the current layout", async () => {
+        await program.methods
+          .migrate(relocatedId)
+          .accountsPartial({
+            counter: baselinePDA,
+            relocated: relocatedPDA,
+            payer: baselineAuthority.publicKey,
+          })
+          .signers([baselineAuthority])
+          .rpc();
+
+        const migrated = await program.account.counter.fetch(relocatedPDA);
+        expect(migrated.version).to.equal(12);
+        expect(migrated.count.toNumber()).to.equal(42);
+        expect(migrated.authority.toBase58()).to.equal(
+          baselineAuthority.publicKey.toBase58()
+        );
+        expect(migrated.seedAuthority.toBase58()).to.equal(
+          baselineAuthority.publicKey.toBase58()
+        );
+        expect(migrated.counterId.toNumber()).to.equal(0);
+        expect(migrated.min.toNumber()).to.equal(0);
+        expect(migrated.max.toString()).to.equal("18446744073709551615");
+        expect(migrated.overflowPolicy).to.deep.equal({ error: {} });
+        expect(await provider.connection.getAccountInfo(baselinePDA)).to.be.null;
+      });
+    });
+  });
+
+  describe("multisig", () => {
+    const multisigId = new anchor.BN(35);
+    const [multisigCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), multisigId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const derivePDA = (seed: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(seed), multisigCounter.toBuffer()],
+        program.programId
+      )[0];
+    const multisigPDA = derivePDA("multisig");
+    // The wallet pays for proposals, the other signers only approve
+    const signerA = web3.Keypair.generate();
+    const signerB = web3.Keypair.generate();
+
+    // Returns the index of the new proposal
+    const propose = async (action: any) => {
+      const { proposalCount } = await program.account.multisig.fetch(
+        multisigPDA
+      );
+      await program.methods
+        .createProposal(multisigId, action)
+        .accountsPartial({
+          counter: multisigCounter,
+          proposer: authority.publicKey,
+        })
+        .rpc();
+      return proposalCount.toNumber();
+    };
+
+    const approve = (index: number, member: web3.Keypair) =>
+      program.methods
+        .approveProposal(multisigId, new anchor.BN(index))
+        .accountsPartial({
+          counter: multisigCounter,
+          member: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+
+    const execute = (index: number, accounts: any = {}) =>
+      program.methods
+        .executeProposal(multisigId, new anchor.BN(index))
+        .accountsPartial({
+          counter: multisigCounter,
+          proposer: authority.publicKey,
+          executor: authority.publicKey,
+          history: null,
+          treasury: null,
+          pendingSet: null,
+          recipient: null,
+          ...accounts,
+        })
+        .rpc();
+
+    // Approved by the wallet as proposer and signerA, meeting the threshold of 2
+    const carryOut = async (action: any, accounts: any = {}) => {
+      const index = await propose(action);
+      await approve(index, signerA);
+      await execute(index, accounts);
+    };
+
+    const update = (method: "increment" | "set", signer: web3.Keypair | null) =>
+      (method === "increment"
+        ? program.methods.increment(multisigId)
+        : program.methods.set(multisigId, new anchor.BN(7))
+      )
+        .accountsPartial({
+          counter: multisigCounter,
+          signer: signer ? signer.publicKey : authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: multisigPDA,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(multisigId, ...defaultBounds, {
+          signers: [authority.publicKey, signerA.publicKey, signerB.publicKey],
+          threshold: 2,
+          updatePolicy: { anySigner: {} },
+        })
+        .accounts({
+          authority: authority.publicKey,
+          multisig: multisigPDA,
+        })
+        .rpc();
+      // signerA pays for the accounts it creates on the multisig's behalf
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: signerA.publicKey,
+            lamports: LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+    });
+
+    it("makes the multisig the counter's authority", async () => {
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.authority.toBase58()).to.equal(
+        multisigPDA.toBase58()
+      );
+      const multisig = await program.account.multisig.fetch(multisigPDA);
+      expect(multisig.config.threshold).to.equal(2);
+      expect(multisig.config.signers).to.have.length(3);
+    });
+
+    it("lets a single signer increment under the AnySigner policy", async () => {
+      await update("increment", signerA);
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(1);
+    });
+
+    it("requires a proposal to set", async () => {
+      try {
+        await update("set", null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("MultisigProposalRequired");
+      }
+    });
+
+    it("sets the counter once the threshold of signers approved", async () => {
+      const index = await propose({ set: { value: new anchor.BN(42) } });
+
+      try {
+        await execute(index);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ThresholdNotMet");
+      }
+
+      await approve(index, signerA);
+      await execute(index);
+
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(42);
+    });
+
+    it("lets a signer create the history and records proposals in it", async () => {
+      const historyPDA = derivePDA("history");
+      await program.methods
+        .initHistory(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: signerA.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([signerA])
+        .rpc();
+
+      await carryOut(
+        { increment: { amount: new anchor.BN(3) } },
+        { history: historyPDA }
+      );
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.total.toNumber()).to.equal(1);
+      const [entry] = history.entries;
+      expect(entry.old.toNumber()).to.equal(42);
+      expect(entry.new.toNumber()).to.equal(45);
+      expect(entry.signer.toBase58()).to.equal(multisigPDA.toBase58());
+    });
+
+    it("freezes the counter through a proposal", async () => {
+      await carryOut({ setFrozen: { frozen: true } });
+      const index = await propose({ increment: { amount: new anchor.BN(1) } });
+      await approve(index, signerA);
+      try {
+        await execute(index);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      }
+
+      await carryOut({ setFrozen: { frozen: false } });
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.frozen).to.equal(false);
+    });
+
+    it("withdraws fees by proposal", async () => {
+      const treasuryPDA = derivePDA("treasury");
+      // Enough for the recipient to be rent-exempt on its own
+      const fee = LAMPORTS_PER_SOL / 100;
+      const recipient = web3.Keypair.generate().publicKey;
+      await program.methods
+        .initTreasury(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: signerA.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([signerA])
+        .rpc();
+      await carryOut({ setFee: { fee: new anchor.BN(fee) } });
+
+      await carryOut(
+        { increment: { amount: new anchor.BN(1) } },
+        { treasury: treasuryPDA }
+      );
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(treasury.collected.toNumber()).to.equal(fee);
+
+      try {
+        await carryOut({ withdraw: { amount: new anchor.BN(fee), recipient } });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ProposalAccountsRequired");
+      }
+
+      await carryOut(
+        { withdraw: { amount: new anchor.BN(fee), recipient } },
+        { treasury: treasuryPDA, recipient }
+      );
+      expect(await provider.connection.getBalance(recipient)).to.equal(fee);
+
+      await carryOut({ setFee: { fee: new anchor.BN(0) } });
+    });
+
+    it("schedules and cancels sets through proposals", async () => {
+      const pendingSetPDA = derivePDA("pending_set");
+
+      try {
+        await carryOut({
+          scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(0) },
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ProposalAccountsRequired");
+      }
+
+      await carryOut(
+        { scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(0) } },
+        { pendingSet: pendingSetPDA }
+      );
+      const pendingSet = await program.account.pendingSet.fetch(pendingSetPDA);
+      expect(pendingSet.value.toNumber()).to.equal(5);
+      // Its rent goes back to the multisig that scheduled it
+      expect(pendingSet.authority.toBase58()).to.equal(multisigPDA.toBase58());
+
+      await program.methods
+        .executeSet(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: multisigPDA,
+          executor: authority.publicKey,
+          history: null,
+          treasury: null,
+        })
+        .rpc();
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(5);
+
+      await carryOut(
+        {
+          scheduleSet: {
+            value: new anchor.BN(6),
+            delay: new anchor.BN(60 * 60),
+          },
+        },
+        { pendingSet: pendingSetPDA }
+      );
+      await carryOut({ cancelSet: {} }, { pendingSet: pendingSetPDA });
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("closes the counter and its multisig through a proposal", async () => {
+      const closeId = new anchor.BN(37);
+      const [closeCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), closeId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const derive = (seed: string) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from(seed), closeCounter.toBuffer()],
+          program.programId
+        )[0];
+      const closeMultisig = derive("multisig");
+      const proposalPDA = (index: number) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("proposal"),
+            closeMultisig.toBuffer(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        )[0];
+      const recipient = web3.Keypair.generate().publicKey;
+
+      const initialize = () =>
+        program.methods
+          .initialize(closeId, ...defaultBounds, {
+            signers: [authority.publicKey],
+            threshold: 1,
+            updatePolicy: { proposal: {} },
+          })
+          .accounts({
+            authority: authority.publicKey,
+            multisig: closeMultisig,
+          })
+          .rpc();
+      // The wallet is the only signer, its proposals are approved as they are created
+      const proposeAs = (action: any) =>
+        program.methods
+          .createProposal(closeId, action)
+          .accountsPartial({
+            counter: closeCounter,
+            proposer: authority.publicKey,
+          })
+          .rpc();
+      const executeAs = (index: number, accounts: any = {}) =>
+        program.methods
+          .executeProposal(closeId, new anchor.BN(index))
+          .accountsPartial({
+            counter: closeCounter,
+            proposer: authority.publicKey,
+            executor: authority.publicKey,
+            history: null,
+            treasury: null,
+            pendingSet: null,
+            recipient: null,
+            ...accounts,
+          });
+
+      await initialize();
+      await proposeAs({
+        scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(60 * 60) },
+      });
+      await executeAs(0, { pendingSet: derive("pending_set") }).rpc();
+      // Left open, to outlive the multisig
+      await proposeAs({ setFrozen: { frozen: true } });
+      await proposeAs({ close: { recipient } });
+
+      await executeAs(2, { recipient })
+        .remainingAccounts(
+          ["history", "treasury", "pending_set"].map((seed) => ({
+            pubkey: derive(seed),
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+
+      for (const closed of [closeCounter, closeMultisig, derive("pending_set")]) {
+        expect(await provider.connection.getAccountInfo(closed)).to.be.null;
+      }
+      expect(await provider.connection.getBalance(recipient)).to.be.greaterThan(0);
+
+      // The counter can be created again, without the proposals of the old multisig
+      await initialize();
+      try {
+        await executeAs(1).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("StaleProposal");
+      }
+      expect(
+        (await program.account.proposal.fetch(proposalPDA(1))).index.toNumber()
+      ).to.equal(1);
+    });
+
+    it("only counts approvals from the multisig's signers", async () => {
+      const newAuthority = web3.Keypair.generate();
+      const index = await propose({
+        proposeAuthority: { newAuthority: newAuthority.publicKey },
+      });
+
+      try {
+        await approve(index, web3.Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("NotMultisigSigner");
+      }
+
+      await approve(index, signerB);
+      await execute(index);
+
+      await program.methods
+        .acceptAuthority(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.authority.toBase58()).to.equal(
+        newAuthority.publicKey.toBase58()
+      );
+
+      // The multisig no longer holds the counter, but is closed along with it
+      await program.methods
+        .close(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: newAuthority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(multisigPDA)).to.be.null;
+    });
+  });
+
+  describe("authority transfer", () => {
+    const transferId = new anchor.BN(3);
+    const [transferPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), transferId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const newAuthority = web3.Keypair.generate();
+
+    it("hands over the counter while keeping the PDA address", async () => {
+      await program.methods
+        .initialize(transferId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await program.methods
+        .proposeAuthority(transferId, newAuthority.publicKey)
+        .accountsPartial({
+          counter: transferPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .acceptAuthority(transferId)
+        .accountsPartial({
+          counter: transferPDA,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(transferPDA);
+      expect(counterAccount.authority.toBase58()).to.equal(
+        newAuthority.publicKey.toBase58()
+      );
+      expect(counterAccount.seedAuthority.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+    });
+
+    it("lets the new authority update and blocks the previous one", async () => {
+      await program.methods
+        .set(transferId, new anchor.BN(5))
+        .accountsPartial({
+          counter: transferPDA,
+          signer: newAuthority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .signers([newAuthority])
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(transferPDA)).count.toNumber()
+      ).to.equal(5);
+
+      try {
+        await program.methods
+          .increment(transferId)
+          .accountsPartial({
+            counter: transferPDA,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("timelocked set", () => {
+    const timelockId = new anchor.BN(15);
+    const [timelockCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), timelockId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [pendingSetPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_set"), timelockCounter.toBuffer()],
+      program.programId
+    );
+
+    const scheduleSet = (value: number, delay: number) =>
+      program.methods
+        .scheduleSet(timelockId, new anchor.BN(value), new anchor.BN(delay))
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const [historyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("history"), timelockCounter.toBuffer()],
+      program.programId
+    );
+
+    const executeSet = (
+      executor: web3.Keypair,
+      history: anchor.web3.PublicKey | null = null
+    ) =>
+      program.methods
+        .executeSet(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+          executor: executor.publicKey,
+          history,
+          treasury: null,
+        })
+        .signers([executor])
+        .rpc();
+
+    const setRateLimit = (minSlots: number) =>
+      program.methods
+        .setRateLimit(timelockId, {
+          minSlots: new anchor.BN(minSlots),
+          minSeconds: new anchor.BN(0),
+          maxPerWindow: new anchor.BN(0),
+          windowSeconds: new anchor.BN(0),
+        })
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(timelockId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("refuses to execute before the delay has passed", async () => {
+      await scheduleSet(5, 60 * 60);
+
+      const pendingSet = await program.account.pendingSet.fetch(pendingSetPDA);
+      expect(pendingSet.value.toNumber()).to.equal(5);
+
+      try {
+        await executeSet(web3.Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SetNotReady");
+      }
+    });
+
+    it("allows a single pending set per counter", async () => {
+      try {
+        await scheduleSet(6, 0);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SetAlreadyPending");
+      }
+    });
+
+    it("lets the authority cancel a pending set", async () => {
+      await program.methods
+        .cancelSet(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("lets anyone execute once the delay has passed", async () => {
+      await scheduleSet(7, 0);
+      await executeSet(web3.Keypair.generate());
+
+      const counterAccount = await program.account.counter.fetch(
+        timelockCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(7);
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("holds an executed set to the rate limit", async () => {
+      await setRateLimit(1_000_000);
+      await scheduleSet(8, 0);
+      try {
+        await executeSet(web3.Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+      await setRateLimit(0);
+    });
+
+    it("records an executed set in the history", async () => {
+      await program.methods
+        .initHistory(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      const executor = web3.Keypair.generate();
+      await executeSet(executor, historyPDA);
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.total.toNumber()).to.equal(1);
+      const [entry] = history.entries;
+      expect(entry.old.toNumber()).to.equal(7);
+      expect(entry.new.toNumber()).to.equal(8);
+      expect(entry.op).to.equal(2);
+      expect(entry.signer.toBase58()).to.equal(executor.publicKey.toBase58());
+    });
+  });
+
+  describe("pause", () => {
+    const increment = () =>
+      program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          config: configPDA,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+    it("rejects updates to a frozen counter", async () => {
+      await program.methods
+        .setFrozen(counterId, true)
+        .accountsPartial({
+          counter: counterPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      try {
+        await increment();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      }
+
+      await program.methods
+        .setFrozen(counterId, false)
+        .accountsPartial({
+          counter: counterPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await increment();
+    });
+
+    it("rejects updates to every counter while the program is paused", async () => {
+      await program.methods
+        .setPaused(true)
+        .accounts({
+          admin: authority.publicKey,
+        })
+        .rpc();
+
+      try {
+        await increment();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      } finally {
+        await program.methods
+          .setPaused(false)
+          .accounts({
+            admin: authority.publicKey,
+          })
+          .rpc();
+      }
+
+      await increment();
+    });
+
+    it("counts a left-out config as unpaused", async () => {
+      await program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          config: null,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+    });
+
+    it("only lets the admin pause", async () => {
+      const fakeAdmin = web3.Keypair.generate();
+      try {
+        await program.methods
+          .setPaused(true)
+          .accounts({
+            admin: fakeAdmin.publicKey,
+          })
+          .signers([fakeAdmin])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("session scopes", () => {
+    const scopeId = new anchor.BN(6);
+    const [scopePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), scopeId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const sessionSigner = web3.Keypair.generate();
+    const sessionToken = deriveSessionToken(sessionSigner.publicKey);
+
+    // Permission bits, matching SessionScope::INCREMENT / DECREMENT / SET
+    const INCREMENT = 1;
+    const SET = 4;
+
+    const setScope = (permissions: number, setMin = 0, setMax = 0) =>
+      program.methods
+        .setSessionScope(scopeId, {
+          restricted: true,
+          permissions,
+          setMin: new anchor.BN(setMin),
+          setMax: new anchor.BN(setMax),
+          maxUses: new anchor.BN(0),
+        })
+        .accountsPartial({
+          counter: scopePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const updateWithSession = (method: "increment" | "set", value = 0) =>
+      (method === "increment"
+        ? program.methods.increment(scopeId)
+        : program.methods.set(scopeId, new anchor.BN(value))
+      )
+        .accountsPartial({
+          counter: scopePDA,
+          signer: sessionSigner.publicKey,
+          sessionToken,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .signers([sessionSigner])
+        .rpc();
+
+    const count = async () =>
+      (await program.account.counter.fetch(scopePDA)).count.toNumber();
+
+    before(async () => {
+      await program.methods
+        .initialize(scopeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await createSession(sessionSigner);
+    });
+
+    it("leaves sessions unrestricted by default", async () => {
+      await updateWithSession("set", 500);
+      expect(await count()).to.equal(500);
+    });
+
+    it("limits sessions to the permitted instructions", async () => {
+      await setScope(INCREMENT);
+
+      await updateWithSession("increment");
+      expect(await count()).to.equal(501);
+
+      try {
+        await updateWithSession("set", 7);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionScopeDenied");
+      }
+    });
+
+    it("limits set through a session to the scope's range", async () => {
+      await setScope(SET, 10, 20);
+
+      await updateWithSession("set", 15);
+      expect(await count()).to.equal(15);
+
+      try {
+        await updateWithSession("set", 30);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionScopeDenied");
+      }
+      try {
+        await updateWithSession("increment");
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionScopeDenied");
+      }
+    });
+
+    it("never restricts the authority", async () => {
+      await program.methods
+        .set(scopeId, new anchor.BN(30))
+        .accountsPartial({
+          counter: scopePDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+      expect(await count()).to.equal(30);
+    });
+
+    it("rejects an empty set range", async () => {
+      try {
+        await setScope(SET, 20, 10);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidBounds");
+      }
+    });
+  });
+
+  describe("session quotas", () => {
+    const quotaId = new anchor.BN(7);
+    const [quotaPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), quotaId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const sessionSigner = web3.Keypair.generate();
+    const sessionToken = deriveSessionToken(sessionSigner.publicKey);
+    const sessionUsage = deriveSessionUsage(quotaPDA, sessionSigner.publicKey);
+
+    const incrementWithSession = (usage: web3.PublicKey | null) =>
+      program.methods
+        .increment(quotaId)
+        .accountsPartial({
+          counter: quotaPDA,
+          signer: sessionSigner.publicKey,
+          sessionToken,
+          history: null,
+          multisig: null,
+          sessionUsage: usage,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .signers([sessionSigner])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(quotaId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      // Any update, at most two per session
+      await program.methods
+        .setSessionScope(quotaId, {
+          restricted: true,
+          permissions: 7,
+          setMin: new anchor.BN(0),
+          setMax: new anchor.BN(1000),
+          maxUses: new anchor.BN(2),
+        })
+        .accountsPartial({
+          counter: quotaPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      await createSession(sessionSigner);
+    });
+
+    it("requires the session usage account once uses are capped", async () => {
+      try {
+        await incrementWithSession(null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionUsageRequired");
+      }
+    });
+
+    it("rejects a session that has used up its quota", async () => {
+      await program.methods
+        .initSessionUsage(quotaId, sessionSigner.publicKey)
+        .accountsPartial({
+          counter: quotaPDA,
+          payer: authority.publicKey,
+        })
+        .rpc();
+
+      await incrementWithSession(sessionUsage);
+      await incrementWithSession(sessionUsage);
+      try {
+        await incrementWithSession(sessionUsage);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionQuotaExceeded");
+      }
+
+      const usage = await program.account.sessionUsage.fetch(sessionUsage);
+      expect(usage.used.toNumber()).to.equal(2);
+      const counterAccount = await program.account.counter.fetch(quotaPDA);
+      expect(counterAccount.count.toNumber()).to.equal(2);
+    });
+
+    it("starts a fresh quota once the session is renewed", async () => {
+      await revokeSession(sessionSigner.publicKey);
+      // Wait for the clock to move on so the renewed token gets a new expiry
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      await createSession(sessionSigner);
+
+      await incrementWithSession(sessionUsage);
+
+      const usage = await program.account.sessionUsage.fetch(sessionUsage);
+      expect(usage.used.toNumber()).to.equal(1);
+    });
+  });
+
+  // ========================================
+  // Ephemeral Rollups Tests
+  // ========================================
+
+  describe("reset period", () => {
+    const periodicId = new anchor.BN(17);
+    const [periodicCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), periodicId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const increment = () =>
+      program.methods
+        .increment(periodicId)
+        .accountsPartial({
+          counter: periodicCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+    const setResetPeriod = (resetPeriod: any) =>
+      program.methods
+        .setResetPeriod(periodicId, resetPeriod)
+        .accountsPartial({
+          counter: periodicCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(periodicId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("rejects an empty period", async () => {
+      try {
+        await setResetPeriod({ seconds: { seconds: new anchor.BN(0) } });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidResetPeriod");
+      }
+    });
+
+    it("starts daily periods at midnight UTC", async () => {
+      await setResetPeriod({ dailyUtc: {} });
+
+      const counterAccount = await program.account.counter.fetch(
+        periodicCounter
+      );
+      expect(counterAccount.resetPeriod).to.deep.equal({ dailyUtc: {} });
+      expect(counterAccount.periodStart.toNumber() % 86400).to.equal(0);
+    });
+
+    it("resets on the first update of a new period", async () => {
+      await setResetPeriod({ seconds: { seconds: new anchor.BN(2) } });
+      await increment();
+      await increment();
+      await increment();
+      const current = await program.account.counter.fetch(periodicCounter);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await increment();
+
+      const next = await program.account.counter.fetch(periodicCounter);
+      expect(next.count.toNumber()).to.equal(1);
+      expect(next.lastPeriodCount.toNumber()).to.equal(
+        current.count.toNumber()
+      );
+      expect(next.periodStart.toNumber()).to.be.greaterThan(
+        current.periodStart.toNumber()
+      );
+    });
+  });
+
+  describe("public counter", () => {
+    const publicId = new anchor.BN(18);
+    const [publicCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), publicId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const contributor = web3.Keypair.generate();
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        publicCounter.toBuffer(),
+        contributor.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    const incrementBy = (
+      amount: number,
+      contribution: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .incrementBy(publicId, new anchor.BN(amount))
+        .accountsPartial({
+          counter: publicCounter,
+          signer: contributor.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+    const setPublic = (isPublic: boolean) =>
+      program.methods
+        .setPublic(publicId, isPublic)
+        .accountsPartial({
+          counter: publicCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const initContribution = () =>
+      program.methods
+        .initContribution(publicId)
+        .accountsPartial({
+          counter: publicCounter,
+          contributor: contributor.publicKey,
+          multisig: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(publicId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      // The contributor pays the rent of its own contribution
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+    });
+
+    it("keeps a private counter to its authority", async () => {
+      try {
+        await incrementBy(1, null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+
+      try {
+        await initContribution();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotPublic");
+      }
+    });
+
+    it("requires a contribution from other signers", async () => {
+      await setPublic(true);
+
+      try {
+        await incrementBy(1, null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ContributionRequired");
+      }
+    });
+
+    it("tracks each contributor's share", async () => {
+      await initContribution();
+      await incrementBy(3, contributionPDA);
+      await incrementBy(2, contributionPDA);
+
+      const contribution = await program.account.contribution.fetch(
+        contributionPDA
+      );
+      expect(contribution.amount.toNumber()).to.equal(5);
+      expect(contribution.updates.toNumber()).to.equal(2);
+      expect(
+        (await program.account.counter.fetch(publicCounter)).count.toNumber()
+      ).to.equal(5);
+    });
+
+    it("still keeps decrements to the authority", async () => {
+      try {
+        await program.methods
+          .decrement(publicId)
+          .accountsPartial({
+            counter: publicCounter,
+            signer: contributor.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: contributionPDA,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .signers([contributor])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("leaderboard", () => {
+    const ids = [20, 21, 22].map((id) => new anchor.BN(id));
+    const counterOf = (id: anchor.BN) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [authority.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+    const [leaderboardPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const setCount = (id: anchor.BN, value: number) =>
+      program.methods
+        .set(id, new anchor.BN(value))
+        .accountsPartial({
+          counter: counterOf(id),
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+    const submitScore = (id: anchor.BN, counter = counterOf(id)) =>
+      program.methods
+        .submitScore(id)
+        .accountsPartial({
+          leaderboard: leaderboardPDA,
+          counter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const ranking = async () =>
+      (await program.account.leaderboard.fetch(leaderboardPDA)).entries.map(
+        (entry) => entry.count.toNumber()
+      );
+
+    before(async () => {
+      await program.methods
+        .initLeaderboard()
+        .accounts({
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      for (const [index, id] of ids.entries()) {
+        await program.methods
+          .initialize(id, ...defaultBounds, null)
+          .accounts({
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        await setCount(id, [5, 9, 7][index]);
+      }
+    });
+
+    it("ranks submitted counters by count", async () => {
+      for (const id of ids) {
+        await submitScore(id);
+      }
+      expect(await ranking()).to.deep.equal([9, 7, 5]);
+
+      const leaderboard = await program.account.leaderboard.fetch(
+        leaderboardPDA
+      );
+      expect(leaderboard.entries[0].counter.toBase58()).to.equal(
+        counterOf(ids[1]).toBase58()
+      );
+      expect(leaderboard.entries[0].authority.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+    });
+
+    it("moves a resubmitted counter instead of adding it twice", async () => {
+      await setCount(ids[0], 12);
+      await submitScore(ids[0]);
+      expect(await ranking()).to.deep.equal([12, 9, 7]);
+    });
+
+    it("only takes counters owned by the program", async () => {
+      try {
+        await submitScore(ids[0], authority.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("AccountOwnedByWrongProgram");
+      }
+    });
+
+    it("only takes submissions signed by the leaderboard's authority", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .submitScore(ids[0])
+          .accountsPartial({
+            leaderboard: leaderboardPDA,
+            counter: counterOf(ids[0]),
+            authority: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+
+    it("prunes closed counters but keeps the ones that still exist", async () => {
+      const closedId = new anchor.BN(38);
+      await program.methods
+        .initialize(closedId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await setCount(closedId, 20);
+      await submitScore(closedId);
+      expect(await ranking()).to.deep.equal([20, 12, 9, 7]);
+
+      const prune = (counter: anchor.web3.PublicKey) =>
+        program.methods
+          .pruneLeaderboard()
+          .accountsPartial({ leaderboard: leaderboardPDA })
+          .remainingAccounts([
+            { pubkey: counter, isSigner: false, isWritable: false },
+          ])
+          .rpc();
+
+      try {
+        await prune(counterOf(closedId));
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotClosed");
+      }
+
+      await program.methods
+        .close(closedId)
+        .accounts({
+          counter: counterOf(closedId),
+          authority: authority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .rpc();
+      await prune(counterOf(closedId));
+      expect(await ranking()).to.deep.equal([12, 9, 7]);
+    });
+  });
+
+  describe("fees", () => {
+    const feeId = new anchor.BN(24);
+    const [feeCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), feeId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), feeCounter.toBuffer()],
+      program.programId
+    );
+    const fee = 100_000;
+
+    const update = (
+      method: "increment" | "decrement",
+      treasury: anchor.web3.PublicKey | null = treasuryPDA
+    ) =>
+      program.methods[method](feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury,
+          feeEscrow: null,
+        })
+        .rpc();
+
+    const withdraw = (amount: number, signer: web3.Keypair, recipient = signer.publicKey) =>
+      program.methods
+        .withdraw(feeId, new anchor.BN(amount))
+        .accountsPartial({
+          counter: feeCounter,
+          signer: signer.publicKey,
+          recipient,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(feeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setFee(feeId, new anchor.BN(fee))
+        .accountsPartial({
+          counter: feeCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+    });
+
+    it("requires the treasury while a fee is set", async () => {
+      try {
+        await update("increment", null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("TreasuryRequired");
+      }
+    });
+
+    it("charges increments but not decrements", async () => {
+      await program.methods
+        .initTreasury(feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      const start = await provider.connection.getBalance(treasuryPDA);
+
+      await update("increment");
+      await update("increment");
+      await update("decrement");
+
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(treasury.collected.toNumber()).to.equal(2 * fee);
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(
+        start + 2 * fee
+      );
+    });
+
+    it("pays the treasury straight from an escrow on the base layer", async () => {
+      const [feeEscrowPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("fee_escrow"),
+          feeCounter.toBuffer(),
+          authority.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      await program.methods
+        .initFeeEscrow(feeId, new anchor.BN(fee))
+        .accountsPartial({
+          counter: feeCounter,
+          payer: authority.publicKey,
+        })
+        .rpc();
+      const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+      const escrowBefore = await provider.connection.getBalance(feeEscrowPDA);
+
+      await program.methods
+        .increment(feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: treasuryPDA,
+          feeEscrow: feeEscrowPDA,
+        })
+        .rpc();
+
+      const escrow = await program.account.feeEscrow.fetch(feeEscrowPDA);
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(escrow.pending.toNumber()).to.equal(0);
+      expect(treasury.collected.toNumber()).to.equal(3 * fee);
+      expect(await provider.connection.getBalance(feeEscrowPDA)).to.equal(
+        escrowBefore - fee
+      );
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(
+        treasuryBefore + fee
+      );
+
+      await program.methods
+        .closeFeeEscrow()
+        .accountsPartial({
+          feeEscrow: feeEscrowPDA,
+          payer: authority.publicKey,
+        })
+        .rpc();
+    });
+
+    it("lets only the authority or admin withdraw", async () => {
+      const stranger = web3.Keypair.generate();
+      try {
+        await withdraw(fee, stranger);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+
+    it("withdraws the fees but keeps the treasury rent-exempt", async () => {
+      const recipient = web3.Keypair.generate().publicKey;
+      const payer = provider.wallet.payer;
+      try {
+        await withdraw(4 * fee, payer, recipient);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InsufficientFunds");
+      }
+
+      await withdraw(3 * fee, payer, recipient);
+      expect(await provider.connection.getBalance(recipient)).to.equal(3 * fee);
+    });
+  });
+
+  describe("rewards", () => {
+    const rewardId = new anchor.BN(28);
+    const [rewardCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), rewardId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [rewardMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_mint"), rewardCounter.toBuffer()],
+      program.programId
+    );
+    let rewardAccount: web3.PublicKey;
+
+    const mintRewards = () =>
+      program.methods
+        .mintRewards(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          rewardMint,
+          contribution: null,
+          recipient: rewardAccount,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(rewardId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .initRewardMint(rewardId, 0)
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setReward(rewardId, new anchor.BN(5))
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      rewardAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        authority.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    });
+
+    it("creates a Token-2022 mint owned by a PDA", async () => {
+      const mint = await getMint(
+        provider.connection,
+        rewardMint,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(mint.mintAuthority!.toBase58()).to.equal(rewardMint.toBase58());
+    });
+
+    it("accrues the reward on increments", async () => {
+      for (const method of ["increment", "increment", "decrement"] as const) {
+        await program.methods[method](rewardId)
+          .accountsPartial({
+            counter: rewardCounter,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .rpc();
+      }
+      const counter = await program.account.counter.fetch(rewardCounter);
+      expect(counter.pendingRewards.toNumber()).to.equal(10);
+    });
+
+    it("mints the accrued rewards to the authority", async () => {
+      await mintRewards();
+      const account = await getAccount(
+        provider.connection,
+        rewardAccount,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(account.amount)).to.equal(10);
+      const counter = await program.account.counter.fetch(rewardCounter);
+      expect(counter.pendingRewards.toNumber()).to.equal(0);
+
+      try {
+        await mintRewards();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("NoPendingRewards");
+      }
+    });
+  });
+
+  describe("rewards on ER", () => {
+    const rewardId = new anchor.BN(29);
+    const [rewardCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), rewardId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [rewardMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_mint"), rewardCounter.toBuffer()],
+      program.programId
+    );
+    // Increments the public counter on ER, earning rewards of its own
+    const contributor = web3.Keypair.generate();
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        rewardCounter.toBuffer(),
+        contributor.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    // Increments in the authority's place, its rewards accruing for the authority
+    const sessionSigner = web3.Keypair.generate();
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction, signer?: web3.Keypair) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      if (signer) {
+        tx.partialSign(signer);
+      }
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(rewardId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .initRewardMint(rewardId, 0)
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setReward(rewardId, new anchor.BN(3))
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setPublic(rewardId, true)
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+      await program.methods
+        .initContribution(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          contributor: contributor.publicKey,
+          multisig: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+      const remainingAccounts =
+        providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+        providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+          ? [
+              {
+                pubkey: new web3.PublicKey(
+                  "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+                ),
+                isSigner: false,
+                isWritable: false,
+              },
+            ]
+          : [];
+      const tx = await program.methods
+        .delegate(rewardId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: rewardCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+      const delegateContributionTx = await program.methods
+        .delegateContribution()
+        .accountsPartial({
+          payer: contributor.publicKey,
+          counter: rewardCounter,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(delegateContributionTx, [contributor], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    });
+
+    it("accrues rewards in the counter on ER", async () => {
+      const tx = await program.methods
+        .increment(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      const counter = program.coder.accounts.decode(
+        "counter",
+        (await providerEphemeralRollup.connection.getAccountInfo(rewardCounter))!
+          .data
+      );
+      expect(counter.pendingRewards.toNumber()).to.equal(3);
+    });
+
+    it("accrues a contributor's rewards in their contribution on ER", async () => {
+      const tx = await program.methods
+        .increment(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          signer: contributor.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: contributionPDA,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx, contributor)).value.err).to.be.null;
+
+      const contribution = program.coder.accounts.decode(
+        "contribution",
+        (await providerEphemeralRollup.connection.getAccountInfo(
+          contributionPDA
+        ))!.data
+      );
+      expect(contribution.pendingRewards.toNumber()).to.equal(3);
+      const counter = program.coder.accounts.decode(
+        "counter",
+        (await providerEphemeralRollup.connection.getAccountInfo(rewardCounter))!
+          .data
+      );
+      expect(counter.pendingRewards.toNumber()).to.equal(3);
+    });
+
+    it("accrues a session signer's rewards in the counter on ER", async () => {
+      await createSession(sessionSigner);
+
+      const tx = await program.methods
+        .increment(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          signer: sessionSigner.publicKey,
+          sessionToken: deriveSessionToken(sessionSigner.publicKey),
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx, sessionSigner)).value.err).to.be.null;
+
+      // The session token was issued by the authority, who gets the rewards
+      const counter = program.coder.accounts.decode(
+        "counter",
+        (await providerEphemeralRollup.connection.getAccountInfo(rewardCounter))!
+          .data
+      );
+      expect(counter.pendingRewards.toNumber()).to.equal(6);
+    });
+
+    it("mints the rewards once undelegated", async () => {
+      const tx = await program.methods
+        .undelegate(rewardId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: rewardCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .remainingAccounts([
+          { pubkey: contributionPDA, isSigner: false, isWritable: true },
+        ])
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(contributionPDA);
+        if (info?.owner.equals(program.programId)) {
+          break;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      const rewardAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        authority.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .mintRewards(rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          rewardMint,
+          contribution: null,
+          recipient: rewardAccount,
+        })
+        .rpc();
+
+      const account = await getAccount(
+        provider.connection,
+        rewardAccount,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(account.amount)).to.equal(6);
+    });
+
+    it("mints a contributor's rewards to their own token account", async () => {
+      const contributorAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        contributor.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const mintTo = (recipient: web3.PublicKey) =>
+        program.methods
+          .mintRewards(rewardId)
+          .accountsPartial({
+            counter: rewardCounter,
+            rewardMint,
+            contribution: contributionPDA,
+            recipient,
+          })
+          .rpc();
+
+      const authorityAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        authority.publicKey,
+        web3.Keypair.generate(),
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      try {
+        await mintTo(authorityAccount);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidRewardAccount");
+      }
+
+      await mintTo(contributorAccount);
+      const account = await getAccount(
+        provider.connection,
+        contributorAccount,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(account.amount)).to.equal(3);
+      const contribution = await program.account.contribution.fetch(
+        contributionPDA
+      );
+      expect(contribution.pendingRewards.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("token gate", () => {
+    const gateId = new anchor.BN(26);
+    const [gateCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), gateId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    let mint: web3.PublicKey;
+    let tokenAccount: web3.PublicKey;
+
+    const increment = (gateTokenAccount: web3.PublicKey | null) =>
+      program.methods
+        .increment(gateId)
+        .accountsPartial({
+          counter: gateCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+          gateTokenAccount,
+        })
+        .rpc();
+
+    const setGate = (gateMint: web3.PublicKey | null, minBalance = 0) =>
+      program.methods
+        .setTokenGate(gateId, gateMint, new anchor.BN(minBalance))
+        .accountsPartial({
+          counter: gateCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(gateId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      [mint, tokenAccount] = await createTokenHolding(TOKEN_PROGRAM_ID, 4);
+      await setGate(mint, 5);
+    });
+
+    it("requires a token account while gated", async () => {
+      try {
+        await increment(null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("GateTokenRequired");
+      }
+    });
+
+    it("rejects accounts of another mint or owner", async () => {
+      const [, otherMint] = await createTokenHolding(TOKEN_PROGRAM_ID, 5);
+      const [, otherOwner] = await createTokenHolding(
+        TOKEN_PROGRAM_ID,
+        5,
+        web3.Keypair.generate().publicKey
+      );
+      for (const account of [otherMint, otherOwner]) {
+        try {
+          await increment(account);
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidGateToken");
+        }
+      }
+    });
+
+    it("enforces the minimum balance", async () => {
+      try {
+        await increment(tokenAccount);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("GateBalanceTooLow");
+      }
+
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        tokenAccount,
+        provider.wallet.payer,
+        1
+      );
+      await increment(tokenAccount);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.count.toNumber()).to.equal(1);
+    });
+
+    it("accepts Token-2022 accounts", async () => {
+      const [mint2022, account2022] = await createTokenHolding(
+        TOKEN_2022_PROGRAM_ID,
+        1
+      );
+      await setGate(mint2022);
+      await increment(account2022);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.gateMint.toBase58()).to.equal(mint2022.toBase58());
+      expect(counter.count.toNumber()).to.equal(2);
+    });
+
+    it("lifts the gate", async () => {
+      await setGate(null);
+      await increment(null);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.gateMint.toBase58()).to.equal(
+        web3.PublicKey.default.toBase58()
+      );
+      expect(counter.count.toNumber()).to.equal(3);
+    });
+  });
+
+  describe("token gate on ER", () => {
+    const gateId = new anchor.BN(27);
+    const [gateCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), gateId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    let tokenAccount: web3.PublicKey;
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    const incrementOnEr = async (gateTokenAccount: web3.PublicKey | null) =>
+      sendToEr(
+        await program.methods
+          .increment(gateId)
+          .accountsPartial({
+            counter: gateCounter,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+            gateTokenAccount,
+          })
+          .transaction()
+      );
+
+    before(async () => {
+      await program.methods
+        .initialize(gateId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      let mint: web3.PublicKey;
+      [mint, tokenAccount] = await createTokenHolding(TOKEN_2022_PROGRAM_ID, 1);
+      await program.methods
+        .setTokenGate(gateId, mint, new anchor.BN(0))
+        .accountsPartial({
+          counter: gateCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const remainingAccounts =
+        providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+        providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+          ? [
+              {
+                pubkey: new web3.PublicKey(
+                  "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+                ),
+                isSigner: false,
+                isWritable: false,
+              },
+            ]
+          : [];
+      const tx = await program.methods
+        .delegate(gateId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: gateCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    });
+
+    it("rejects gated increments on ER without a token account", async () => {
+      expect((await incrementOnEr(null)).value.err).to.not.be.null;
+    });
+
+    it("reads the undelegated token account on ER", async () => {
+      expect((await incrementOnEr(tokenAccount)).value.err).to.be.null;
+      const counter = program.coder.accounts.decode(
+        "counter",
+        (await providerEphemeralRollup.connection.getAccountInfo(gateCounter))!
+          .data
+      );
+      expect(counter.count.toNumber()).to.equal(1);
+    });
+
+    it("undelegates the gated counter", async () => {
+      const tx = await program.methods
+        .undelegate(gateId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: gateCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(gateCounter);
+        if (info?.owner.equals(program.programId)) {
+          break;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("fees on ER", () => {
+    const escrowId = new anchor.BN(25);
+    const [escrowCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), escrowId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), escrowCounter.toBuffer()],
+      program.programId
+    );
+    const [feeEscrowPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("fee_escrow"),
+        escrowCounter.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const fee = 100_000;
+
+    const remainingAccounts =
+      providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+      providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+        ? [
+            {
+              pubkey: new web3.PublicKey(
+                "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+              ),
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : [];
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    const incrementOnEr = async () =>
+      sendToEr(
+        await program.methods
+          .increment(escrowId)
+          .accountsPartial({
+            counter: escrowCounter,
+            signer: authority.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: feeEscrowPDA,
+          })
+          .transaction()
+      );
+
+    before(async () => {
+      await program.methods
+        .initialize(escrowId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setFee(escrowId, new anchor.BN(fee))
+        .accountsPartial({
+          counter: escrowCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .initTreasury(escrowId)
+        .accountsPartial({
+          counter: escrowCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      // Enough for two fees on top of the escrow's rent
+      await program.methods
+        .initFeeEscrow(escrowId, new anchor.BN(2.5 * fee))
+        .accountsPartial({
+          counter: escrowCounter,
+          payer: authority.publicKey,
+        })
+        .rpc();
+
+      const delegateTreasuryIx = await program.methods
+        .delegateTreasury(escrowId)
+        .accountsPartial({
+          payer: authority.publicKey,
+          counter: escrowCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+      const delegateFeeEscrowIx = await program.methods
+        .delegateFeeEscrow()
+        .accountsPartial({
+          payer: authority.publicKey,
+          counter: escrowCounter,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+      const tx = await program.methods
+        .delegate(escrowId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: escrowCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions([delegateTreasuryIx, delegateFeeEscrowIx])
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    });
+
+    it("accrues fees against the escrow on ER", async () => {
+      expect((await incrementOnEr()).value.err).to.be.null;
+      expect((await incrementOnEr()).value.err).to.be.null;
+
+      const info =
+        await providerEphemeralRollup.connection.getAccountInfo(feeEscrowPDA);
+      const escrow = program.coder.accounts.decode("feeEscrow", info!.data);
+      expect(escrow.pending.toNumber()).to.equal(2 * fee);
+    });
+
+    it("rejects updates the deposit can't pay for", async () => {
+      expect((await incrementOnEr()).value.err).to.not.be.null;
+    });
+
+    it("settles the escrow into the treasury on undelegate", async () => {
+      const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+      const tx = await program.methods
+        .undelegate(escrowId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: escrowCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: treasuryPDA,
+        })
+        .remainingAccounts([
+          { pubkey: feeEscrowPDA, isSigner: false, isWritable: true },
+        ])
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(feeEscrowPDA);
+        if (info?.owner.equals(program.programId)) {
+          break;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      const escrow = await program.account.feeEscrow.fetch(feeEscrowPDA);
+      expect(treasury.collected.toNumber()).to.equal(2 * fee);
+      expect(escrow.pending.toNumber()).to.equal(0);
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(
+        treasuryBefore + 2 * fee
+      );
+    });
+
+    it("refunds a settled escrow on close", async () => {
+      await program.methods
+        .closeFeeEscrow()
+        .accountsPartial({
+          feeEscrow: feeEscrowPDA,
+          payer: authority.publicKey,
+        })
+        .rpc();
+      expect(await program.account.feeEscrow.fetchNullable(feeEscrowPDA)).to.be
+        .null;
+    });
+  });
+
+  describe("leaderboard on ER", () => {
+    const scoreId = new anchor.BN(23);
+    const [scoreCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), scoreId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [leaderboardPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const remainingAccounts =
+      providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+      providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+        ? [
+            {
+              pubkey: new web3.PublicKey(
+                "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+              ),
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : [];
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    // Wait for the base layer leaderboard to lead with `count`
+    const waitForTopCount = async (count: number) => {
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(leaderboardPDA);
+        const leaderboard = program.coder.accounts.decode(
+          "leaderboard",
+          info!.data
+        );
+        if (leaderboard.entries[0]?.count.toNumber() === count) {
+          return;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+      throw new Error("Leaderboard was not committed");
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(scoreId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .set(scoreId, new anchor.BN(50))
+        .accountsPartial({
+          counter: scoreCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .rpc();
+
+      const delegateLeaderboardIx = await program.methods
+        .delegateLeaderboard(null)
+        .accounts({
+          payer: authority.publicKey,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+      const tx = await program.methods
+        .delegate(scoreId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: scoreCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .preInstructions([delegateLeaderboardIx])
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    });
+
+    it("submits a score on ER", async () => {
+      const incrementIx = await program.methods
+        .increment(scoreId)
+        .accountsPartial({
+          counter: scoreCounter,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .instruction();
+      const tx = await program.methods
+        .submitScore(scoreId)
+        .accountsPartial({
+          leaderboard: leaderboardPDA,
+          counter: scoreCounter,
+          authority: authority.publicKey,
+        })
+        .preInstructions([incrementIx])
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      const info =
+        await providerEphemeralRollup.connection.getAccountInfo(leaderboardPDA);
+      const leaderboard = program.coder.accounts.decode("leaderboard", info!.data);
+      expect(leaderboard.entries[0].count.toNumber()).to.equal(51);
+      expect(leaderboard.entries[0].counter.toBase58()).to.equal(
+        scoreCounter.toBase58()
+      );
+    });
+
+    it("commits the leaderboard to Solana", async () => {
+      const tx = await program.methods
+        .commitLeaderboard()
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          leaderboard: leaderboardPDA,
+        })
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+      await waitForTopCount(51);
+    });
+
+    it("undelegates the leaderboard and counter", async () => {
+      const undelegateLeaderboardIx = await program.methods
+        .undelegateLeaderboard()
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          leaderboard: leaderboardPDA,
+        })
+        .instruction();
+      const tx = await program.methods
+        .undelegate(scoreId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: scoreCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .preInstructions([undelegateLeaderboardIx])
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(leaderboardPDA);
+        if (info?.owner.equals(program.programId)) {
+          return;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+      throw new Error("Leaderboard was not undelegated");
+    });
+  });
+
+  describe("public counter on ER", () => {
+    const sharedId = new anchor.BN(19);
+    const [sharedCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), sharedId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    // One contributor joins before the counter is delegated, the other after
+    const early = web3.Keypair.generate();
+    const late = web3.Keypair.generate();
+    const contributionOf = (contributor: web3.Keypair) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("contribution"),
+          sharedCounter.toBuffer(),
+          contributor.publicKey.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+    const remainingAccounts =
+      providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+      providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+        ? [
+            {
+              pubkey: new web3.PublicKey(
+                "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+              ),
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : [];
+
+    const join = async (contributor: web3.Keypair) => {
+      await program.methods
+        .initContribution(sharedId)
+        .accountsPartial({
+          counter: sharedCounter,
+          contributor: contributor.publicKey,
+          multisig: null,
+        })
+        .signers([contributor])
+        .rpc();
+      const tx = await program.methods
+        .delegateContribution()
+        .accountsPartial({
+          payer: contributor.publicKey,
+          counter: sharedCounter,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(tx, [contributor], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    };
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction, signer?: web3.Keypair) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      if (signer) {
+        tx.partialSign(signer);
+      }
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    const contribute = async (contributor: web3.Keypair, amount: number) => {
+      const tx = await program.methods
+        .incrementBy(sharedId, new anchor.BN(amount))
+        .accountsPartial({
+          counter: sharedCounter,
+          signer: contributor.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: contributionOf(contributor),
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+      return sendToEr(tx, contributor);
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(sharedId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setPublic(sharedId, true)
+        .accountsPartial({
+          counter: sharedCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const fund = new anchor.web3.Transaction();
+      for (const contributor of [early, late]) {
+        fund.add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: LAMPORTS_PER_SOL / 10,
+          })
+        );
+      }
+      await provider.sendAndConfirm(fund);
+
+      await join(early);
+      const tx = await program.methods
+        .delegate(sharedId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: sharedCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+      await join(late);
+    });
+
+    it("lets contributors increment concurrently on ER", async () => {
+      const results = await Promise.all([
+        contribute(early, 2),
+        contribute(late, 3),
+        contribute(early, 1),
+      ]);
+      for (const result of results) {
+        expect(result.value.err).to.be.null;
+      }
+
+      const info =
+        await providerEphemeralRollup.connection.getAccountInfo(sharedCounter);
+      expect(
+        program.coder.accounts.decode("counter", info!.data).count.toNumber()
+      ).to.equal(6);
+    });
+
+    it("undelegates the contributions along with the counter", async () => {
+      const tx = await program.methods
+        .undelegate(sharedId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: sharedCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .remainingAccounts(
+          [early, late].map((contributor) => ({
+            pubkey: contributionOf(contributor),
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(
+          contributionOf(late)
+        );
+        if (info?.owner.equals(program.programId)) {
+          break;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      const earlyShare = await program.account.contribution.fetch(
+        contributionOf(early)
+      );
+      const lateShare = await program.account.contribution.fetch(
+        contributionOf(late)
+      );
+      expect(earlyShare.amount.toNumber()).to.equal(3);
+      expect(earlyShare.updates.toNumber()).to.equal(2);
+      expect(lateShare.amount.toNumber()).to.equal(3);
+      expect(
+        (await program.account.counter.fetch(sharedCounter)).count.toNumber()
+      ).to.equal(6);
+    });
+  });
+
+  describe("rate limit on ER", () => {
+    const limitedId = new anchor.BN(16);
+    const [limitedCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), limitedId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const increment = () =>
+      program.methods.increment(limitedId).accountsPartial({
+        counter: limitedCounter,
+        signer: authority.publicKey,
+        sessionToken: null,
+        history: null,
+        multisig: null,
+        sessionUsage: null,
+        contribution: null,
+        treasury: null,
+        feeEscrow: null,
+      });
+
+    const setRateLimit = (
+      minSlots: number,
+      minSeconds: number,
+      maxPerWindow = 0,
+      windowSeconds = 0
+    ) =>
+      program.methods
+        .setRateLimit(limitedId, {
+          minSlots: new anchor.BN(minSlots),
+          minSeconds: new anchor.BN(minSeconds),
+          maxPerWindow: new anchor.BN(maxPerWindow),
+          windowSeconds: new anchor.BN(windowSeconds),
+        })
+        .accountsPartial({
+          counter: limitedCounter,
+          authority: authority.publicKey,
+        });
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    const erCounter = async () =>
+      program.coder.accounts.decode(
+        "counter",
+        (await providerEphemeralRollup.connection.getAccountInfo(limitedCounter))!
+          .data
+      );
+
+    // ER slots are short but not instant, wait until it has moved past `slot`
+    const waitForErSlot = async (slot: number) => {
+      while ((await providerEphemeralRollup.connection.getSlot()) <= slot) {
+        await new Promise((resolve) => setTimeout(resolve, 50));
+      }
+    };
+
+    const delegate = async () => {
+      const remainingAccounts =
+        providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+        providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+          ? [
+              {
+                pubkey: new web3.PublicKey(
+                  "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+                ),
+                isSigner: false,
+                isWritable: false,
+              },
+            ]
+          : [];
+      const tx = await program.methods
+        .delegate(limitedId)
+        .accounts({
+          payer: authority.publicKey,
+          pda: limitedCounter,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .transaction();
+      await provider.sendAndConfirm(tx, [provider.wallet.payer], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
+    };
+
+    // Undelegate and wait for the counter to be back on Solana
+    const undelegate = async () => {
+      const tx = await program.methods
+        .undelegate(limitedId)
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          counter: limitedCounter,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.be.null;
+
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(limitedCounter);
+        if (info?.owner.equals(program.programId)) {
+          return;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+      throw new Error("Counter was not undelegated");
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(limitedId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await setRateLimit(1, 0).rpc();
+      // Solana's slots are far ahead of the ER's, this one must not hold the ER back
+      await increment().rpc();
+      await delegate();
+    });
+
+    it("drops only the slot of the last update on delegate", async () => {
+      const onSolana = await program.account.counter.fetch(limitedCounter);
+      const counter = await erCounter();
+      expect(counter.count.toNumber()).to.equal(1);
+      expect(counter.lastUpdateSlot.toNumber()).to.equal(0);
+      expect(counter.lastUpdateTs.toNumber()).to.be.greaterThan(0);
+      expect(counter.lastUpdateTs.toNumber()).to.equal(
+        onSolana.lastUpdateTs.toNumber()
+      );
+    });
+
+    it("rejects two updates in the same ER slot", async () => {
+      const tx = await increment()
+        .preInstructions([await increment().instruction()])
+        .transaction();
+      expect((await sendToEr(tx)).value.err).to.not.be.null;
+    });
+
+    it("accepts updates in later ER slots", async () => {
+      expect((await sendToEr(await increment().transaction())).value.err).to.be
+        .null;
+      const first = await erCounter();
+
+      await waitForErSlot(first.lastUpdateSlot.toNumber());
+      expect((await sendToEr(await increment().transaction())).value.err).to.be
+        .null;
+      const second = await erCounter();
+
+      expect(second.count.toNumber()).to.equal(3);
+      expect(second.lastUpdateSlot.toNumber()).to.be.greaterThan(
+        first.lastUpdateSlot.toNumber()
+      );
+    });
+
+    it("holds updates on ER to the Clock's timestamp", async () => {
+      expect((await sendToEr(await setRateLimit(0, 2).transaction())).value.err)
+        .to.be.null;
 
-  before(async function () {
-    const balance = await provider.connection.getBalance(
-      anchor.Wallet.local().publicKey
-    );
-    console.log("Current balance is", balance / LAMPORTS_PER_SOL, " SOL", "\n");
+      // Slots keep ticking on the ER, the timestamp still has to move on
+      expect((await sendToEr(await increment().transaction())).value.err).to.be
+        .null;
+      expect((await sendToEr(await increment().transaction())).value.err).to.not
+        .be.null;
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      expect((await sendToEr(await increment().transaction())).value.err).to.be
+        .null;
+      expect((await erCounter()).count.toNumber()).to.equal(5);
+    });
+
+    it("keeps the rate limit timestamps on the way back to Solana", async () => {
+      const lastUpdateTs = (await erCounter()).lastUpdateTs.toNumber();
+      await undelegate();
+
+      const counterAccount = await program.account.counter.fetch(
+        limitedCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(5);
+      expect(counterAccount.rateLimit.minSeconds.toNumber()).to.equal(2);
+      expect(counterAccount.lastUpdateSlot.toNumber()).to.equal(0);
+      expect(counterAccount.lastUpdateTs.toNumber()).to.equal(lastUpdateTs);
+
+      // The ER's last slot doesn't count against Solana's, its timestamp still does
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await increment().rpc();
+      expect(
+        (await program.account.counter.fetch(limitedCounter)).count.toNumber()
+      ).to.equal(6);
+    });
+
+    it("still applies the limit after an undelegate/delegate cycle", async () => {
+      await setRateLimit(0, 0, 1, 3600).rpc();
+      await increment().rpc();
+
+      await delegate();
+      expect((await sendToEr(await increment().transaction())).value.err).to.not
+        .be.null;
+
+      await undelegate();
+      try {
+        await increment().rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+      expect(
+        (await program.account.counter.fetch(limitedCounter)).count.toNumber()
+      ).to.equal(7);
+    });
   });
 
-  // ========================================
-  // Base Layer Tests
-  // ========================================
+  describe("multisig on ER", () => {
+    const memberId = new anchor.BN(36);
+    const [memberCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), memberId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [multisigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig"), memberCounter.toBuffer()],
+      program.programId
+    );
+    // Delegates, updates and undelegates the counter without the wallet
+    const member = web3.Keypair.generate();
+    // The member's rewards accrue here, the multisig can't receive them
+    const [memberContribution] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        memberCounter.toBuffer(),
+        member.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
 
-  describe("initialize", () => {
-    it("initializes a counter with count 0", async () => {
-      const start = Date.now();
-      let tx = await program.methods
-        .initialize()
+    const remainingAccounts =
+      providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+      providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+        ? [
+            {
+              pubkey: new web3.PublicKey(
+                "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+              ),
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : [];
+
+    // Send a transaction to the ER and report its result rather than throwing
+    const sendToEr = async (tx: web3.Transaction, signer?: web3.Keypair) => {
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      if (signer) {
+        tx.partialSign(signer);
+      }
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      return providerEphemeralRollup.connection.confirmTransaction(
+        txHash,
+        "confirmed"
+      );
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(memberId, ...defaultBounds, {
+          signers: [authority.publicKey, member.publicKey],
+          threshold: 2,
+          updatePolicy: { anySigner: {} },
+        })
         .accounts({
           authority: authority.publicKey,
+          multisig: multisigPDA,
         })
-        .transaction();
-
-      const txHash = await provider.sendAndConfirm(
-        tx,
-        [provider.wallet.payer],
-        {
-          skipPreflight: true,
-          commitment: "confirmed",
-        }
+        .rpc();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: member.publicKey,
+            lamports: LAMPORTS_PER_SOL / 10,
+          })
+        )
       );
-      const duration = Date.now() - start;
-      console.log(`${duration}ms (Base Layer) Initialize txHash: ${txHash}`);
 
-      const counterAccount = await program.account.counter.fetch(counterPDA);
-      expect(counterAccount.count.toNumber()).to.equal(0);
-      expect(counterAccount.authority.toBase58()).to.equal(
-        authority.publicKey.toBase58()
+      // Reward increments, set through a proposal the member approves
+      await program.methods
+        .initRewardMint(memberId, 0)
+        .accountsPartial({
+          counter: memberCounter,
+          authority: member.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([member])
+        .rpc();
+      await program.methods
+        .createProposal(memberId, { setReward: { amount: new anchor.BN(2) } })
+        .accountsPartial({
+          counter: memberCounter,
+          proposer: authority.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .approveProposal(memberId, new anchor.BN(0))
+        .accountsPartial({
+          counter: memberCounter,
+          member: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+      await program.methods
+        .executeProposal(memberId, new anchor.BN(0))
+        .accountsPartial({
+          counter: memberCounter,
+          proposer: authority.publicKey,
+          executor: authority.publicKey,
+          history: null,
+          treasury: null,
+          pendingSet: null,
+          recipient: null,
+        })
+        .rpc();
+
+      // Members may keep a contribution on a counter that isn't public
+      await program.methods
+        .initContribution(memberId)
+        .accountsPartial({
+          counter: memberCounter,
+          contributor: member.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([member])
+        .rpc();
+      await provider.sendAndConfirm(
+        await program.methods
+          .delegateContribution()
+          .accountsPartial({
+            payer: member.publicKey,
+            counter: memberCounter,
+          })
+          .remainingAccounts(remainingAccounts)
+          .transaction(),
+        [member],
+        { skipPreflight: true, commitment: "confirmed" }
       );
     });
-  });
 
-  describe("increment", () => {
-    it("increments the counter by 1", async () => {
-      const start = Date.now();
-      let tx = await program.methods
-        .increment()
+    it("lets a member delegate the counter", async () => {
+      const tx = await program.methods
+        .delegate(memberId)
         .accounts({
-          authority: authority.publicKey,
+          payer: member.publicKey,
+          pda: memberCounter,
+          multisig: multisigPDA,
         })
+        .remainingAccounts(remainingAccounts)
         .transaction();
+      await provider.sendAndConfirm(tx, [member], {
+        skipPreflight: true,
+        commitment: "confirmed",
+      });
 
-      const txHash = await provider.sendAndConfirm(
-        tx,
-        [provider.wallet.payer],
-        {
-          skipPreflight: true,
-          commitment: "confirmed",
-        }
-      );
-      const duration = Date.now() - start;
-      console.log(`${duration}ms (Base Layer) Increment txHash: ${txHash}`);
+      const info = await provider.connection.getAccountInfo(memberCounter);
+      expect(info?.owner.equals(program.programId)).to.be.false;
+    });
 
-      const counterAccount = await program.account.counter.fetch(counterPDA);
-      expect(counterAccount.count.toNumber()).to.be.greaterThan(0);
+    it("lets a member increment on ER, accruing the rewards in their contribution", async () => {
+      const increment = (contribution: web3.PublicKey | null) =>
+        program.methods
+          .increment(memberId)
+          .accountsPartial({
+            counter: memberCounter,
+            signer: member.publicKey,
+            sessionToken: null,
+            history: null,
+            multisig: multisigPDA,
+            sessionUsage: null,
+            contribution,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .transaction();
+
+      // Without a contribution the rewards would go to the multisig
+      expect((await sendToEr(await increment(null), member)).value.err).to.not.be
+        .null;
+      expect(
+        (await sendToEr(await increment(memberContribution), member)).value.err
+      ).to.be.null;
+
+      const decode = async (name: string, address: web3.PublicKey) =>
+        program.coder.accounts.decode(
+          name,
+          (await providerEphemeralRollup.connection.getAccountInfo(address))!
+            .data
+        );
+      const counter = await decode("counter", memberCounter);
+      expect(counter.count.toNumber()).to.equal(1);
+      expect(counter.pendingRewards.toNumber()).to.equal(0);
+      const contribution = await decode("contribution", memberContribution);
+      expect(contribution.pendingRewards.toNumber()).to.equal(2);
     });
 
-    it("increments multiple times", async () => {
-      const counterBefore = await program.account.counter.fetch(counterPDA);
-      const initialCount = counterBefore.count.toNumber();
+    it("rejects undelegation by anyone outside the multisig", async () => {
+      const stranger = web3.Keypair.generate();
+      const tx = await program.methods
+        .undelegate(memberId)
+        .accountsPartial({
+          payer: stranger.publicKey,
+          counter: memberCounter,
+          history: null,
+          multisig: multisigPDA,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .transaction();
+      expect((await sendToEr(tx, stranger)).value.err).to.not.be.null;
+    });
 
-      // Increment 3 times
-      for (let i = 0; i < 3; i++) {
-        await program.methods
-          .increment()
-          .accounts({
-            authority: authority.publicKey,
-          })
-          .rpc();
-      }
+    it("lets a member undelegate the counter", async () => {
+      const tx = await program.methods
+        .undelegate(memberId)
+        .accountsPartial({
+          payer: member.publicKey,
+          counter: memberCounter,
+          history: null,
+          multisig: multisigPDA,
+          sessionUsage: null,
+          treasury: null,
+        })
+        .remainingAccounts([
+          { pubkey: memberContribution, isSigner: false, isWritable: true },
+        ])
+        .transaction();
+      expect((await sendToEr(tx, member)).value.err).to.be.null;
 
-      const counterAccount = await program.account.counter.fetch(counterPDA);
-      expect(counterAccount.count.toNumber()).to.equal(initialCount + 3);
+      for (let attempt = 0; attempt < 20; attempt++) {
+        const info = await provider.connection.getAccountInfo(memberCounter);
+        if (info?.owner.equals(program.programId)) {
+          break;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+      expect(
+        (await program.account.counter.fetch(memberCounter)).count.toNumber()
+      ).to.equal(1);
     });
   });
 
-  describe("decrement", () => {
-    it("decrements the counter by 1", async () => {
-      const counterBefore = await program.account.counter.fetch(counterPDA);
-      const initialCount = counterBefore.count.toNumber();
+  describe("delegation", () => {
+    // Session signer whose updates on the ER are capped by a delegated usage account
+    const sessionSigner = web3.Keypair.generate();
+    const sessionUsage = deriveSessionUsage(counterPDA, sessionSigner.publicKey);
 
+    it("creates a history for the counter", async () => {
       await program.methods
-        .decrement()
-        .accounts({
+        .initHistory(counterId)
+        .accountsPartial({
+          counter: counterPDA,
           authority: authority.publicKey,
+          multisig: null,
         })
         .rpc();
 
-      const counterAccount = await program.account.counter.fetch(counterPDA);
-      expect(counterAccount.count.toNumber()).to.equal(initialCount - 1);
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.counter.toBase58()).to.equal(counterPDA.toBase58());
+      expect(history.total.toNumber()).to.equal(0);
     });
-  });
 
-  describe("set", () => {
-    it("sets the counter to a specific value", async () => {
+    it("caps session updates on the counter", async () => {
+      await createSession(sessionSigner);
+
       await program.methods
-        .set(new anchor.BN(42))
-        .accounts({
+        .setSessionScope(counterId, {
+          restricted: true,
+          permissions: 7,
+          setMin: new anchor.BN(0),
+          setMax: new anchor.BN(1000),
+          maxUses: new anchor.BN(1),
+        })
+        .accountsPartial({
+          counter: counterPDA,
           authority: authority.publicKey,
         })
         .rpc();
 
-      const counterAccount = await program.account.counter.fetch(counterPDA);
-      expect(counterAccount.count.toNumber()).to.equal(42);
+      await program.methods
+        .initSessionUsage(counterId, sessionSigner.publicKey)
+        .accountsPartial({
+          counter: counterPDA,
+          payer: authority.publicKey,
+        })
+        .rpc();
     });
-  });
-
-  // ========================================
-  // Ephemeral Rollups Tests
-  // ========================================
 
-  describe("delegation", () => {
     it("delegates counter to ER", async () => {
       // First reset counter to a known value
       await program.methods
-        .set(new anchor.BN(100))
-        .accounts({
-          authority: authority.publicKey,
+        .set(counterId, new anchor.BN(100))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: null,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
         })
         .rpc();
 
@@ -189,12 +4463,35 @@ describe("counter", () => {
           ]
           : [];
 
+      // The history and session usage have to be delegated while the counter is
+      // still on the base layer
+      const delegateHistoryIx = await program.methods
+        .delegateHistory(counterId)
+        .accountsPartial({
+          payer: authority.publicKey,
+          counter: counterPDA,
+          multisig: null,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+      const delegateSessionUsageIx = await program.methods
+        .delegateSessionUsage(counterId, sessionSigner.publicKey)
+        .accountsPartial({
+          payer: authority.publicKey,
+          counter: counterPDA,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+
       let tx = await program.methods
-        .delegate()
+        .delegate(counterId)
         .accounts({
           payer: authority.publicKey,
+          pda: counterPDA,
+          multisig: null,
         })
         .remainingAccounts(remainingAccounts)
+        .preInstructions([delegateHistoryIx, delegateSessionUsageIx])
         .transaction();
 
       const txHash = await provider.sendAndConfirm(
@@ -209,13 +4506,52 @@ describe("counter", () => {
       console.log(`${duration}ms (Base Layer) Delegate txHash: ${txHash}`);
     });
 
+    it("refuses to close a delegated counter", async () => {
+      try {
+        await program.methods
+          .close(counterId)
+          .accounts({
+            counter: counterPDA,
+            authority: authority.publicKey,
+            recipient: authority.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterDelegated");
+      }
+    });
+
+    it("refuses to migrate a delegated counter", async () => {
+      try {
+        await program.methods
+          .migrate(counterId)
+          .accounts({
+            counter: counterPDA,
+            payer: authority.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterDelegated");
+      }
+    });
+
     it("increments counter on ER", async () => {
       const start = Date.now();
       // Build transaction using base program
       let tx = await program.methods
-        .increment()
-        .accounts({
-          authority: authority.publicKey,
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: historyPDA,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
         })
         .transaction();
 
@@ -237,13 +4573,195 @@ describe("counter", () => {
       console.log(`${duration}ms (ER) Increment txHash: ${txHash}`);
     });
 
+    it("increments counter by an amount on ER", async () => {
+      const start = Date.now();
+      // Build transaction using base program
+      let tx = await program.methods
+        .incrementBy(counterId, new anchor.BN(10))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: historyPDA,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+
+      // Set up for ER connection
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      // Send using raw connection to avoid Anchor response parsing issues
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      await providerEphemeralRollup.connection.confirmTransaction(txHash, "confirmed");
+
+      const duration = Date.now() - start;
+      console.log(`${duration}ms (ER) IncrementBy txHash: ${txHash}`);
+    });
+
+    it("compares and sets counter on ER", async () => {
+      const erCounter = async () =>
+        program.coder.accounts.decode(
+          "counter",
+          (await providerEphemeralRollup.connection.getAccountInfo(counterPDA))
+            .data
+        );
+      const before = await erCounter();
+
+      let tx = await program.methods
+        .compareAndSet(counterId, before.count, before.count.addn(1))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          sessionToken: null,
+          history: historyPDA,
+          multisig: null,
+          sessionUsage: null,
+          contribution: null,
+          treasury: null,
+          feeEscrow: null,
+        })
+        .transaction();
+      tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+      tx.recentBlockhash = (
+        await providerEphemeralRollup.connection.getLatestBlockhash()
+      ).blockhash;
+      tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+      const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+        tx.serialize(),
+        { skipPreflight: true }
+      );
+      await providerEphemeralRollup.connection.confirmTransaction(txHash, "confirmed");
+
+      const after = await erCounter();
+      expect(after.count.toNumber()).to.equal(before.count.toNumber() + 1);
+      expect(after.sequence.toNumber()).to.equal(before.sequence.toNumber() + 1);
+    });
+
+    it("counts session updates on ER against the quota", async () => {
+      const incrementWithSession = async () => {
+        let tx = await program.methods
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: sessionSigner.publicKey,
+            sessionToken: deriveSessionToken(sessionSigner.publicKey),
+            history: null,
+            sessionUsage,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .transaction();
+        tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+        tx.recentBlockhash = (
+          await providerEphemeralRollup.connection.getLatestBlockhash()
+        ).blockhash;
+        tx.partialSign(sessionSigner);
+        tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+        const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+          tx.serialize(),
+          { skipPreflight: true }
+        );
+        return providerEphemeralRollup.connection.confirmTransaction(
+          txHash,
+          "confirmed"
+        );
+      };
+
+      expect((await incrementWithSession()).value.err).to.be.null;
+      expect((await incrementWithSession()).value.err).to.not.be.null;
+    });
+
+    it("enforces the program pause on ER", async () => {
+      const setPaused = (paused: boolean) =>
+        program.methods
+          .setPaused(paused)
+          .accounts({
+            admin: authority.publicKey,
+          })
+          .rpc({ commitment: "confirmed" });
+
+      // The ER follows the base layer config, give it a moment to pick up the change
+      const waitForErPause = async (paused: boolean) => {
+        for (let attempt = 0; attempt < 20; attempt++) {
+          const info =
+            await providerEphemeralRollup.connection.getAccountInfo(configPDA);
+          if (
+            info &&
+            program.coder.accounts.decode("programConfig", info.data).paused ===
+              paused
+          ) {
+            return;
+          }
+          await new Promise((resolve) => setTimeout(resolve, 500));
+        }
+        throw new Error("ER did not pick up the config change");
+      };
+
+      await setPaused(true);
+      try {
+        await waitForErPause(true);
+
+        let tx = await program.methods
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            config: configPDA,
+            sessionToken: null,
+            history: historyPDA,
+            multisig: null,
+            sessionUsage: null,
+            contribution: null,
+            treasury: null,
+            feeEscrow: null,
+          })
+          .transaction();
+        tx.feePayer = providerEphemeralRollup.wallet.publicKey;
+        tx.recentBlockhash = (
+          await providerEphemeralRollup.connection.getLatestBlockhash()
+        ).blockhash;
+        tx = await providerEphemeralRollup.wallet.signTransaction(tx);
+
+        const txHash = await providerEphemeralRollup.connection.sendRawTransaction(
+          tx.serialize(),
+          { skipPreflight: true }
+        );
+        const result = await providerEphemeralRollup.connection.confirmTransaction(
+          txHash,
+          "confirmed"
+        );
+        expect(result.value.err).to.not.be.null;
+      } finally {
+        await setPaused(false);
+      }
+      await waitForErPause(false);
+    });
+
     it("commits counter state on ER to Solana", async () => {
       const start = Date.now();
       // Build transaction using base program
       let tx = await program.methods
-        .commit()
-        .accounts({
+        .commit(counterId)
+        .accountsPartial({
           payer: providerEphemeralRollup.wallet.publicKey,
+          counter: counterPDA,
+          history: historyPDA,
+          sessionUsage,
+          treasury: null,
         })
         .transaction();
 
@@ -284,9 +4802,13 @@ describe("counter", () => {
       const start = Date.now();
       // Build transaction using base program
       let tx = await program.methods
-        .undelegate()
-        .accounts({
+        .undelegate(counterId)
+        .accountsPartial({
           payer: providerEphemeralRollup.wallet.publicKey,
+          counter: counterPDA,
+          history: historyPDA,
+          sessionUsage,
+          treasury: null,
         })
         .transaction();
 
@@ -312,6 +4834,18 @@ describe("counter", () => {
       const counterAccount = await program.account.counter.fetch(counterPDA);
       console.log(`Counter value after undelegation: ${counterAccount.count}`);
       expect(counterAccount.count.toNumber()).to.be.at.least(100);
+
+      // The ER increments were recorded in the history and committed with the counter
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      const latest =
+        history.entries[(history.total.toNumber() - 1) % history.entries.length];
+      expect(history.total.toNumber()).to.equal(3);
+      expect(latest.new.toNumber()).to.equal(counterAccount.count.toNumber());
+      expect(latest.signer.toBase58()).to.equal(authority.publicKey.toBase58());
+
+      // So was the session usage counted on the ER
+      const usage = await program.account.sessionUsage.fetch(sessionUsage);
+      expect(usage.used.toNumber()).to.equal(1);
     });
   });
 });
@@ -13,10 +13,10 @@ interface CounterAccount {
 
 /**
  * Hook to interact with the Counter program on Solana.
- * Each user has their own counter derived from their public key (PDA).
+ * Each user can own many counters, derived from their public key and a counter id (PDA).
  * Provides real-time updates via WebSocket subscriptions.
  */
-export function useCounterProgram() {
+export function useCounterProgram(counterId: number = 0) {
     const { connection } = useConnection();
     const wallet = useWallet();
 
@@ -47,14 +47,14 @@ export function useCounterProgram() {
         return new Program<Counter>(IDL as Counter, provider);
     }, [connection, wallet.publicKey, wallet.signTransaction, wallet.signAllTransactions]);
 
-    // Derive PDA from wallet public key
+    // Derive PDA from wallet public key and counter id
     const derivePDA = useCallback((authority: PublicKey) => {
         const [pda] = PublicKey.findProgramAddressSync(
-            [authority.toBuffer()],
+            [authority.toBuffer(), new BN(counterId).toArrayLike(Buffer, "le", 8)],
             new PublicKey(IDL.address)
         );
         return pda;
-    }, []);
+    }, [counterId]);
 
     // Auto-derive counter PDA when wallet connects
     useEffect(() => {
@@ -125,6 +125,7 @@ export function useCounterProgram() {
     }, [program, counterPubkey, connection, fetchCounterAccount]);
 
     // Initialize a new counter (uses PDA derived from wallet)
+    // Bounded to 0..=1000 and wrapping, like the original demo counter
     const initialize = useCallback(async (): Promise<string> => {
         if (!program || !wallet.publicKey) {
             throw new Error("Wallet not connected");
@@ -135,9 +136,10 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .initialize()
+                .initialize(new BN(counterId), new BN(0), new BN(1000), { wrap: {} }, null)
                 .accounts({
                     authority: wallet.publicKey,
+                    multisig: null,
                 })
                 .rpc();
 
@@ -151,7 +153,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, fetchCounterAccount]);
+    }, [program, wallet.publicKey, fetchCounterAccount, counterId]);
 
     // Increment the counter
     const increment = useCallback(async (): Promise<string> => {
@@ -164,9 +166,16 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .increment()
-                .accounts({
-                    authority: wallet.publicKey,
+                .increment(new BN(counterId))
+                .accountsPartial({
+                    counter: counterPubkey,
+                    signer: wallet.publicKey,
+                    history: null,
+                    operators: null,
+                    sessionToken: null,
+                    multisig: null,
+                    contribution: null,
+                    treasury: null,
                 })
                 .rpc();
 
@@ -178,7 +187,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     // Decrement the counter
     const decrement = useCallback(async (): Promise<string> => {
@@ -191,9 +200,16 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .decrement()
-                .accounts({
-                    authority: wallet.publicKey,
+                .decrement(new BN(counterId))
+                .accountsPartial({
+                    counter: counterPubkey,
+                    signer: wallet.publicKey,
+                    history: null,
+                    operators: null,
+                    sessionToken: null,
+                    multisig: null,
+                    contribution: null,
+                    treasury: null,
                 })
                 .rpc();
 
@@ -205,7 +221,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     // Set the counter to a specific value
     const set = useCallback(async (value: number): Promise<string> => {
@@ -218,9 +234,16 @@ export function useCounterProgram() {
 
         try {
             const tx = await program.methods
-                .set(new BN(value))
-                .accounts({
-                    authority: wallet.publicKey,
+                .set(new BN(counterId), new BN(value))
+                .accountsPartial({
+                    counter: counterPubkey,
+                    signer: wallet.publicKey,
+                    history: null,
+                    operators: null,
+                    sessionToken: null,
+                    multisig: null,
+                    contribution: null,
+                    treasury: null,
                 })
                 .rpc();
 
@@ -232,7 +255,7 @@ export function useCounterProgram() {
         } finally {
             setIsLoading(false);
         }
-    }, [program, wallet.publicKey, counterPubkey]);
+    }, [program, wallet.publicKey, counterPubkey, counterId]);
 
     return {
         program,
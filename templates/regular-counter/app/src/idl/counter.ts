@@ -14,19 +14,20 @@ export type Counter = {
   },
   "instructions": [
     {
-      "name": "decrement",
+      "name": "acceptAuthority",
       "docs": [
-        "Decrement the counter by 1"
+        "Accept a pending authority transfer (step 2 of 2)",
+        "Must be signed by the proposed authority"
       ],
       "discriminator": [
-        106,
-        227,
-        168,
-        59,
-        248,
-        27,
-        150,
-        101
+        107,
+        86,
+        198,
+        91,
+        33,
+        12,
+        107,
+        160
       ],
       "accounts": [
         {
@@ -36,176 +37,7029 @@ export type Counter = {
             "seeds": [
               {
                 "kind": "account",
-                "path": "authority"
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
               }
             ]
           }
         },
         {
-          "name": "authority",
+          "name": "newAuthority",
           "signer": true
         }
       ],
-      "args": []
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
     },
     {
-      "name": "increment",
+      "name": "addOperator",
       "docs": [
-        "Increment the counter by 1",
-        "Wraps around to 0 if count exceeds 1000 (for demo purposes)"
+        "Add `operator` to the allow-list, or replace its permissions if already listed",
+        "`permissions` is a mask of Operator::INCREMENT, Operator::DECREMENT and Operator::SET"
       ],
       "discriminator": [
-        11,
-        18,
-        104,
-        9,
-        104,
-        174,
-        59,
-        33
+        149,
+        142,
+        187,
+        68,
+        33,
+        250,
+        87,
+        105
       ],
       "accounts": [
         {
           "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
           "writable": true,
           "pda": {
             "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
               {
                 "kind": "account",
-                "path": "authority"
+                "path": "counter"
               }
             ]
           }
         },
         {
           "name": "authority",
-          "signer": true
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
         }
       ],
-      "args": []
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "operator",
+          "type": "pubkey"
+        },
+        {
+          "name": "permissions",
+          "type": "u8"
+        }
+      ]
     },
     {
-      "name": "initialize",
+      "name": "approveProposal",
       "docs": [
-        "Initialize a new counter account with count set to 0",
-        "Uses PDA derivation with user's public key for deterministic addresses"
+        "Approve a pending proposal, signed by one of the multisig's signers",
+        "Approving twice has no further effect"
       ],
       "discriminator": [
-        175,
-        175,
-        109,
-        31,
-        13,
-        152,
-        155,
-        237
+        136,
+        108,
+        102,
+        85,
+        98,
+        114,
+        7,
+        147
       ],
       "accounts": [
         {
           "name": "counter",
-          "writable": true,
           "pda": {
             "seeds": [
               {
                 "kind": "account",
-                "path": "authority"
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
               }
             ]
           }
         },
         {
-          "name": "authority",
+          "name": "multisig",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "proposal",
           "writable": true,
-          "signer": true
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  114,
+                  111,
+                  112,
+                  111,
+                  115,
+                  97,
+                  108
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "multisig"
+              },
+              {
+                "kind": "arg",
+                "path": "index"
+              }
+            ]
+          }
         },
         {
-          "name": "systemProgram",
-          "address": "11111111111111111111111111111111"
+          "name": "member",
+          "docs": [
+            "One of the multisig's signers"
+          ],
+          "signer": true
         }
       ],
-      "args": []
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "index",
+          "type": "u64"
+        }
+      ]
     },
     {
-      "name": "set",
+      "name": "cancelSet",
       "docs": [
-        "Set the counter to a specific value"
+        "Drop a scheduled set before it is executed, returning its rent to the authority"
       ],
       "discriminator": [
-        198,
-        51,
-        53,
-        241,
-        116,
-        29,
         126,
-        194
+        166,
+        199,
+        90,
+        119,
+        225,
+        180,
+        92
       ],
       "accounts": [
         {
           "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "pendingSet",
           "writable": true,
           "pda": {
             "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  101,
+                  110,
+                  100,
+                  105,
+                  110,
+                  103,
+                  95,
+                  115,
+                  101,
+                  116
+                ]
+              },
               {
                 "kind": "account",
-                "path": "authority"
+                "path": "counter"
               }
             ]
           }
         },
         {
           "name": "authority",
-          "signer": true
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
         }
       ],
       "args": [
         {
-          "name": "value",
+          "name": "counterId",
           "type": "u64"
         }
       ]
-    }
-  ],
-  "accounts": [
+    },
     {
-      "name": "counter",
+      "name": "close",
+      "docs": [
+        "Close the counter account and return its rent to the recipient",
+        "Pass the authority as recipient to reclaim the lamports to the owner's wallet",
+        "The counter's history, operators, treasury, pending set and multisig are closed",
+        "along with it whenever they exist, their rent and any fees left in the treasury",
+        "going to the recipient, so a counter created again at the same address starts from",
+        "scratch",
+        "A counter held by a multisig is closed through a Close proposal instead",
+        "Its contributions are closed too when passed as remaining accounts, each followed",
+        "by its contributor, who gets the rent back"
+      ],
       "discriminator": [
-        255,
-        176,
-        4,
-        245,
-        188,
-        253,
-        124,
-        25
+        98,
+        165,
+        201,
+        177,
+        108,
+        65,
+        206,
+        96
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        },
+        {
+          "name": "recipient",
+          "writable": true
+        },
+        {
+          "name": "history",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "pendingSet",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  101,
+                  110,
+                  100,
+                  105,
+                  110,
+                  103,
+                  95,
+                  115,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "created"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
       ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "counterUnderflow",
-      "msg": "Counter cannot go below zero"
-    }
-  ],
-  "types": [
+    },
     {
-      "name": "counter",
-      "type": {
-        "kind": "struct",
-        "fields": [
-          {
-            "name": "count",
-            "docs": [
-              "The current count value"
-            ],
-            "type": "u64"
-          },
-          {
-            "name": "authority",
-            "docs": [
-              "The authority who can update the counter"
-            ],
+      "name": "compareAndSet",
+      "docs": [
+        "Set the counter to `new` only if it currently holds `expected`",
+        "Fails with CountMismatch otherwise, so concurrent writers can't silently",
+        "overwrite each other; `new` must lie within the counter's bounds"
+      ],
+      "discriminator": [
+        51,
+        50,
+        220,
+        198,
+        182,
+        215,
+        242,
+        38
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "expected",
+          "type": "u64"
+        },
+        {
+          "name": "new",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "createProposal",
+      "docs": [
+        "Propose `action` on a counter whose authority is a multisig",
+        "Must be signed by one of its signers, which counts as the first approval"
+      ],
+      "discriminator": [
+        132,
+        116,
+        68,
+        174,
+        216,
+        160,
+        198,
+        22
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "proposal",
+          "docs": [
+            "A proposal left at this index by a multisig closed with an earlier counter at the",
+            "same address is overwritten"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  114,
+                  111,
+                  112,
+                  111,
+                  115,
+                  97,
+                  108
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "multisig"
+              },
+              {
+                "kind": "account",
+                "path": "multisig.proposal_count",
+                "account": "multisig"
+              }
+            ]
+          }
+        },
+        {
+          "name": "proposer",
+          "docs": [
+            "One of the multisig's signers"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "action",
+          "type": {
+            "defined": {
+              "name": "multisigAction"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "decrement",
+      "docs": [
+        "Decrement the counter by 1",
+        "Going below `min` wraps, saturates or fails depending on the overflow policy"
+      ],
+      "discriminator": [
+        106,
+        227,
+        168,
+        59,
+        248,
+        27,
+        150,
+        101
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "decrementBy",
+      "docs": [
+        "Decrement the counter by `amount` in a single instruction",
+        "Follows the same bounds and overflow policy as decrement"
+      ],
+      "discriminator": [
+        103,
+        195,
+        73,
+        36,
+        174,
+        179,
+        60,
+        246
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "executeProposal",
+      "docs": [
+        "Carry out a proposal once the multisig's threshold of signers approved it",
+        "Anyone may execute; the proposal is closed and its rent returned to the proposer",
+        "Count changes go through the same pause, rate limit, fee and history as an",
+        "update, the executor paying the fee; the other actions stand in for the",
+        "instructions only the authority may sign"
+      ],
+      "discriminator": [
+        186,
+        60,
+        116,
+        133,
+        108,
+        128,
+        111,
+        28
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Must still hold the authority, so proposals die with a handover",
+            "Receives the rent of a cancelled set and is closed by Close"
+          ],
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "proposal",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  114,
+                  111,
+                  112,
+                  111,
+                  115,
+                  97,
+                  108
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "multisig"
+              },
+              {
+                "kind": "arg",
+                "path": "index"
+              }
+            ]
+          }
+        },
+        {
+          "name": "proposer",
+          "writable": true,
+          "relations": [
+            "proposal"
+          ]
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Counted as unpaused when left out, as in Update"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "executor",
+          "docs": [
+            "Pays the counter's fee on count changes, as the signer of an update does"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to by count changes when present, as in Update"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required by AddOperator and RemoveOperator"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of count changes while the counter charges one, and pays out",
+            "withdraw"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "pendingSet",
+          "docs": [
+            "Required by ScheduleSet, which creates it if needed, and CancelSet"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  101,
+                  110,
+                  100,
+                  105,
+                  110,
+                  103,
+                  95,
+                  115,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "recipient",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "index",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "executeSet",
+      "docs": [
+        "Carry out a scheduled set once its delay has passed",
+        "Anyone may execute it; the rent of the pending set goes back to the authority",
+        "that scheduled it",
+        "The set is held to the rate limit and recorded like any other, with the executor",
+        "paying the counter's fee"
+      ],
+      "discriminator": [
+        214,
+        64,
+        226,
+        31,
+        189,
+        199,
+        108,
+        118
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "pendingSet",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  101,
+                  110,
+                  100,
+                  105,
+                  110,
+                  103,
+                  95,
+                  115,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "relations": [
+            "pendingSet"
+          ]
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Counted as unpaused when left out, as in Update"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "executor",
+          "docs": [
+            "Pays the counter's fee, as the signer of an update does"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to when present, as in Update"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee while the counter charges one, as in Update"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "increment",
+      "docs": [
+        "Increment the counter by 1",
+        "Going above `max` wraps, saturates or fails depending on the overflow policy"
+      ],
+      "discriminator": [
+        11,
+        18,
+        104,
+        9,
+        104,
+        174,
+        59,
+        33
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "incrementBy",
+      "docs": [
+        "Increment the counter by `amount` in a single instruction",
+        "Follows the same bounds and overflow policy as increment"
+      ],
+      "discriminator": [
+        103,
+        82,
+        124,
+        55,
+        231,
+        50,
+        146,
+        138
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initConfig",
+      "docs": [
+        "Create the program-wide config with `admin` as the key allowed to pause",
+        "Can only be called once, by the program's upgrade authority"
+      ],
+      "discriminator": [
+        23,
+        235,
+        115,
+        232,
+        168,
+        96,
+        1,
+        231
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "programData",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  143,
+                  42,
+                  154,
+                  15,
+                  254,
+                  109,
+                  157,
+                  228,
+                  19,
+                  21,
+                  42,
+                  254,
+                  19,
+                  186,
+                  108,
+                  126,
+                  2,
+                  67,
+                  85,
+                  129,
+                  33,
+                  18,
+                  59,
+                  17,
+                  122,
+                  187,
+                  46,
+                  104,
+                  76,
+                  54,
+                  12,
+                  182
+                ]
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                2,
+                168,
+                246,
+                145,
+                78,
+                136,
+                161,
+                176,
+                226,
+                16,
+                21,
+                62,
+                247,
+                99,
+                174,
+                43,
+                0,
+                194,
+                185,
+                61,
+                22,
+                193,
+                36,
+                210,
+                192,
+                83,
+                122,
+                16,
+                4,
+                128,
+                0,
+                0
+              ]
+            }
+          }
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "admin",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "initContribution",
+      "docs": [
+        "Create the account tracking the contributor's increments to a public counter"
+      ],
+      "discriminator": [
+        174,
+        165,
+        46,
+        111,
+        205,
+        50,
+        193,
+        229
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "contributor"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contributor",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initHistory",
+      "docs": [
+        "Create the optional change history of a counter",
+        "Once it exists, pass it to increment, decrement and set to record every change"
+      ],
+      "discriminator": [
+        100,
+        184,
+        105,
+        125,
+        152,
+        243,
+        211,
+        70
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The authority, or any one of the signers of the multisig holding it"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initLeaderboard",
+      "docs": [
+        "Create the authority's leaderboard, ranking the counters submitted to it by count"
+      ],
+      "discriminator": [
+        70,
+        179,
+        5,
+        151,
+        152,
+        16,
+        47,
+        15
+      ],
+      "accounts": [
+        {
+          "name": "leaderboard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  101,
+                  97,
+                  100,
+                  101,
+                  114,
+                  98,
+                  111,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "authority"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "initOperators",
+      "docs": [
+        "Create the operator allow-list of a counter",
+        "Listed operators may then sign increment, decrement and set in the authority's place"
+      ],
+      "discriminator": [
+        111,
+        109,
+        243,
+        44,
+        156,
+        233,
+        195,
+        208
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The authority, or any one of the signers of the multisig holding it"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initRewardMint",
+      "docs": [
+        "Create the counter's Token-2022 reward mint, a PDA that is its own mint authority",
+        "Nothing is minted until an amount is set via set_reward"
+      ],
+      "discriminator": [
+        7,
+        81,
+        73,
+        12,
+        174,
+        180,
+        120,
+        165
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  109,
+                  105,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The authority, or any one of the signers of the multisig holding it"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "decimals",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "initTreasury",
+      "docs": [
+        "Create the treasury collecting the counter's fees"
+      ],
+      "discriminator": [
+        105,
+        152,
+        173,
+        51,
+        158,
+        151,
+        49,
+        14
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "The authority, or any one of the signers of the multisig holding it"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initialize",
+      "docs": [
+        "Initialize a new counter account with count set to `min`",
+        "Uses PDA derivation with user's public key and a counter id for deterministic addresses,",
+        "so a single authority can own many independent counters",
+        "`min`, `max` and `overflow_policy` bound every later increment, decrement and set",
+        "Passing `multisig` (along with the multisig account) makes an M-of-N multisig the",
+        "counter's authority instead of the signer"
+      ],
+      "discriminator": [
+        175,
+        175,
+        109,
+        31,
+        13,
+        152,
+        155,
+        237
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "authority"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "docs": [
+            "Pays for the counter, and becomes its authority unless a multisig is created"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Created, and made the counter's authority, when initialize is passed a multisig"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "min",
+          "type": "u64"
+        },
+        {
+          "name": "max",
+          "type": "u64"
+        },
+        {
+          "name": "overflowPolicy",
+          "type": {
+            "defined": {
+              "name": "overflowPolicy"
+            }
+          }
+        },
+        {
+          "name": "multisig",
+          "type": {
+            "option": {
+              "defined": {
+                "name": "multisigConfig"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "migrate",
+      "docs": [
+        "Bring a counter created by an earlier program version up to the current layout",
+        "Reallocs the account to the current size, topping up its rent from `payer`, and",
+        "fills the fields the old layout lacked with their defaults",
+        "Anyone may pay for a migration since it never changes how the counter behaves",
+        "Counters in the original layout lived at the PDA of their authority alone; their",
+        "authority moves them to `relocated`, the address of `[authority, counter_id]`,",
+        "and gets the old account's rent back"
+      ],
+      "discriminator": [
+        155,
+        234,
+        231,
+        146,
+        236,
+        158,
+        162,
+        30
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true
+        },
+        {
+          "name": "relocated",
+          "docs": [
+            "The new address of a counter in the original layout, which is closed into it",
+            "Only passed for such counters, by their authority"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "payer"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "proposeAuthority",
+      "docs": [
+        "Propose a new authority for the counter (step 1 of 2)",
+        "Proposing again replaces the pending authority; the PDA address never changes",
+        "Propose Pubkey::default() to cancel a pending transfer"
+      ],
+      "discriminator": [
+        20,
+        148,
+        236,
+        198,
+        76,
+        119,
+        99,
+        142
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "newAuthority",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "pruneLeaderboard",
+      "docs": [
+        "Drop the entries of closed counters, passed as remaining accounts",
+        "Anyone may prune; a counter that still exists keeps its place"
+      ],
+      "discriminator": [
+        118,
+        104,
+        214,
+        67,
+        93,
+        0,
+        98,
+        80
+      ],
+      "accounts": [
+        {
+          "name": "leaderboard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  101,
+                  97,
+                  100,
+                  101,
+                  114,
+                  98,
+                  111,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "leaderboard.authority",
+                "account": "leaderboard"
+              }
+            ]
+          }
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "removeOperator",
+      "docs": [
+        "Remove `operator` from the allow-list"
+      ],
+      "discriminator": [
+        84,
+        183,
+        126,
+        251,
+        137,
+        150,
+        214,
+        134
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "operator",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "scheduleSet",
+      "docs": [
+        "Schedule a set to `value` that anyone can execute once `delay` seconds have passed",
+        "The authority can cancel it until then; only one set can be pending per counter"
+      ],
+      "discriminator": [
+        235,
+        68,
+        14,
+        46,
+        218,
+        1,
+        175,
+        47
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "pendingSet",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  112,
+                  101,
+                  110,
+                  100,
+                  105,
+                  110,
+                  103,
+                  95,
+                  115,
+                  101,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "value",
+          "type": "u64"
+        },
+        {
+          "name": "delay",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "set",
+      "docs": [
+        "Set the counter to a specific value",
+        "The value must lie within the counter's `min..=max` bounds"
+      ],
+      "discriminator": [
+        198,
+        51,
+        53,
+        241,
+        116,
+        29,
+        126,
+        194
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority, an operator permitted to perform the instruction,",
+            "a session signer acting for the authority, or a member of the multisig",
+            "holding the authority where its update policy allows",
+            "Writable as it pays the counter's fee, even when it isn't the transaction's fee payer"
+          ],
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "sessionToken",
+          "docs": [
+            "Lets a short-lived session key sign in the authority's place"
+          ],
+          "optional": true
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Holds the program-wide pause, counted as unpaused when left out",
+            "(as before init_config has run)"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "history",
+          "docs": [
+            "Appended to on every change when present"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  104,
+                  105,
+                  115,
+                  116,
+                  111,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "operators",
+          "docs": [
+            "Required when the signer is an operator rather than the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  111,
+                  112,
+                  101,
+                  114,
+                  97,
+                  116,
+                  111,
+                  114,
+                  115
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "multisig",
+          "docs": [
+            "Required when the signer is a member of the multisig holding the authority"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  109,
+                  117,
+                  108,
+                  116,
+                  105,
+                  115,
+                  105,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "contribution",
+          "docs": [
+            "Required from signers incrementing a public counter they hold no other rights on"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  116,
+                  114,
+                  105,
+                  98,
+                  117,
+                  116,
+                  105,
+                  111,
+                  110
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              },
+              {
+                "kind": "account",
+                "path": "signer"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "docs": [
+            "Receives the fee of increments and sets while the counter charges one"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "gateTokenAccount",
+          "docs": [
+            "Required for increments while the counter is token gated, an SPL Token or",
+            "Token-2022 account of the gate mint owned by the signer"
+          ],
+          "optional": true
+        },
+        {
+          "name": "rewardMint",
+          "docs": [
+            "Required for increments while the counter mints rewards, see set_reward"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "docs": [
+            "Receives the signer's rewards, a Token-2022 account of the reward mint"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram",
+          "optional": true,
+          "address": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "value",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setAdmin",
+      "docs": [
+        "Hand the config over to a new admin"
+      ],
+      "discriminator": [
+        251,
+        163,
+        0,
+        52,
+        91,
+        194,
+        187,
+        92
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "newAdmin",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setCallerProgram",
+      "docs": [
+        "Let `program` increment the counter through CPI, None to revoke it",
+        "The program signs with its PDA of [CALLER_SIGNER_SEED, counter address]"
+      ],
+      "discriminator": [
+        170,
+        32,
+        132,
+        244,
+        93,
+        29,
+        76,
+        67
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "program",
+          "type": {
+            "option": "pubkey"
+          }
+        }
+      ]
+    },
+    {
+      "name": "setFee",
+      "docs": [
+        "Charge `fee` lamports for every increment and set on the counter, 0 to make",
+        "them free again; decrements are never charged",
+        "The signer pays into the counter's Treasury, created via init_treasury"
+      ],
+      "discriminator": [
+        18,
+        154,
+        24,
+        18,
+        237,
+        214,
+        19,
+        80
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "fee",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setFrozen",
+      "docs": [
+        "Freeze or unfreeze the counter",
+        "While frozen, increment, decrement and set fail with Paused"
+      ],
+      "discriminator": [
+        62,
+        87,
+        99,
+        96,
+        206,
+        47,
+        204,
+        18
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "frozen",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setPaused",
+      "docs": [
+        "Pause or resume increment, decrement and set on every counter"
+      ],
+      "discriminator": [
+        91,
+        60,
+        125,
+        192,
+        176,
+        225,
+        166,
+        218
+      ],
+      "accounts": [
+        {
+          "name": "config",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "config"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "paused",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setPublic",
+      "docs": [
+        "Open the counter to increments from any signer, or close it again",
+        "Signers with no other rights on the counter must pass their Contribution,",
+        "created via init_contribution, which records their share"
+      ],
+      "discriminator": [
+        79,
+        228,
+        216,
+        119,
+        143,
+        73,
+        52,
+        177
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "public",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setRateLimit",
+      "docs": [
+        "Limit how often increment, decrement and set may be called on the counter",
+        "Every signer is held to it, the authority included; see RateLimit for the fields"
+      ],
+      "discriminator": [
+        42,
+        212,
+        44,
+        91,
+        198,
+        58,
+        60,
+        239
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "rateLimit",
+          "type": {
+            "defined": {
+              "name": "rateLimit"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "setResetPeriod",
+      "docs": [
+        "Reset the counter at every boundary of `reset_period`",
+        "The reset happens lazily, on the first update in a new period"
+      ],
+      "discriminator": [
+        243,
+        158,
+        68,
+        191,
+        101,
+        64,
+        105,
+        142
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "resetPeriod",
+          "type": {
+            "defined": {
+              "name": "resetPeriod"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "setReward",
+      "docs": [
+        "Mint `amount` tokens of the reward mint to the signer on every increment, 0 to stop",
+        "Incrementing signers must then pass the reward mint, their Token-2022 account",
+        "of it and the Token-2022 program"
+      ],
+      "discriminator": [
+        143,
+        113,
+        125,
+        109,
+        166,
+        210,
+        95,
+        50
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "setTokenGate",
+      "docs": [
+        "Only let holders of `gate_mint` increment the counter, None to lift the gate",
+        "Incrementing signers must pass a token account of the mint they own with a",
+        "balance of at least `min_balance`, or of 1 when it is 0",
+        "Accounts of both the SPL Token and the Token-2022 program are accepted"
+      ],
+      "discriminator": [
+        181,
+        246,
+        120,
+        133,
+        255,
+        105,
+        150,
+        113
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "counter"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "gateMint",
+          "type": {
+            "option": "pubkey"
+          }
+        },
+        {
+          "name": "minBalance",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "submitScore",
+      "docs": [
+        "Rank a counter on a leaderboard by its current count",
+        "Only the leaderboard's authority may submit, the count is read from the counter",
+        "itself; a counter already on the board is moved to its new place"
+      ],
+      "discriminator": [
+        212,
+        128,
+        45,
+        22,
+        112,
+        82,
+        85,
+        235
+      ],
+      "accounts": [
+        {
+          "name": "leaderboard",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  108,
+                  101,
+                  97,
+                  100,
+                  101,
+                  114,
+                  98,
+                  111,
+                  97,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "leaderboard.authority",
+                "account": "leaderboard"
+              }
+            ]
+          }
+        },
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true,
+          "relations": [
+            "leaderboard"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "withdraw",
+      "docs": [
+        "Move `amount` of the collected fees to the recipient",
+        "Either the counter's authority or the program admin may withdraw; the treasury",
+        "keeps enough lamports to stay rent-exempt"
+      ],
+      "discriminator": [
+        183,
+        18,
+        70,
+        156,
+        148,
+        109,
+        161,
+        34
+      ],
+      "accounts": [
+        {
+          "name": "counter",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "counter.seed_authority",
+                "account": "counter"
+              },
+              {
+                "kind": "arg",
+                "path": "counterId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  116,
+                  114,
+                  101,
+                  97,
+                  115,
+                  117,
+                  114,
+                  121
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "counter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "config",
+          "docs": [
+            "Only needed for the admin to withdraw"
+          ],
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  111,
+                  110,
+                  102,
+                  105,
+                  103
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "signer",
+          "docs": [
+            "The counter's authority or the program admin"
+          ],
+          "signer": true
+        },
+        {
+          "name": "recipient",
+          "writable": true
+        }
+      ],
+      "args": [
+        {
+          "name": "counterId",
+          "type": "u64"
+        },
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "contribution",
+      "discriminator": [
+        182,
+        187,
+        14,
+        111,
+        72,
+        167,
+        242,
+        212
+      ]
+    },
+    {
+      "name": "counter",
+      "discriminator": [
+        255,
+        176,
+        4,
+        245,
+        188,
+        253,
+        124,
+        25
+      ]
+    },
+    {
+      "name": "counterHistory",
+      "discriminator": [
+        43,
+        21,
+        146,
+        74,
+        250,
+        212,
+        105,
+        134
+      ]
+    },
+    {
+      "name": "leaderboard",
+      "discriminator": [
+        247,
+        186,
+        238,
+        243,
+        194,
+        30,
+        9,
+        36
+      ]
+    },
+    {
+      "name": "multisig",
+      "discriminator": [
+        224,
+        116,
+        121,
+        186,
+        68,
+        161,
+        79,
+        236
+      ]
+    },
+    {
+      "name": "operators",
+      "discriminator": [
+        166,
+        36,
+        163,
+        187,
+        138,
+        13,
+        64,
+        52
+      ]
+    },
+    {
+      "name": "pendingSet",
+      "discriminator": [
+        242,
+        3,
+        119,
+        46,
+        251,
+        112,
+        145,
+        10
+      ]
+    },
+    {
+      "name": "programConfig",
+      "discriminator": [
+        196,
+        210,
+        90,
+        231,
+        144,
+        149,
+        140,
+        63
+      ]
+    },
+    {
+      "name": "proposal",
+      "discriminator": [
+        26,
+        94,
+        189,
+        187,
+        116,
+        136,
+        53,
+        33
+      ]
+    },
+    {
+      "name": "sessionToken",
+      "discriminator": [
+        233,
+        4,
+        115,
+        14,
+        46,
+        21,
+        1,
+        15
+      ]
+    },
+    {
+      "name": "treasury",
+      "discriminator": [
+        238,
+        239,
+        123,
+        238,
+        89,
+        1,
+        168,
+        253
+      ]
+    }
+  ],
+  "events": [
+    {
+      "name": "authorityProposed",
+      "discriminator": [
+        244,
+        117,
+        94,
+        112,
+        53,
+        151,
+        35,
+        89
+      ]
+    },
+    {
+      "name": "authorityTransferred",
+      "discriminator": [
+        245,
+        109,
+        179,
+        54,
+        135,
+        92,
+        22,
+        64
+      ]
+    },
+    {
+      "name": "callerProgramChanged",
+      "discriminator": [
+        245,
+        199,
+        220,
+        55,
+        134,
+        58,
+        206,
+        5
+      ]
+    },
+    {
+      "name": "configUpdated",
+      "discriminator": [
+        40,
+        241,
+        230,
+        122,
+        11,
+        19,
+        198,
+        194
+      ]
+    },
+    {
+      "name": "contributionInitialized",
+      "discriminator": [
+        155,
+        68,
+        84,
+        116,
+        148,
+        227,
+        228,
+        191
+      ]
+    },
+    {
+      "name": "counterChanged",
+      "discriminator": [
+        98,
+        53,
+        157,
+        176,
+        193,
+        167,
+        71,
+        242
+      ]
+    },
+    {
+      "name": "counterClosed",
+      "discriminator": [
+        61,
+        84,
+        59,
+        97,
+        131,
+        189,
+        51,
+        193
+      ]
+    },
+    {
+      "name": "counterFrozen",
+      "discriminator": [
+        105,
+        180,
+        30,
+        110,
+        104,
+        64,
+        188,
+        244
+      ]
+    },
+    {
+      "name": "counterInitialized",
+      "discriminator": [
+        115,
+        205,
+        233,
+        189,
+        129,
+        219,
+        117,
+        64
+      ]
+    },
+    {
+      "name": "counterMigrated",
+      "discriminator": [
+        61,
+        167,
+        237,
+        18,
+        8,
+        146,
+        186,
+        149
+      ]
+    },
+    {
+      "name": "counterPublicChanged",
+      "discriminator": [
+        69,
+        112,
+        242,
+        74,
+        96,
+        232,
+        15,
+        4
+      ]
+    },
+    {
+      "name": "feeChanged",
+      "discriminator": [
+        103,
+        252,
+        132,
+        250,
+        1,
+        49,
+        116,
+        145
+      ]
+    },
+    {
+      "name": "feesWithdrawn",
+      "discriminator": [
+        234,
+        15,
+        0,
+        119,
+        148,
+        241,
+        40,
+        21
+      ]
+    },
+    {
+      "name": "historyInitialized",
+      "discriminator": [
+        189,
+        138,
+        180,
+        254,
+        29,
+        103,
+        17,
+        216
+      ]
+    },
+    {
+      "name": "leaderboardInitialized",
+      "discriminator": [
+        135,
+        70,
+        99,
+        96,
+        246,
+        187,
+        226,
+        226
+      ]
+    },
+    {
+      "name": "multisigCreated",
+      "discriminator": [
+        94,
+        25,
+        238,
+        110,
+        95,
+        40,
+        251,
+        66
+      ]
+    },
+    {
+      "name": "operatorChanged",
+      "discriminator": [
+        231,
+        79,
+        62,
+        226,
+        190,
+        139,
+        176,
+        51
+      ]
+    },
+    {
+      "name": "operatorsInitialized",
+      "discriminator": [
+        138,
+        118,
+        243,
+        80,
+        98,
+        181,
+        236,
+        56
+      ]
+    },
+    {
+      "name": "proposalApproved",
+      "discriminator": [
+        70,
+        49,
+        155,
+        228,
+        157,
+        43,
+        88,
+        49
+      ]
+    },
+    {
+      "name": "proposalCreated",
+      "discriminator": [
+        186,
+        8,
+        160,
+        108,
+        81,
+        13,
+        51,
+        206
+      ]
+    },
+    {
+      "name": "proposalExecuted",
+      "discriminator": [
+        92,
+        213,
+        189,
+        201,
+        101,
+        83,
+        111,
+        83
+      ]
+    },
+    {
+      "name": "rateLimitChanged",
+      "discriminator": [
+        24,
+        29,
+        212,
+        173,
+        28,
+        76,
+        149,
+        211
+      ]
+    },
+    {
+      "name": "resetPeriodChanged",
+      "discriminator": [
+        13,
+        90,
+        243,
+        121,
+        136,
+        102,
+        92,
+        12
+      ]
+    },
+    {
+      "name": "rewardChanged",
+      "discriminator": [
+        55,
+        132,
+        228,
+        67,
+        187,
+        161,
+        34,
+        17
+      ]
+    },
+    {
+      "name": "rewardMintInitialized",
+      "discriminator": [
+        86,
+        31,
+        80,
+        53,
+        216,
+        255,
+        226,
+        133
+      ]
+    },
+    {
+      "name": "scorePruned",
+      "discriminator": [
+        145,
+        98,
+        29,
+        138,
+        65,
+        185,
+        132,
+        253
+      ]
+    },
+    {
+      "name": "scoreSubmitted",
+      "discriminator": [
+        15,
+        74,
+        143,
+        188,
+        62,
+        88,
+        81,
+        104
+      ]
+    },
+    {
+      "name": "setCancelled",
+      "discriminator": [
+        47,
+        167,
+        5,
+        211,
+        245,
+        53,
+        188,
+        159
+      ]
+    },
+    {
+      "name": "setScheduled",
+      "discriminator": [
+        150,
+        12,
+        104,
+        13,
+        14,
+        9,
+        24,
+        106
+      ]
+    },
+    {
+      "name": "tokenGateChanged",
+      "discriminator": [
+        102,
+        81,
+        146,
+        157,
+        49,
+        79,
+        171,
+        169
+      ]
+    },
+    {
+      "name": "treasuryInitialized",
+      "discriminator": [
+        199,
+        73,
+        174,
+        205,
+        59,
+        145,
+        55,
+        179
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "counterUnderflow",
+      "msg": "Counter cannot go below its minimum"
+    },
+    {
+      "code": 6001,
+      "name": "invalidAuth",
+      "msg": "Invalid authentication"
+    },
+    {
+      "code": 6002,
+      "name": "notPendingAuthority",
+      "msg": "Signer is not the pending authority"
+    },
+    {
+      "code": 6003,
+      "name": "overflow",
+      "msg": "Counter cannot go above its maximum"
+    },
+    {
+      "code": 6004,
+      "name": "valueOutOfRange",
+      "msg": "Value is outside the counter's min..=max bounds"
+    },
+    {
+      "code": 6005,
+      "name": "invalidBounds",
+      "msg": "Counter min must not exceed max"
+    },
+    {
+      "code": 6006,
+      "name": "accountNotMigrated",
+      "msg": "Counter uses an older account layout, run migrate first"
+    },
+    {
+      "code": 6007,
+      "name": "paused",
+      "msg": "Counter updates are paused"
+    },
+    {
+      "code": 6008,
+      "name": "countMismatch",
+      "msg": "Counter does not hold the expected value"
+    },
+    {
+      "code": 6009,
+      "name": "missingPermission",
+      "msg": "Operator is not permitted to perform this update"
+    },
+    {
+      "code": 6010,
+      "name": "invalidPermissions",
+      "msg": "Permissions must be a non-empty mask of increment, decrement and set"
+    },
+    {
+      "code": 6011,
+      "name": "tooManyOperators",
+      "msg": "Counter already lists the maximum number of operators"
+    },
+    {
+      "code": 6012,
+      "name": "operatorNotFound",
+      "msg": "Key is not a listed operator"
+    },
+    {
+      "code": 6013,
+      "name": "invalidMultisig",
+      "msg": "Multisig needs 1 to 8 distinct signers and a threshold within their number"
+    },
+    {
+      "code": 6014,
+      "name": "notMultisigSigner",
+      "msg": "Signer is not one of the multisig's signers"
+    },
+    {
+      "code": 6015,
+      "name": "multisigProposalRequired",
+      "msg": "This update needs an executed multisig proposal"
+    },
+    {
+      "code": 6016,
+      "name": "thresholdNotMet",
+      "msg": "Proposal does not have enough approvals yet"
+    },
+    {
+      "code": 6017,
+      "name": "setNotReady",
+      "msg": "Scheduled set cannot be executed before its delay has passed"
+    },
+    {
+      "code": 6018,
+      "name": "setAlreadyPending",
+      "msg": "Counter already has a scheduled set, execute or cancel it first"
+    },
+    {
+      "code": 6019,
+      "name": "rateLimited",
+      "msg": "Counter was updated too recently, try again later"
+    },
+    {
+      "code": 6020,
+      "name": "invalidRateLimit",
+      "msg": "A per-window cap needs a non-zero window"
+    },
+    {
+      "code": 6021,
+      "name": "invalidResetPeriod",
+      "msg": "Reset period must be between 1 second and i64::MAX seconds"
+    },
+    {
+      "code": 6022,
+      "name": "counterNotPublic",
+      "msg": "Counter is not public"
+    },
+    {
+      "code": 6023,
+      "name": "contributionRequired",
+      "msg": "Increments to a public counter must pass the signer's contribution"
+    },
+    {
+      "code": 6024,
+      "name": "treasuryRequired",
+      "msg": "Counter charges a fee, pass its treasury"
+    },
+    {
+      "code": 6025,
+      "name": "insufficientFunds",
+      "msg": "Not enough lamports to cover the amount"
+    },
+    {
+      "code": 6026,
+      "name": "gateTokenRequired",
+      "msg": "Counter is token gated, pass a token account of its gate mint"
+    },
+    {
+      "code": 6027,
+      "name": "invalidGateToken",
+      "msg": "Token account is not of the gate mint or not owned by the signer"
+    },
+    {
+      "code": 6028,
+      "name": "gateBalanceTooLow",
+      "msg": "Token account holds less than the counter's minimum balance"
+    },
+    {
+      "code": 6029,
+      "name": "rewardMintRequired",
+      "msg": "Counter has no reward mint, create one via init_reward_mint"
+    },
+    {
+      "code": 6030,
+      "name": "rewardAccountsRequired",
+      "msg": "Counter mints rewards, pass its reward mint, a token account and the token program"
+    },
+    {
+      "code": 6031,
+      "name": "invalidRewardAccount",
+      "msg": "Token account is not of the reward mint or not owned by the signer"
+    },
+    {
+      "code": 6032,
+      "name": "callerMayOnlyIncrement",
+      "msg": "Caller programs may only increment the counter"
+    },
+    {
+      "code": 6033,
+      "name": "relocationMismatch",
+      "msg": "Only counters in the original layout, and all of them, move to a relocated address"
+    },
+    {
+      "code": 6034,
+      "name": "invalidContribution",
+      "msg": "Contribution belongs to another counter or contributor"
+    },
+    {
+      "code": 6035,
+      "name": "proposalAccountsRequired",
+      "msg": "Proposal needs the counter's operators, treasury or pending set, or the recipient"
+    },
+    {
+      "code": 6036,
+      "name": "staleProposal",
+      "msg": "Proposal was created under a multisig that has since been closed"
+    },
+    {
+      "code": 6037,
+      "name": "counterNotClosed",
+      "msg": "Only entries of closed counters can be pruned from a leaderboard"
+    }
+  ],
+  "types": [
+    {
+      "name": "authorityProposed",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "pendingAuthority",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "authorityTransferred",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "oldAuthority",
+            "type": "pubkey"
+          },
+          {
+            "name": "newAuthority",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "callerProgramChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "program",
+            "type": {
+              "option": "pubkey"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "configUpdated",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "paused",
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "contribution",
+      "docs": [
+        "A contributor's share of a public counter, a PDA per (counter, contributor)"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter contributed to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "contributor",
+            "docs": [
+              "The signer whose increments are tracked"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "docs": [
+              "Sum of the amounts the contributor incremented by"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "updates",
+            "docs": [
+              "Number of increments the contributor made"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the contribution PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "contributionInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "contributor",
+            "type": "pubkey"
+          },
+          {
+            "name": "contribution",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counter",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "version",
+            "docs": [
+              "Layout version of the account, see Counter::VERSION"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "count",
+            "docs": [
+              "The current count value"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "authority",
+            "docs": [
+              "The authority who can update the counter"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "counterId",
+            "docs": [
+              "Id distinguishing this counter among the authority's counters (part of the PDA seeds)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "seedAuthority",
+            "docs": [
+              "The original authority whose key is part of the PDA seeds",
+              "Kept separately so the address stays stable after an authority handover"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the counter PDA"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "pendingAuthority",
+            "docs": [
+              "Authority proposed via propose_authority, waiting for accept_authority",
+              "Pubkey::default() when no transfer is pending"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "min",
+            "docs": [
+              "Lowest value the counter may hold"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "max",
+            "docs": [
+              "Highest value the counter may hold"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "overflowPolicy",
+            "docs": [
+              "What happens when an update would leave `min..=max`"
+            ],
+            "type": {
+              "defined": {
+                "name": "overflowPolicy"
+              }
+            }
+          },
+          {
+            "name": "frozen",
+            "docs": [
+              "Set by the authority to reject increment, decrement and set"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "sequence",
+            "docs": [
+              "Bumped by every write to `count`, so clients can tell whether the value they",
+              "read is still current"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "rateLimit",
+            "docs": [
+              "How often the counter may be updated, all zeroes for no limit"
+            ],
+            "type": {
+              "defined": {
+                "name": "rateLimit"
+              }
+            }
+          },
+          {
+            "name": "lastUpdateSlot",
+            "docs": [
+              "Slot of the last increment, decrement or set"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "lastUpdateTs",
+            "docs": [
+              "Unix timestamp of the last increment, decrement or set"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "windowStart",
+            "docs": [
+              "Unix timestamp the current rate limit window started at"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "windowCount",
+            "docs": [
+              "Updates made in the current rate limit window"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "resetPeriod",
+            "docs": [
+              "How often the count resets, Never unless set via set_reset_period"
+            ],
+            "type": {
+              "defined": {
+                "name": "resetPeriod"
+              }
+            }
+          },
+          {
+            "name": "periodStart",
+            "docs": [
+              "Start of the reset period the count belongs to, see ResetPeriod::period_start"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "lastPeriodCount",
+            "docs": [
+              "Final count of the previous reset period"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "public",
+            "docs": [
+              "Whether any signer may increment, see set_public"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "fee",
+            "docs": [
+              "Lamports charged for every increment and set, see set_fee"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "gateMint",
+            "docs": [
+              "Mint whose holders may increment, Pubkey::default() for none, see set_token_gate"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "minBalance",
+            "docs": [
+              "Balance of gate_mint an incrementing signer must hold, at least 1"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "rewardMint",
+            "docs": [
+              "Token-2022 mint increments are rewarded in, Pubkey::default() for none,",
+              "see init_reward_mint"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "rewardAmount",
+            "docs": [
+              "Reward tokens minted to the signer on every increment, see set_reward"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "rewardBump",
+            "docs": [
+              "Bump of the reward mint, which signs as its own mint authority"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "callerProgram",
+            "docs": [
+              "Program whose caller signer PDA may increment, Pubkey::default() for none,",
+              "see set_caller_program"
+            ],
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "old",
+            "type": "u64"
+          },
+          {
+            "name": "new",
+            "type": "u64"
+          },
+          {
+            "name": "sequence",
+            "docs": [
+              "The counter's sequence after this change"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "op",
+            "type": {
+              "defined": {
+                "name": "counterOp"
+              }
+            }
+          },
+          {
+            "name": "signer",
+            "type": "pubkey"
+          },
+          {
+            "name": "viaSession",
+            "docs": [
+              "Whether the signer acted through a session token rather than as the authority"
+            ],
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterClosed",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "recipient",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterFrozen",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "frozen",
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterHistory",
+      "docs": [
+        "Fixed-size ring buffer of the most recent changes to a counter"
+      ],
+      "serialization": "bytemuck",
+      "repr": {
+        "kind": "c"
+      },
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter whose changes are recorded"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "total",
+            "docs": [
+              "Number of entries ever appended; the oldest are overwritten once `entries` is full"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the history PDA"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": [
+                "u8",
+                7
+              ]
+            }
+          },
+          {
+            "name": "entries",
+            "docs": [
+              "Entry `i` of the ring lives at `entries[i % entries.len()]`"
+            ],
+            "type": {
+              "array": [
+                {
+                  "defined": {
+                    "name": "historyEntry"
+                  }
+                },
+                32
+              ]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "counterId",
+            "type": "u64"
+          },
+          {
+            "name": "count",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterMigrated",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "fromVersion",
+            "type": "u8"
+          },
+          {
+            "name": "toVersion",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterOp",
+      "docs": [
+        "Kind of update recorded in CounterChanged and HistoryEntry"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "increment"
+          },
+          {
+            "name": "decrement"
+          },
+          {
+            "name": "set"
+          },
+          {
+            "name": "compareAndSet"
+          }
+        ]
+      }
+    },
+    {
+      "name": "counterPublicChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "public",
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "feeChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "fee",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "feesWithdrawn",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "recipient",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "historyEntry",
+      "docs": [
+        "A single recorded change"
+      ],
+      "serialization": "bytemuck",
+      "repr": {
+        "kind": "c"
+      },
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "slot",
+            "type": "u64"
+          },
+          {
+            "name": "unixTs",
+            "type": "i64"
+          },
+          {
+            "name": "signer",
+            "docs": [
+              "The key that signed the update"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "old",
+            "type": "u64"
+          },
+          {
+            "name": "new",
+            "type": "u64"
+          },
+          {
+            "name": "op",
+            "docs": [
+              "CounterOp as its discriminant (0 increment, 1 decrement, 2 set, 3 compare_and_set)"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": [
+                "u8",
+                7
+              ]
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "historyInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "history",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "leaderboard",
+      "docs": [
+        "Top counters by count, a PDA per authority"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "authority",
+            "docs": [
+              "The key that created the leaderboard (part of the PDA seeds)"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "entries",
+            "docs": [
+              "Highest counts first, ties in order of submission"
+            ],
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "leaderboardEntry"
+                }
+              }
+            }
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the leaderboard PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "leaderboardEntry",
+      "docs": [
+        "A counter's place on a leaderboard"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "docs": [
+              "The counter's authority at the time of submission"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "count",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "leaderboardInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "leaderboard",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "multisig",
+      "docs": [
+        "M-of-N signer set holding a counter's authority, a PDA per counter"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter whose authority this is"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "config",
+            "type": {
+              "defined": {
+                "name": "multisigConfig"
+              }
+            }
+          },
+          {
+            "name": "proposalCount",
+            "docs": [
+              "Number of proposals ever created, the index of the next one"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the multisig PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "multisigAction",
+      "docs": [
+        "What a proposal does once executed"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "set",
+            "fields": [
+              {
+                "name": "value",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "increment",
+            "fields": [
+              {
+                "name": "amount",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "decrement",
+            "fields": [
+              {
+                "name": "amount",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "proposeAuthority",
+            "fields": [
+              {
+                "name": "newAuthority",
+                "type": "pubkey"
+              }
+            ]
+          },
+          {
+            "name": "setFrozen",
+            "fields": [
+              {
+                "name": "frozen",
+                "type": "bool"
+              }
+            ]
+          },
+          {
+            "name": "setRateLimit",
+            "fields": [
+              {
+                "name": "rateLimit",
+                "type": {
+                  "defined": {
+                    "name": "rateLimit"
+                  }
+                }
+              }
+            ]
+          },
+          {
+            "name": "setResetPeriod",
+            "fields": [
+              {
+                "name": "resetPeriod",
+                "type": {
+                  "defined": {
+                    "name": "resetPeriod"
+                  }
+                }
+              }
+            ]
+          },
+          {
+            "name": "setPublic",
+            "fields": [
+              {
+                "name": "public",
+                "type": "bool"
+              }
+            ]
+          },
+          {
+            "name": "setFee",
+            "fields": [
+              {
+                "name": "fee",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "setTokenGate",
+            "fields": [
+              {
+                "name": "gateMint",
+                "type": {
+                  "option": "pubkey"
+                }
+              },
+              {
+                "name": "minBalance",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "setReward",
+            "fields": [
+              {
+                "name": "amount",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "setCallerProgram",
+            "fields": [
+              {
+                "name": "program",
+                "type": {
+                  "option": "pubkey"
+                }
+              }
+            ]
+          },
+          {
+            "name": "addOperator",
+            "fields": [
+              {
+                "name": "operator",
+                "type": "pubkey"
+              },
+              {
+                "name": "permissions",
+                "type": "u8"
+              }
+            ]
+          },
+          {
+            "name": "removeOperator",
+            "fields": [
+              {
+                "name": "operator",
+                "type": "pubkey"
+              }
+            ]
+          },
+          {
+            "name": "withdraw",
+            "fields": [
+              {
+                "name": "amount",
+                "type": "u64"
+              },
+              {
+                "name": "recipient",
+                "type": "pubkey"
+              }
+            ]
+          },
+          {
+            "name": "scheduleSet",
+            "fields": [
+              {
+                "name": "value",
+                "type": "u64"
+              },
+              {
+                "name": "delay",
+                "type": "u64"
+              }
+            ]
+          },
+          {
+            "name": "cancelSet"
+          },
+          {
+            "name": "close",
+            "fields": [
+              {
+                "name": "recipient",
+                "type": "pubkey"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "multisigConfig",
+      "docs": [
+        "Signers, threshold and update policy of a multisig, as passed to initialize"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "signers",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "threshold",
+            "docs": [
+              "Approvals a proposal needs before it can be executed"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "updatePolicy",
+            "docs": [
+              "Who may increment and decrement the counter"
+            ],
+            "type": {
+              "defined": {
+                "name": "multisigUpdatePolicy"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "multisigCreated",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "multisig",
+            "type": "pubkey"
+          },
+          {
+            "name": "signers",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "threshold",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "multisigUpdatePolicy",
+      "docs": [
+        "How increment and decrement are authorized on a multisig counter",
+        "Set and authority changes always go through proposals"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "anySigner"
+          },
+          {
+            "name": "proposal"
+          }
+        ]
+      }
+    },
+    {
+      "name": "operator",
+      "docs": [
+        "A key allowed to perform some updates on a counter"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "key",
+            "type": "pubkey"
+          },
+          {
+            "name": "permissions",
+            "docs": [
+              "Mask of Operator::INCREMENT, Operator::DECREMENT and Operator::SET"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "operatorChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "operator",
+            "type": "pubkey"
+          },
+          {
+            "name": "permissions",
+            "docs": [
+              "The operator's new permission mask, 0 when it was removed"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "operators",
+      "docs": [
+        "Keys other than the authority allowed to update a counter"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter the operators may update"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the operators PDA"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "operators",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "operator"
+                }
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "operatorsInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "operators",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "overflowPolicy",
+      "docs": [
+        "Behaviour of increment and decrement at the counter's bounds"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "wrap"
+          },
+          {
+            "name": "saturate"
+          },
+          {
+            "name": "error"
+          }
+        ]
+      }
+    },
+    {
+      "name": "pendingSet",
+      "docs": [
+        "A set waiting out its delay, a PDA per counter"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter the set applies to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "docs": [
+              "The authority that scheduled the set, refunded when it is executed or cancelled"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "value",
+            "docs": [
+              "The value the counter will be set to"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "executeAfter",
+            "docs": [
+              "Unix timestamp from which the set can be executed"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the pending set PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "programConfig",
+      "docs": [
+        "Program-wide settings, a singleton PDA"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "admin",
+            "docs": [
+              "The key allowed to pause the program and hand over the config"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "paused",
+            "docs": [
+              "While set, increment, decrement and set fail on every counter"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the config PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "proposal",
+      "docs": [
+        "A change to a multisig counter waiting for approvals, a PDA per proposal index"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "multisig",
+            "docs": [
+              "The multisig the proposal belongs to"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "docs": [
+              "Position among the multisig's proposals (part of the PDA seeds)"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "proposer",
+            "docs": [
+              "The signer who created the proposal, refunded when it is executed"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "action",
+            "type": {
+              "defined": {
+                "name": "multisigAction"
+              }
+            }
+          },
+          {
+            "name": "approvals",
+            "docs": [
+              "Bit `i` is set once the multisig's signer `i` approved"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the proposal PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "proposalApproved",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "proposal",
+            "type": "pubkey"
+          },
+          {
+            "name": "member",
+            "type": "pubkey"
+          },
+          {
+            "name": "approvals",
+            "docs": [
+              "Number of signers who approved so far"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "proposalCreated",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "proposal",
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "type": "u64"
+          },
+          {
+            "name": "proposer",
+            "type": "pubkey"
+          },
+          {
+            "name": "action",
+            "type": {
+              "defined": {
+                "name": "multisigAction"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "proposalExecuted",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "proposal",
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "type": "u64"
+          },
+          {
+            "name": "action",
+            "type": {
+              "defined": {
+                "name": "multisigAction"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "rateLimit",
+      "docs": [
+        "Limits on how often a counter may be updated, set via set_rate_limit",
+        "Each zero field disables its own check"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "minSlots",
+            "docs": [
+              "Slots that must pass between two updates"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minSeconds",
+            "docs": [
+              "Seconds that must pass between two updates, at the Clock's whole-second resolution"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "maxPerWindow",
+            "docs": [
+              "Most updates within a window of `window_seconds`"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "windowSeconds",
+            "docs": [
+              "Length of the window `max_per_window` applies to"
+            ],
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rateLimitChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "rateLimit",
+            "type": {
+              "defined": {
+                "name": "rateLimit"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "resetPeriod",
+      "docs": [
+        "Boundaries at which a counter resets, set via set_reset_period"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "never"
+          },
+          {
+            "name": "dailyUtc"
+          },
+          {
+            "name": "epoch"
+          },
+          {
+            "name": "seconds",
+            "fields": [
+              {
+                "name": "seconds",
+                "type": "u64"
+              }
+            ]
+          }
+        ]
+      }
+    },
+    {
+      "name": "resetPeriodChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "resetPeriod",
+            "type": {
+              "defined": {
+                "name": "resetPeriod"
+              }
+            }
+          },
+          {
+            "name": "periodStart",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardMintInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "mint",
+            "type": "pubkey"
+          },
+          {
+            "name": "decimals",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "scorePruned",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "leaderboard",
+            "type": "pubkey"
+          },
+          {
+            "name": "counter",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "scoreSubmitted",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "leaderboard",
+            "type": "pubkey"
+          },
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "count",
+            "type": "u64"
+          },
+          {
+            "name": "rank",
+            "docs": [
+              "Zero-based place on the board, None if the count didn't make it"
+            ],
+            "type": {
+              "option": "u8"
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "sessionToken",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "targetProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "sessionSigner",
+            "type": "pubkey"
+          },
+          {
+            "name": "validUntil",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "setCancelled",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "value",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "setScheduled",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "value",
+            "type": "u64"
+          },
+          {
+            "name": "executeAfter",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "tokenGateChanged",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "gateMint",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "minBalance",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "treasury",
+      "docs": [
+        "Fees collected for a counter, a PDA per counter holding them as lamports"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "docs": [
+              "The counter whose fees are collected"
+            ],
+            "type": "pubkey"
+          },
+          {
+            "name": "collected",
+            "docs": [
+              "Total fees ever paid in, withdrawals aside"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "bump",
+            "docs": [
+              "The canonical bump of the treasury PDA"
+            ],
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "treasuryInitialized",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "counter",
+            "type": "pubkey"
+          },
+          {
+            "name": "treasury",
             "type": "pubkey"
           }
         ]
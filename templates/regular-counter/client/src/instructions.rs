@@ -0,0 +1,1237 @@
+//! Builders for every instruction of the program
+//! PDAs are derived from the counter's key, so callers only pass the signers and
+//! the accounts that can't be derived
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_lang::InstructionData;
+use counter::{accounts, instruction};
+use counter::{MultisigAction, MultisigConfig, OverflowPolicy, RateLimit, ResetPeriod};
+
+use crate::{pda, CounterKey};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: counter::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Optional accounts of the update instructions (increment, decrement, set, ...),
+/// none of them passed by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateAccounts {
+    /// Session token the signer acts through for the authority
+    #[cfg(feature = "session-keys")]
+    pub session_token: Option<Pubkey>,
+    /// Leave out the program config, for deployments where init_config hasn't run
+    pub without_config: bool,
+    /// Append the change to the counter's history
+    pub history: bool,
+    /// Pass the counter's operators, needed when the signer is one
+    pub operators: bool,
+    /// Pass the counter's multisig, needed when the signer is one of its members
+    pub multisig: bool,
+    /// Pass the signer's contribution, needed to increment a public counter
+    pub contribution: bool,
+    /// Pass the counter's treasury, needed while it charges a fee
+    pub treasury: bool,
+    /// The signer's token account of the gate mint, needed while the counter is gated
+    pub gate_token_account: Option<Pubkey>,
+    /// The signer's Token-2022 account of the reward mint, needed while the counter
+    /// mints rewards
+    pub reward_token_account: Option<Pubkey>,
+}
+
+fn update(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> accounts::Update {
+    let address = counter.address();
+    let passed = |pass: bool, (key, _): (Pubkey, u8)| pass.then_some(key);
+    let rewarded = options.reward_token_account.is_some();
+    accounts::Update {
+        counter: address,
+        signer,
+        #[cfg(feature = "session-keys")]
+        session_token: options.session_token,
+        config: passed(!options.without_config, pda::config()),
+        history: passed(options.history, pda::history(&address)),
+        operators: passed(options.operators, pda::operators(&address)),
+        multisig: passed(options.multisig, pda::multisig(&address)),
+        contribution: passed(options.contribution, pda::contribution(&address, &signer)),
+        treasury: passed(options.treasury, pda::treasury(&address)),
+        gate_token_account: options.gate_token_account,
+        reward_mint: passed(rewarded, pda::reward_mint(&address)),
+        reward_token_account: options.reward_token_account,
+        token_program: rewarded.then_some(anchor_spl::token_2022::ID),
+        system_program: system_program::ID,
+    }
+}
+
+/// Create counter `counter_id` of `authority`, optionally held by a new multisig
+pub fn initialize(
+    authority: Pubkey,
+    counter_id: u64,
+    min: u64,
+    max: u64,
+    overflow_policy: OverflowPolicy,
+    multisig: Option<MultisigConfig>,
+) -> Instruction {
+    let counter = pda::counter(&authority, counter_id).0;
+    build(
+        accounts::Initialize {
+            counter,
+            authority,
+            multisig: multisig.as_ref().map(|_| pda::multisig(&counter).0),
+            system_program: system_program::ID,
+        },
+        instruction::Initialize {
+            counter_id,
+            min,
+            max,
+            overflow_policy,
+            multisig,
+        },
+    )
+}
+
+pub fn increment(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Increment {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn decrement(counter: CounterKey, signer: Pubkey, options: &UpdateAccounts) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Decrement {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn increment_by(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::IncrementBy {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+pub fn decrement_by(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::DecrementBy {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+pub fn set(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    value: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::Set {
+            counter_id: counter.counter_id,
+            value,
+        },
+    )
+}
+
+pub fn compare_and_set(
+    counter: CounterKey,
+    signer: Pubkey,
+    options: &UpdateAccounts,
+    expected: u64,
+    new: u64,
+) -> Instruction {
+    build(
+        update(counter, signer, options),
+        instruction::CompareAndSet {
+            counter_id: counter.counter_id,
+            expected,
+            new,
+        },
+    )
+}
+
+pub fn schedule_set(counter: CounterKey, authority: Pubkey, value: u64, delay: u64) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::ScheduleSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::ScheduleSet {
+            counter_id: counter.counter_id,
+            value,
+            delay,
+        },
+    )
+}
+
+/// Execute the scheduled set, refunding its rent to `authority`, who scheduled it
+/// Of `options`, only the config, history and treasury apply
+pub fn execute_set(
+    counter: CounterKey,
+    authority: Pubkey,
+    executor: Pubkey,
+    options: &UpdateAccounts,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::ExecuteSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+            config: (!options.without_config).then(|| pda::config().0),
+            executor,
+            history: options.history.then(|| pda::history(&address).0),
+            treasury: options.treasury.then(|| pda::treasury(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteSet {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn cancel_set(counter: CounterKey, authority: Pubkey) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::CancelSet {
+            counter: address,
+            pending_set: pda::pending_set(&address).0,
+            authority,
+        },
+        instruction::CancelSet {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_history(counter: CounterKey, authority: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitHistory {
+            counter: address,
+            history: pda::history(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitHistory {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_operators(counter: CounterKey, authority: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitOperators {
+            counter: address,
+            operators: pda::operators(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitOperators {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+fn manage_operators(counter: CounterKey, authority: Pubkey) -> accounts::ManageOperators {
+    let address = counter.address();
+    accounts::ManageOperators {
+        counter: address,
+        operators: pda::operators(&address).0,
+        authority,
+    }
+}
+
+pub fn add_operator(
+    counter: CounterKey,
+    authority: Pubkey,
+    operator: Pubkey,
+    permissions: u8,
+) -> Instruction {
+    build(
+        manage_operators(counter, authority),
+        instruction::AddOperator {
+            counter_id: counter.counter_id,
+            operator,
+            permissions,
+        },
+    )
+}
+
+pub fn remove_operator(counter: CounterKey, authority: Pubkey, operator: Pubkey) -> Instruction {
+    build(
+        manage_operators(counter, authority),
+        instruction::RemoveOperator {
+            counter_id: counter.counter_id,
+            operator,
+        },
+    )
+}
+
+/// Accounts closed along with the counter, besides its history, operators, treasury
+/// and pending set, which are always passed
+#[derive(Clone, Debug, Default)]
+pub struct CloseAccounts {
+    /// Contributors whose contributions were created, refunded their rent
+    pub contributors: Vec<Pubkey>,
+}
+
+/// Close the counter along with `options`, returning their rent to `recipient`
+pub fn close(
+    counter: CounterKey,
+    authority: Pubkey,
+    recipient: Pubkey,
+    options: &CloseAccounts,
+) -> Instruction {
+    let address = counter.address();
+    let mut ix = build(
+        accounts::Close {
+            counter: address,
+            authority,
+            recipient,
+            history: pda::history(&address).0,
+            operators: pda::operators(&address).0,
+            treasury: pda::treasury(&address).0,
+            pending_set: pda::pending_set(&address).0,
+            multisig: pda::multisig(&address).0,
+        },
+        instruction::Close {
+            counter_id: counter.counter_id,
+        },
+    );
+    for contributor in &options.contributors {
+        ix.accounts.extend([
+            AccountMeta::new(pda::contribution(&address, contributor).0, false),
+            AccountMeta::new(*contributor, false),
+        ]);
+    }
+    ix
+}
+
+pub fn migrate(counter: CounterKey, payer: Pubkey) -> Instruction {
+    build(
+        accounts::Migrate {
+            counter: counter.address(),
+            relocated: None,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Move a counter still in the original layout to the address of `counter_id`
+pub fn migrate_baseline(authority: Pubkey, counter_id: u64) -> Instruction {
+    build(
+        accounts::Migrate {
+            counter: pda::baseline_counter(&authority).0,
+            relocated: Some(pda::counter(&authority, counter_id).0),
+            payer: authority,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate { counter_id },
+    )
+}
+
+pub fn propose_authority(
+    counter: CounterKey,
+    authority: Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    build(
+        accounts::ProposeAuthority {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::ProposeAuthority {
+            counter_id: counter.counter_id,
+            new_authority,
+        },
+    )
+}
+
+pub fn accept_authority(counter: CounterKey, new_authority: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAuthority {
+            counter: counter.address(),
+            new_authority,
+        },
+        instruction::AcceptAuthority {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Create the multisig's proposal number `index`, which must be its current
+/// `proposal_count`
+pub fn create_proposal(
+    counter: CounterKey,
+    proposer: Pubkey,
+    index: u64,
+    action: MultisigAction,
+) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    build(
+        accounts::CreateProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            proposer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateProposal {
+            counter_id: counter.counter_id,
+            action,
+        },
+    )
+}
+
+pub fn approve_proposal(counter: CounterKey, member: Pubkey, index: u64) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    build(
+        accounts::ApproveProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            member,
+        },
+        instruction::ApproveProposal {
+            counter_id: counter.counter_id,
+            index,
+        },
+    )
+}
+
+/// Execute approved proposal `index`, refunding its rent to `proposer`
+/// The `action` decides the accounts passed along: the counter's operators for
+/// operator changes, its treasury and the recipient for withdrawals, its pending set
+/// for scheduled sets, and the recipient followed by the accounts closed along with the
+/// counter for closing it
+/// `options` applies to count changes as to execute_set
+pub fn execute_proposal(
+    counter: CounterKey,
+    proposer: Pubkey,
+    executor: Pubkey,
+    index: u64,
+    action: &MultisigAction,
+    options: &UpdateAccounts,
+) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address).0;
+    let (recipient, treasury) = match *action {
+        MultisigAction::Withdraw { recipient, .. } => (Some(recipient), true),
+        MultisigAction::Close { recipient } => (Some(recipient), false),
+        _ => (None, false),
+    };
+    let operators = matches!(
+        action,
+        MultisigAction::AddOperator { .. } | MultisigAction::RemoveOperator { .. }
+    );
+    let pending_set = matches!(
+        action,
+        MultisigAction::ScheduleSet { .. } | MultisigAction::CancelSet
+    );
+    let mut ix = build(
+        accounts::ExecuteProposal {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index).0,
+            proposer,
+            config: (!options.without_config).then(|| pda::config().0),
+            executor,
+            history: options.history.then(|| pda::history(&address).0),
+            operators: operators.then(|| pda::operators(&address).0),
+            treasury: (options.treasury || treasury).then(|| pda::treasury(&address).0),
+            pending_set: pending_set.then(|| pda::pending_set(&address).0),
+            recipient,
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteProposal {
+            counter_id: counter.counter_id,
+            index,
+        },
+    );
+    if let MultisigAction::Close { .. } = action {
+        ix.accounts.extend(
+            [
+                pda::history(&address).0,
+                pda::operators(&address).0,
+                pda::treasury(&address).0,
+                pda::pending_set(&address).0,
+            ]
+            .map(|address| AccountMeta::new(address, false)),
+        );
+    }
+    ix
+}
+
+pub fn set_frozen(counter: CounterKey, authority: Pubkey, frozen: bool) -> Instruction {
+    build(
+        accounts::SetFrozen {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetFrozen {
+            counter_id: counter.counter_id,
+            frozen,
+        },
+    )
+}
+
+pub fn set_rate_limit(
+    counter: CounterKey,
+    authority: Pubkey,
+    rate_limit: RateLimit,
+) -> Instruction {
+    build(
+        accounts::SetRateLimit {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetRateLimit {
+            counter_id: counter.counter_id,
+            rate_limit,
+        },
+    )
+}
+
+pub fn set_reset_period(
+    counter: CounterKey,
+    authority: Pubkey,
+    reset_period: ResetPeriod,
+) -> Instruction {
+    build(
+        accounts::SetResetPeriod {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetResetPeriod {
+            counter_id: counter.counter_id,
+            reset_period,
+        },
+    )
+}
+
+pub fn set_public(counter: CounterKey, authority: Pubkey, public: bool) -> Instruction {
+    build(
+        accounts::SetPublic {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetPublic {
+            counter_id: counter.counter_id,
+            public,
+        },
+    )
+}
+
+pub fn set_fee(counter: CounterKey, authority: Pubkey, fee: u64) -> Instruction {
+    build(
+        accounts::SetFee {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetFee {
+            counter_id: counter.counter_id,
+            fee,
+        },
+    )
+}
+
+pub fn set_token_gate(
+    counter: CounterKey,
+    authority: Pubkey,
+    gate_mint: Option<Pubkey>,
+    min_balance: u64,
+) -> Instruction {
+    build(
+        accounts::SetTokenGate {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetTokenGate {
+            counter_id: counter.counter_id,
+            gate_mint,
+            min_balance,
+        },
+    )
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_reward_mint(
+    counter: CounterKey,
+    authority: Pubkey,
+    multisig: bool,
+    decimals: u8,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitRewardMint {
+            counter: address,
+            reward_mint: pda::reward_mint(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            token_program: anchor_spl::token_2022::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitRewardMint {
+            counter_id: counter.counter_id,
+            decimals,
+        },
+    )
+}
+
+pub fn set_reward(counter: CounterKey, authority: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::SetReward {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetReward {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+pub fn set_caller_program(
+    counter: CounterKey,
+    authority: Pubkey,
+    program: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::SetCallerProgram {
+            counter: counter.address(),
+            authority,
+        },
+        instruction::SetCallerProgram {
+            counter_id: counter.counter_id,
+            program,
+        },
+    )
+}
+
+pub fn init_contribution(counter: CounterKey, contributor: Pubkey) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitContribution {
+            counter: address,
+            contribution: pda::contribution(&address, &contributor).0,
+            contributor,
+            system_program: system_program::ID,
+        },
+        instruction::InitContribution {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn init_leaderboard(authority: Pubkey) -> Instruction {
+    build(
+        accounts::InitLeaderboard {
+            leaderboard: pda::leaderboard(&authority).0,
+            authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitLeaderboard {},
+    )
+}
+
+/// Submit the counter's count to the leaderboard created by `leaderboard_authority`,
+/// who signs the submission
+pub fn submit_score(leaderboard_authority: Pubkey, counter: CounterKey) -> Instruction {
+    build(
+        accounts::SubmitScore {
+            leaderboard: pda::leaderboard(&leaderboard_authority).0,
+            counter: counter.address(),
+            authority: leaderboard_authority,
+        },
+        instruction::SubmitScore {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+/// Drop the entries of the closed `counters` from the leaderboard created by
+/// `leaderboard_authority`
+pub fn prune_leaderboard(leaderboard_authority: Pubkey, counters: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::PruneLeaderboard {
+            leaderboard: pda::leaderboard(&leaderboard_authority).0,
+        },
+        instruction::PruneLeaderboard {},
+    );
+    ix.accounts.extend(
+        counters
+            .iter()
+            .map(|counter| AccountMeta::new_readonly(*counter, false)),
+    );
+    ix
+}
+
+/// Pass `multisig` when `authority` is one of the signers of the multisig holding
+/// the counter's authority
+pub fn init_treasury(counter: CounterKey, authority: Pubkey, multisig: bool) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::InitTreasury {
+            counter: address,
+            treasury: pda::treasury(&address).0,
+            authority,
+            multisig: multisig.then(|| pda::multisig(&address).0),
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            counter_id: counter.counter_id,
+        },
+    )
+}
+
+pub fn withdraw(
+    counter: CounterKey,
+    signer: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::Withdraw {
+            counter: address,
+            treasury: pda::treasury(&address).0,
+            config: Some(pda::config().0),
+            signer,
+            recipient,
+        },
+        instruction::Withdraw {
+            counter_id: counter.counter_id,
+            amount,
+        },
+    )
+}
+
+/// Create the program config, signed by the program's upgrade authority
+pub fn init_config(payer: Pubkey, admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitConfig {
+            config: pda::config().0,
+            program_data: pda::program_data().0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitConfig { admin },
+    )
+}
+
+pub fn set_paused(admin: Pubkey, paused: bool) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config().0,
+            admin,
+        },
+        instruction::SetPaused { paused },
+    )
+}
+
+pub fn set_admin(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    build(
+        accounts::UpdateConfig {
+            config: pda::config().0,
+            admin,
+        },
+        instruction::SetAdmin { new_admin },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn key() -> CounterKey {
+        CounterKey::new(Pubkey::new_unique(), 4)
+    }
+
+    /// Decode the arguments of `ix`, checking it carries the discriminator of `T`
+    fn args<T: InstructionData + AnchorDeserialize>(ix: &Instruction) -> T {
+        assert_eq!(ix.program_id, counter::ID);
+        assert_eq!(&ix.data[..8], T::DISCRIMINATOR);
+        T::deserialize(&mut &ix.data[8..]).unwrap()
+    }
+
+    /// Where the program expects an optional account that was left out
+    fn omitted() -> AccountMeta {
+        AccountMeta::new_readonly(counter::ID, false)
+    }
+
+    #[test]
+    fn builders_carry_their_instructions_discriminator() {
+        let counter = key();
+        let (signer, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let options = UpdateAccounts::default();
+        let action = MultisigAction::Set { value: 1 };
+        let cases: &[(Instruction, &[u8])] = &[
+            (
+                initialize(signer, 1, 0, 10, OverflowPolicy::Wrap, None),
+                instruction::Initialize::DISCRIMINATOR,
+            ),
+            (
+                increment(counter, signer, &options),
+                instruction::Increment::DISCRIMINATOR,
+            ),
+            (
+                decrement(counter, signer, &options),
+                instruction::Decrement::DISCRIMINATOR,
+            ),
+            (
+                increment_by(counter, signer, &options, 2),
+                instruction::IncrementBy::DISCRIMINATOR,
+            ),
+            (
+                decrement_by(counter, signer, &options, 2),
+                instruction::DecrementBy::DISCRIMINATOR,
+            ),
+            (
+                set(counter, signer, &options, 2),
+                instruction::Set::DISCRIMINATOR,
+            ),
+            (
+                compare_and_set(counter, signer, &options, 1, 2),
+                instruction::CompareAndSet::DISCRIMINATOR,
+            ),
+            (
+                schedule_set(counter, signer, 2, 60),
+                instruction::ScheduleSet::DISCRIMINATOR,
+            ),
+            (
+                execute_set(counter, signer, other, &options),
+                instruction::ExecuteSet::DISCRIMINATOR,
+            ),
+            (
+                cancel_set(counter, signer),
+                instruction::CancelSet::DISCRIMINATOR,
+            ),
+            (
+                init_history(counter, signer, false),
+                instruction::InitHistory::DISCRIMINATOR,
+            ),
+            (
+                init_operators(counter, signer, false),
+                instruction::InitOperators::DISCRIMINATOR,
+            ),
+            (
+                add_operator(counter, signer, other, 1),
+                instruction::AddOperator::DISCRIMINATOR,
+            ),
+            (
+                remove_operator(counter, signer, other),
+                instruction::RemoveOperator::DISCRIMINATOR,
+            ),
+            (
+                close(counter, signer, other, &CloseAccounts::default()),
+                instruction::Close::DISCRIMINATOR,
+            ),
+            (
+                migrate(counter, signer),
+                instruction::Migrate::DISCRIMINATOR,
+            ),
+            (
+                migrate_baseline(signer, 1),
+                instruction::Migrate::DISCRIMINATOR,
+            ),
+            (
+                propose_authority(counter, signer, other),
+                instruction::ProposeAuthority::DISCRIMINATOR,
+            ),
+            (
+                accept_authority(counter, other),
+                instruction::AcceptAuthority::DISCRIMINATOR,
+            ),
+            (
+                create_proposal(counter, signer, 0, action),
+                instruction::CreateProposal::DISCRIMINATOR,
+            ),
+            (
+                approve_proposal(counter, other, 0),
+                instruction::ApproveProposal::DISCRIMINATOR,
+            ),
+            (
+                execute_proposal(counter, signer, other, 0, &action, &options),
+                instruction::ExecuteProposal::DISCRIMINATOR,
+            ),
+            (
+                set_frozen(counter, signer, true),
+                instruction::SetFrozen::DISCRIMINATOR,
+            ),
+            (
+                set_rate_limit(counter, signer, RateLimit::default()),
+                instruction::SetRateLimit::DISCRIMINATOR,
+            ),
+            (
+                set_reset_period(counter, signer, ResetPeriod::Epoch),
+                instruction::SetResetPeriod::DISCRIMINATOR,
+            ),
+            (
+                set_public(counter, signer, true),
+                instruction::SetPublic::DISCRIMINATOR,
+            ),
+            (
+                set_fee(counter, signer, 5),
+                instruction::SetFee::DISCRIMINATOR,
+            ),
+            (
+                set_token_gate(counter, signer, Some(other), 1),
+                instruction::SetTokenGate::DISCRIMINATOR,
+            ),
+            (
+                init_reward_mint(counter, signer, false, 0),
+                instruction::InitRewardMint::DISCRIMINATOR,
+            ),
+            (
+                set_reward(counter, signer, 3),
+                instruction::SetReward::DISCRIMINATOR,
+            ),
+            (
+                set_caller_program(counter, signer, Some(other)),
+                instruction::SetCallerProgram::DISCRIMINATOR,
+            ),
+            (
+                init_contribution(counter, other),
+                instruction::InitContribution::DISCRIMINATOR,
+            ),
+            (
+                init_leaderboard(signer),
+                instruction::InitLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                submit_score(signer, counter),
+                instruction::SubmitScore::DISCRIMINATOR,
+            ),
+            (
+                prune_leaderboard(signer, &[other]),
+                instruction::PruneLeaderboard::DISCRIMINATOR,
+            ),
+            (
+                init_treasury(counter, signer, false),
+                instruction::InitTreasury::DISCRIMINATOR,
+            ),
+            (
+                withdraw(counter, signer, other, 5),
+                instruction::Withdraw::DISCRIMINATOR,
+            ),
+            (
+                init_config(signer, other),
+                instruction::InitConfig::DISCRIMINATOR,
+            ),
+            (
+                set_paused(signer, true),
+                instruction::SetPaused::DISCRIMINATOR,
+            ),
+            (
+                set_admin(signer, other),
+                instruction::SetAdmin::DISCRIMINATOR,
+            ),
+        ];
+        for (ix, discriminator) in cases {
+            assert_eq!(ix.program_id, counter::ID);
+            assert_eq!(&ix.data[..8], *discriminator);
+        }
+        // migrate_baseline shares migrate's instruction, every other builder has its own
+        let mut distinct: Vec<_> = cases
+            .iter()
+            .map(|(_, discriminator)| *discriminator)
+            .collect();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), cases.len() - 1);
+    }
+
+    #[test]
+    fn encodes_instruction_arguments() {
+        let counter = key();
+        let signer = Pubkey::new_unique();
+        let options = UpdateAccounts::default();
+
+        let decoded: instruction::CompareAndSet =
+            args(&compare_and_set(counter, signer, &options, 5, 9));
+        assert_eq!(
+            (decoded.counter_id, decoded.expected, decoded.new),
+            (4, 5, 9)
+        );
+
+        let decoded: instruction::SetTokenGate =
+            args(&set_token_gate(counter, signer, Some(signer), 10));
+        assert_eq!(decoded.gate_mint, Some(signer));
+        assert_eq!(decoded.min_balance, 10);
+
+        let config = MultisigConfig {
+            signers: vec![signer, Pubkey::new_unique()],
+            threshold: 2,
+            update_policy: counter::MultisigUpdatePolicy::Proposal,
+        };
+        let decoded: instruction::Initialize = args(&initialize(
+            signer,
+            7,
+            1,
+            99,
+            OverflowPolicy::Saturate,
+            Some(config.clone()),
+        ));
+        assert_eq!((decoded.counter_id, decoded.min, decoded.max), (7, 1, 99));
+        assert!(matches!(decoded.overflow_policy, OverflowPolicy::Saturate));
+        assert!(decoded.multisig == Some(config));
+    }
+
+    // The session token's slot only exists with session keys
+    #[cfg(feature = "session-keys")]
+    #[test]
+    fn update_builders_pass_only_the_requested_accounts() {
+        let counter = key();
+        let address = counter.address();
+        let signer = Pubkey::new_unique();
+
+        let ix = increment(counter, signer, &UpdateAccounts::default());
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(signer, true),
+                omitted(),
+                AccountMeta::new_readonly(pda::config().0, false),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                omitted(),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+
+        let session_token = Pubkey::new_unique();
+        let reward_token_account = Pubkey::new_unique();
+        let ix = increment(
+            counter,
+            signer,
+            &UpdateAccounts {
+                session_token: Some(session_token),
+                without_config: true,
+                history: true,
+                operators: true,
+                multisig: true,
+                contribution: true,
+                treasury: true,
+                gate_token_account: None,
+                reward_token_account: Some(reward_token_account),
+            },
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(signer, true),
+                AccountMeta::new_readonly(session_token, false),
+                omitted(),
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new_readonly(pda::operators(&address).0, false),
+                AccountMeta::new_readonly(pda::multisig(&address).0, false),
+                AccountMeta::new(pda::contribution(&address, &signer).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                omitted(),
+                AccountMeta::new(pda::reward_mint(&address).0, false),
+                AccountMeta::new(reward_token_account, false),
+                AccountMeta::new_readonly(anchor_spl::token_2022::ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_proposal_passes_the_accounts_its_action_needs() {
+        let counter = key();
+        let address = counter.address();
+        let multisig = pda::multisig(&address).0;
+        let (proposer, executor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let recipient = Pubkey::new_unique();
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::Withdraw {
+                amount: 1,
+                recipient,
+            },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(multisig, false),
+                AccountMeta::new(pda::proposal(&multisig, 3).0, false),
+                AccountMeta::new(proposer, false),
+                AccountMeta::new_readonly(pda::config().0, false),
+                AccountMeta::new(executor, true),
+                omitted(),
+                omitted(),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                omitted(),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ]
+        );
+        let decoded: instruction::ExecuteProposal = args(&ix);
+        assert_eq!((decoded.counter_id, decoded.index), (4, 3));
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::RemoveOperator {
+                operator: recipient,
+            },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(
+            ix.accounts[7],
+            AccountMeta::new(pda::operators(&address).0, false)
+        );
+        assert_eq!(ix.accounts[8], omitted());
+        assert_eq!(ix.accounts[9], omitted());
+        assert_eq!(ix.accounts[10], omitted());
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::CancelSet,
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(
+            ix.accounts[9],
+            AccountMeta::new(pda::pending_set(&address).0, false)
+        );
+        assert_eq!(ix.accounts[10], omitted());
+
+        let ix = execute_proposal(
+            counter,
+            proposer,
+            executor,
+            3,
+            &MultisigAction::Close { recipient },
+            &UpdateAccounts::default(),
+        );
+        assert_eq!(ix.accounts[8], omitted());
+        assert_eq!(ix.accounts[10], AccountMeta::new(recipient, false));
+        assert_eq!(
+            ix.accounts[12..],
+            [
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new(pda::operators(&address).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                AccountMeta::new(pda::pending_set(&address).0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn close_appends_contributions_with_their_contributors() {
+        let counter = key();
+        let address = counter.address();
+        let (authority, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let contributor = Pubkey::new_unique();
+
+        let ix = close(
+            counter,
+            authority,
+            recipient,
+            &CloseAccounts {
+                contributors: vec![contributor],
+            },
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new(pda::history(&address).0, false),
+                AccountMeta::new(pda::operators(&address).0, false),
+                AccountMeta::new(pda::treasury(&address).0, false),
+                AccountMeta::new(pda::pending_set(&address).0, false),
+                AccountMeta::new(pda::multisig(&address).0, false),
+                AccountMeta::new(pda::contribution(&address, &contributor).0, false),
+                AccountMeta::new(contributor, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaderboard_builders_sign_submissions_and_append_pruned_counters() {
+        let counter = key();
+        let authority = Pubkey::new_unique();
+        let leaderboard = pda::leaderboard(&authority).0;
+
+        assert_eq!(
+            submit_score(authority, counter).accounts,
+            vec![
+                AccountMeta::new(leaderboard, false),
+                AccountMeta::new_readonly(counter.address(), false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(
+            prune_leaderboard(authority, &[counter.address()]).accounts,
+            vec![
+                AccountMeta::new(leaderboard, false),
+                AccountMeta::new_readonly(counter.address(), false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Addresses of the program's PDAs, each returned with its bump like
+//! `Pubkey::find_program_address`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use counter::{
+    CALLER_SIGNER_SEED, CONFIG_SEED, CONTRIBUTION_SEED, HISTORY_SEED, LEADERBOARD_SEED,
+    MULTISIG_SEED, OPERATORS_SEED, PENDING_SET_SEED, PROPOSAL_SEED, REWARD_MINT_SEED,
+    TREASURY_SEED,
+};
+
+/// The counter `seed_authority` created with `counter_id`
+/// The seeds keep the creator's key even after the authority is handed over
+pub fn counter(seed_authority: &Pubkey, counter_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        &counter::ID,
+    )
+}
+
+/// A counter still in the original layout, which lived at the PDA of its authority
+/// alone until migrate moves it to `counter`
+pub fn baseline_counter(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[authority.as_ref()], &counter::ID)
+}
+
+/// The singleton ProgramConfig
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &counter::ID)
+}
+
+/// The program's ProgramData account, holding its upgrade authority
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[counter::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// The counter's CounterHistory
+pub fn history(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[HISTORY_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The counter's PendingSet, while a set is scheduled
+pub fn pending_set(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PENDING_SET_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The counter's Operators
+pub fn operators(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OPERATORS_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The counter's Multisig
+pub fn multisig(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The multisig's proposal number `index`
+pub fn proposal(multisig: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROPOSAL_SEED, multisig.as_ref(), &index.to_le_bytes()],
+        &counter::ID,
+    )
+}
+
+/// The Contribution of `contributor` to a public counter
+pub fn contribution(counter: &Pubkey, contributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CONTRIBUTION_SEED, counter.as_ref(), contributor.as_ref()],
+        &counter::ID,
+    )
+}
+
+/// The Leaderboard created by `authority`
+pub fn leaderboard(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LEADERBOARD_SEED, authority.as_ref()], &counter::ID)
+}
+
+/// The counter's Treasury
+pub fn treasury(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The counter's Token-2022 reward mint
+pub fn reward_mint(counter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_MINT_SEED, counter.as_ref()], &counter::ID)
+}
+
+/// The PDA `caller_program` signs increments of the counter with, derived under
+/// the caller program rather than this one
+pub fn caller_signer(counter: &Pubkey, caller_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CALLER_SIGNER_SEED, counter.as_ref()], caller_program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seeds are spelled out as the TS clients do rather than taken from the program
+    fn derive(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &counter::ID)
+    }
+
+    #[test]
+    fn derives_counters_at_the_program_seeds() {
+        let authority = Pubkey::new_unique();
+        let (address, bump) = counter(&authority, 7);
+        assert_eq!(
+            (address, bump),
+            derive(&[authority.as_ref(), &7u64.to_le_bytes()])
+        );
+        assert_ne!(address, counter(&authority, 8).0);
+        assert_eq!(baseline_counter(&authority), derive(&[authority.as_ref()]));
+        assert_eq!(
+            baseline_counter(&authority).0,
+            counter::Counter::baseline_address(&authority)
+        );
+    }
+
+    #[test]
+    fn derives_counter_accounts_at_the_program_seeds() {
+        let counter = counter(&Pubkey::new_unique(), 0).0;
+        let member = Pubkey::new_unique();
+        assert_eq!(config(), derive(&[b"config"]));
+        assert_eq!(history(&counter), derive(&[b"history", counter.as_ref()]));
+        assert_eq!(
+            pending_set(&counter),
+            derive(&[b"pending_set", counter.as_ref()])
+        );
+        assert_eq!(
+            operators(&counter),
+            derive(&[b"operators", counter.as_ref()])
+        );
+        assert_eq!(treasury(&counter), derive(&[b"treasury", counter.as_ref()]));
+        assert_eq!(
+            reward_mint(&counter),
+            derive(&[b"reward_mint", counter.as_ref()])
+        );
+        assert_eq!(
+            contribution(&counter, &member),
+            derive(&[b"contribution", counter.as_ref(), member.as_ref()])
+        );
+        assert_eq!(
+            leaderboard(&member),
+            derive(&[b"leaderboard", member.as_ref()])
+        );
+
+        let multisig = multisig(&counter);
+        assert_eq!(multisig, derive(&[b"multisig", counter.as_ref()]));
+        assert_eq!(
+            proposal(&multisig.0, 2),
+            derive(&[b"proposal", multisig.0.as_ref(), &2u64.to_le_bytes()])
+        );
+    }
+
+    #[test]
+    fn derives_other_programs_pdas() {
+        let counter = counter(&Pubkey::new_unique(), 0).0;
+        let caller = Pubkey::new_unique();
+        assert_eq!(
+            caller_signer(&counter, &caller),
+            Pubkey::find_program_address(&[b"counter_signer", counter.as_ref()], &caller)
+        );
+        assert_eq!(
+            program_data().0,
+            bpf_loader_upgradeable::get_program_data_address(&counter::ID)
+        );
+    }
+}
@@ -1,95 +1,3720 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, MintTo, Token2022, TokenAccount};
+#[cfg(feature = "session-keys")]
+use session_keys::{session_auth_or, Session, SessionError, SessionToken};
 
 declare_id!("Adryj75Zwpo8Au98xNsCwxdNZ7hY2SX1XeiMWJoVyJZK");
 
+/// Seed prefix of the CounterHistory PDA, followed by the counter's address
+pub const HISTORY_SEED: &[u8] = b"history";
+
+/// Seed of the singleton ProgramConfig PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed prefix of the PendingSet PDA, followed by the counter's address
+pub const PENDING_SET_SEED: &[u8] = b"pending_set";
+
+/// Seed prefix of the Operators PDA, followed by the counter's address
+pub const OPERATORS_SEED: &[u8] = b"operators";
+
+/// Most operators a single counter can list
+pub const MAX_OPERATORS: usize = 8;
+
+/// Seed prefix of the Multisig PDA, followed by the counter's address
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+
+/// Seed of the per-contributor Contribution PDA, followed by the counter and contributor keys
+pub const CONTRIBUTION_SEED: &[u8] = b"contribution";
+
+/// Seed of the Leaderboard PDA, followed by the key of the authority that created it
+pub const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+
+/// Entries kept on a leaderboard
+pub const MAX_LEADERBOARD_ENTRIES: usize = 10;
+
+/// Seed prefix of the per-counter Treasury PDA, followed by the counter's address
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Seed prefix of the per-counter reward mint, followed by the counter's address
+/// The mint signs as its own mint authority
+pub const REWARD_MINT_SEED: &[u8] = b"reward_mint";
+
+/// Seed prefix of the PDA a caller program signs increments with, followed by the
+/// counter's address and derived under the caller program's id
+pub const CALLER_SIGNER_SEED: &[u8] = b"counter_signer";
+
+/// Seed prefix of a Proposal PDA, followed by the multisig's address and the
+/// proposal index
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Most signers a multisig can have, bounded by the width of Proposal::approvals
+pub const MAX_MULTISIG_SIGNERS: usize = 8;
+
+/// Emit an event with `emit_cpi!` when built with the `event-cpi` feature, so it
+/// can't be lost to log truncation, and with `emit!` otherwise
+macro_rules! emit_event {
+    ($ctx:ident, $event:expr) => {{
+        #[cfg(feature = "event-cpi")]
+        {
+            let ctx = &$ctx;
+            emit_cpi!($event);
+        }
+        #[cfg(not(feature = "event-cpi"))]
+        emit!($event);
+    }};
+}
+
 #[program]
 pub mod counter {
     use super::*;
 
-    /// Initialize a new counter account with count set to 0
-    /// Uses PDA derivation with user's public key for deterministic addresses
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    /// Initialize a new counter account with count set to `min`
+    /// Uses PDA derivation with user's public key and a counter id for deterministic addresses,
+    /// so a single authority can own many independent counters
+    /// `min`, `max` and `overflow_policy` bound every later increment, decrement and set
+    /// Passing `multisig` (along with the multisig account) makes an M-of-N multisig the
+    /// counter's authority instead of the signer
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        counter_id: u64,
+        min: u64,
+        max: u64,
+        overflow_policy: OverflowPolicy,
+        multisig: Option<MultisigConfig>,
+    ) -> Result<()> {
+        require!(min <= max, CounterError::InvalidBounds);
+        let multisig_created = match (multisig, &mut ctx.accounts.multisig) {
+            (Some(config), Some(account)) => {
+                config.validate()?;
+                account.set_inner(Multisig {
+                    counter: ctx.accounts.counter.key(),
+                    config,
+                    proposal_count: 0,
+                    bump: ctx.bumps.multisig.ok_or(CounterError::InvalidMultisig)?,
+                });
+                Some(MultisigCreated {
+                    counter: ctx.accounts.counter.key(),
+                    multisig: account.key(),
+                    signers: account.config.signers.clone(),
+                    threshold: account.config.threshold,
+                })
+            }
+            (None, None) => None,
+            _ => return err!(CounterError::InvalidMultisig),
+        };
         let counter = &mut ctx.accounts.counter;
-        counter.count = 0;
-        counter.authority = ctx.accounts.authority.key();
+        counter.authority = multisig_created
+            .as_ref()
+            .map_or(ctx.accounts.authority.key(), |created| created.multisig);
+        counter.version = Counter::VERSION;
+        counter.count = min;
+        counter.counter_id = counter_id;
+        counter.seed_authority = ctx.accounts.authority.key();
+        counter.bump = ctx.bumps.counter;
+        counter.pending_authority = Pubkey::default();
+        counter.min = min;
+        counter.max = max;
+        counter.overflow_policy = overflow_policy;
+        counter.frozen = false;
         msg!(
-            "PDA {} initialized with count: {}",
+            "PDA {} (id {}) initialized with count: {}",
             counter.key(),
+            counter_id,
             counter.count
         );
+        let event = CounterInitialized {
+            counter: counter.key(),
+            authority: counter.authority,
+            counter_id,
+            count: counter.count,
+        };
+        emit_event!(ctx, event);
+        if let Some(event) = multisig_created {
+            emit_event!(ctx, event);
+        }
         Ok(())
     }
 
     /// Increment the counter by 1
-    /// Wraps around to 0 if count exceeds 1000 (for demo purposes)
-    pub fn increment(ctx: Context<Update>) -> Result<()> {
+    /// Going above `max` wraps, saturates or fails depending on the overflow policy
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn increment(ctx: Context<Update>, counter_id: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Increment, |counter| counter.add(1))?;
+        ctx.accounts.contribute(1);
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Decrement the counter by 1
+    /// Going below `min` wraps, saturates or fails depending on the overflow policy
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn decrement(ctx: Context<Update>, counter_id: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Decrement, |counter| counter.sub(1))?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Increment the counter by `amount` in a single instruction
+    /// Follows the same bounds and overflow policy as increment
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn increment_by(ctx: Context<Update>, counter_id: u64, amount: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Increment, |counter| {
+                counter.add(amount)
+            })?;
+        ctx.accounts.contribute(amount);
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Decrement the counter by `amount` in a single instruction
+    /// Follows the same bounds and overflow policy as decrement
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn decrement_by(ctx: Context<Update>, counter_id: u64, amount: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Decrement, |counter| {
+                counter.sub(amount)
+            })?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Set the counter to a specific value
+    /// The value must lie within the counter's `min..=max` bounds
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn set(ctx: Context<Update>, counter_id: u64, value: u64) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::Set, |counter| counter.set(value))?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Set the counter to `new` only if it currently holds `expected`
+    /// Fails with CountMismatch otherwise, so concurrent writers can't silently
+    /// overwrite each other; `new` must lie within the counter's bounds
+    #[cfg_attr(
+        feature = "session-keys",
+        session_auth_or(ctx.accounts.may_sign(), CounterError::InvalidAuth)
+    )]
+    pub fn compare_and_set(
+        ctx: Context<Update>,
+        counter_id: u64,
+        expected: u64,
+        new: u64,
+    ) -> Result<()> {
+        let event = ctx
+            .accounts
+            .apply(counter_id, CounterOp::CompareAndSet, |counter| {
+                counter.compare_and_set(expected, new)
+            })?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Schedule a set to `value` that anyone can execute once `delay` seconds have passed
+    /// The authority can cancel it until then; only one set can be pending per counter
+    pub fn schedule_set(
+        ctx: Context<ScheduleSet>,
+        counter_id: u64,
+        value: u64,
+        delay: u64,
+    ) -> Result<()> {
+        let counter = &ctx.accounts.counter;
+        let execute_after =
+            ctx.accounts
+                .pending_set
+                .schedule(counter, value, delay, ctx.bumps.pending_set)?;
+        msg!(
+            "PDA {} (id {}) set to {} scheduled for: {}",
+            counter.key(),
+            counter_id,
+            value,
+            execute_after
+        );
+        let event = SetScheduled {
+            counter: counter.key(),
+            value,
+            execute_after,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Carry out a scheduled set once its delay has passed
+    /// Anyone may execute it; the rent of the pending set goes back to the authority
+    /// that scheduled it
+    /// The set is held to the rate limit and recorded like any other, with the executor
+    /// paying the counter's fee
+    pub fn execute_set(ctx: Context<ExecuteSet>, counter_id: u64) -> Result<()> {
+        let accounts = &mut *ctx.accounts;
+        require!(
+            Clock::get()?.unix_timestamp >= accounts.pending_set.execute_after,
+            CounterError::SetNotReady
+        );
+        charge_fee(
+            &accounts.counter,
+            CounterOp::Set,
+            &accounts.executor,
+            accounts.treasury.as_mut(),
+            &accounts.system_program,
+        )?;
+        let value = accounts.pending_set.value;
+        let event = write_count(
+            &mut accounts.counter,
+            accounts.history.as_ref(),
+            counter_id,
+            CounterOp::Set,
+            accounts.executor.key(),
+            |counter| counter.set(value),
+        )?;
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Drop a scheduled set before it is executed, returning its rent to the authority
+    pub fn cancel_set(ctx: Context<CancelSet>, counter_id: u64) -> Result<()> {
+        let pending_set = &ctx.accounts.pending_set;
+        msg!(
+            "PDA {} (id {}) scheduled set to {} cancelled",
+            ctx.accounts.counter.key(),
+            counter_id,
+            pending_set.value
+        );
+        let event = SetCancelled {
+            counter: ctx.accounts.counter.key(),
+            value: pending_set.value,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the optional change history of a counter
+    /// Once it exists, pass it to increment, decrement and set to record every change
+    pub fn init_history(ctx: Context<InitHistory>, counter_id: u64) -> Result<()> {
+        let mut history = ctx.accounts.history.load_init()?;
+        history.counter = ctx.accounts.counter.key();
+        history.bump = ctx.bumps.history;
+        msg!(
+            "PDA {} (id {}) history created at {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            ctx.accounts.history.key()
+        );
+        let event = HistoryInitialized {
+            counter: ctx.accounts.counter.key(),
+            history: ctx.accounts.history.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the operator allow-list of a counter
+    /// Listed operators may then sign increment, decrement and set in the authority's place
+    pub fn init_operators(ctx: Context<InitOperators>, counter_id: u64) -> Result<()> {
+        let operators = &mut ctx.accounts.operators;
+        operators.counter = ctx.accounts.counter.key();
+        operators.bump = ctx.bumps.operators;
+        msg!(
+            "PDA {} (id {}) operators created at {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            operators.key()
+        );
+        let event = OperatorsInitialized {
+            counter: ctx.accounts.counter.key(),
+            operators: operators.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Add `operator` to the allow-list, or replace its permissions if already listed
+    /// `permissions` is a mask of Operator::INCREMENT, Operator::DECREMENT and Operator::SET
+    pub fn add_operator(
+        ctx: Context<ManageOperators>,
+        counter_id: u64,
+        operator: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        ctx.accounts.operators.add(operator, permissions)?;
+        msg!(
+            "PDA {} (id {}) operator {} permissions: {:#05b}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            operator,
+            permissions
+        );
+        let event = OperatorChanged {
+            counter: ctx.accounts.counter.key(),
+            operator,
+            permissions,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Remove `operator` from the allow-list
+    pub fn remove_operator(
+        ctx: Context<ManageOperators>,
+        counter_id: u64,
+        operator: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.operators.remove(&operator)?;
+        msg!(
+            "PDA {} (id {}) operator {} removed",
+            ctx.accounts.counter.key(),
+            counter_id,
+            operator
+        );
+        let event = OperatorChanged {
+            counter: ctx.accounts.counter.key(),
+            operator,
+            permissions: 0,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Close the counter account and return its rent to the recipient
+    /// Pass the authority as recipient to reclaim the lamports to the owner's wallet
+    /// The counter's history, operators, treasury, pending set and multisig are closed
+    /// along with it whenever they exist, their rent and any fees left in the treasury
+    /// going to the recipient, so a counter created again at the same address starts from
+    /// scratch
+    /// A counter held by a multisig is closed through a Close proposal instead
+    /// Its contributions are closed too when passed as remaining accounts, each followed
+    /// by its contributor, who gets the rent back
+    pub fn close(ctx: Context<Close>, counter_id: u64) -> Result<()> {
+        let accounts = &ctx.accounts;
+        for account in [
+            &accounts.history,
+            &accounts.operators,
+            &accounts.treasury,
+            &accounts.pending_set,
+            &accounts.multisig,
+        ] {
+            if account.owner == &crate::ID {
+                close_account(account, &accounts.recipient)?;
+            }
+        }
+        close_contributions(&ctx.accounts.counter.key(), ctx.remaining_accounts)?;
+        msg!(
+            "PDA {} (id {}) closed, rent returned to {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            ctx.accounts.recipient.key()
+        );
+        let event = CounterClosed {
+            counter: ctx.accounts.counter.key(),
+            recipient: ctx.accounts.recipient.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Bring a counter created by an earlier program version up to the current layout
+    /// Reallocs the account to the current size, topping up its rent from `payer`, and
+    /// fills the fields the old layout lacked with their defaults
+    /// Anyone may pay for a migration since it never changes how the counter behaves
+    /// Counters in the original layout lived at the PDA of their authority alone; their
+    /// authority moves them to `relocated`, the address of `[authority, counter_id]`,
+    /// and gets the old account's rent back
+    pub fn migrate(ctx: Context<Migrate>, counter_id: u64) -> Result<()> {
+        let counter_info = ctx.accounts.counter.to_account_info();
+        let (from_version, mut counter) = Counter::upgrade(&counter_info.try_borrow_data()?)?;
+        match (from_version, &mut ctx.accounts.relocated) {
+            (0, Some(relocated)) => {
+                require_keys_eq!(
+                    Counter::baseline_address(&counter.authority),
+                    counter_info.key(),
+                    ErrorCode::ConstraintSeeds
+                );
+                require_keys_eq!(
+                    counter.authority,
+                    ctx.accounts.payer.key(),
+                    CounterError::InvalidAuth
+                );
+                counter.counter_id = counter_id;
+                counter.bump = ctx
+                    .bumps
+                    .relocated
+                    .ok_or(CounterError::RelocationMismatch)?;
+                relocated.set_inner(counter);
+                close_account(&counter_info, &ctx.accounts.payer)?;
+                msg!(
+                    "PDA {} moved to {} (id {}) and migrated from version 0 to {}",
+                    counter_info.key(),
+                    relocated.key(),
+                    counter_id,
+                    Counter::VERSION
+                );
+                let event = CounterMigrated {
+                    counter: relocated.key(),
+                    from_version,
+                    to_version: Counter::VERSION,
+                };
+                emit_event!(ctx, event);
+                return Ok(());
+            }
+            (0, None) | (_, Some(_)) => return err!(CounterError::RelocationMismatch),
+            _ => counter.verify_address(counter_info.key, counter_id)?,
+        }
+        if from_version == Counter::VERSION {
+            msg!(
+                "PDA {} (id {}) already at version {}",
+                counter_info.key(),
+                counter_id,
+                from_version
+            );
+            return Ok(());
+        }
+
+        let space = 8 + Counter::INIT_SPACE;
+        let rent = Rent::get()?.minimum_balance(space);
+        let missing = rent.saturating_sub(counter_info.lamports());
+        if missing > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: counter_info.clone(),
+                    },
+                ),
+                missing,
+            )?;
+        }
+        counter_info.resize(space)?;
+        counter.try_serialize(&mut &mut counter_info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "PDA {} (id {}) migrated from version {} to {}",
+            counter_info.key(),
+            counter_id,
+            from_version,
+            Counter::VERSION
+        );
+        let event = CounterMigrated {
+            counter: counter_info.key(),
+            from_version,
+            to_version: Counter::VERSION,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Propose a new authority for the counter (step 1 of 2)
+    /// Proposing again replaces the pending authority; the PDA address never changes
+    /// Propose Pubkey::default() to cancel a pending transfer
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        counter_id: u64,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.pending_authority = new_authority;
+        msg!(
+            "PDA {} (id {}) proposed authority: {}",
+            counter.key(),
+            counter_id,
+            new_authority
+        );
+        let event = AuthorityProposed {
+            counter: counter.key(),
+            authority: counter.authority,
+            pending_authority: new_authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Accept a pending authority transfer (step 2 of 2)
+    /// Must be signed by the proposed authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>, counter_id: u64) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
-        counter.count = counter.count.checked_add(1).unwrap();
-        if counter.count > 1000 {
-            counter.count = 0;
+        let old_authority = counter.authority;
+        counter.authority = ctx.accounts.new_authority.key();
+        counter.pending_authority = Pubkey::default();
+        msg!(
+            "PDA {} (id {}) authority transferred to: {}",
+            counter.key(),
+            counter_id,
+            counter.authority
+        );
+        let event = AuthorityTransferred {
+            counter: counter.key(),
+            old_authority,
+            new_authority: counter.authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Propose `action` on a counter whose authority is a multisig
+    /// Must be signed by one of its signers, which counts as the first approval
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        counter_id: u64,
+        action: MultisigAction,
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposer = ctx.accounts.proposer.key();
+        let index = multisig.proposal_count;
+        multisig.proposal_count += 1;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.index = index;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        proposal.approvals = 0;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.approve(multisig, &proposer)?;
+        msg!(
+            "PDA {} (id {}) proposal {} created by {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index,
+            proposer
+        );
+        let event = ProposalCreated {
+            counter: ctx.accounts.counter.key(),
+            proposal: proposal.key(),
+            index,
+            proposer,
+            action,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Approve a pending proposal, signed by one of the multisig's signers
+    /// Approving twice has no further effect
+    pub fn approve_proposal(
+        ctx: Context<ApproveProposal>,
+        counter_id: u64,
+        index: u64,
+    ) -> Result<()> {
+        let member = ctx.accounts.member.key();
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.approve(&ctx.accounts.multisig, &member)?;
+        msg!(
+            "PDA {} (id {}) proposal {} approved by {} ({} of {})",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index,
+            member,
+            proposal.approval_count(),
+            ctx.accounts.multisig.config.threshold
+        );
+        let event = ProposalApproved {
+            proposal: proposal.key(),
+            member,
+            approvals: proposal.approval_count(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Carry out a proposal once the multisig's threshold of signers approved it
+    /// Anyone may execute; the proposal is closed and its rent returned to the proposer
+    /// Count changes go through the same pause, rate limit, fee and history as an
+    /// update, the executor paying the fee; the other actions stand in for the
+    /// instructions only the authority may sign
+    pub fn execute_proposal(
+        ctx: Context<ExecuteProposal>,
+        counter_id: u64,
+        index: u64,
+    ) -> Result<()> {
+        let action = ctx.accounts.proposal.action;
+        require!(
+            ctx.accounts.proposal.approval_count() >= ctx.accounts.multisig.config.threshold,
+            CounterError::ThresholdNotMet
+        );
+        msg!(
+            "PDA {} (id {}) executing proposal {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            index
+        );
+        let event = ProposalExecuted {
+            counter: ctx.accounts.counter.key(),
+            proposal: ctx.accounts.proposal.key(),
+            index,
+            action,
+        };
+        emit_event!(ctx, event);
+
+        let multisig = ctx.accounts.multisig.key();
+        let counter_key = ctx.accounts.counter.key();
+        match action {
+            MultisigAction::Set { value } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Set, |counter| counter.set(value))?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Increment { amount } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Increment, |counter| {
+                        counter.add(amount)
+                    })?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Decrement { amount } => {
+                let event = ctx
+                    .accounts
+                    .apply(counter_id, CounterOp::Decrement, |counter| {
+                        counter.sub(amount)
+                    })?;
+                emit_event!(ctx, event);
+            }
+            MultisigAction::ProposeAuthority { new_authority } => {
+                ctx.accounts.counter.pending_authority = new_authority;
+                msg!(
+                    "PDA {} (id {}) proposed authority: {}",
+                    counter_key,
+                    counter_id,
+                    new_authority
+                );
+                let event = AuthorityProposed {
+                    counter: counter_key,
+                    authority: multisig,
+                    pending_authority: new_authority,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetFrozen { frozen } => {
+                ctx.accounts.counter.frozen = frozen;
+                let event = CounterFrozen {
+                    counter: counter_key,
+                    frozen,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetRateLimit { rate_limit } => {
+                ctx.accounts.counter.update_rate_limit(rate_limit)?;
+                let event = RateLimitChanged {
+                    counter: counter_key,
+                    rate_limit,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetResetPeriod { reset_period } => {
+                let counter = &mut ctx.accounts.counter;
+                counter.update_reset_period(reset_period, &Clock::get()?)?;
+                let event = ResetPeriodChanged {
+                    counter: counter_key,
+                    reset_period,
+                    period_start: counter.period_start,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetPublic { public } => {
+                ctx.accounts.counter.public = public;
+                let event = CounterPublicChanged {
+                    counter: counter_key,
+                    public,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetFee { fee } => {
+                ctx.accounts.counter.fee = fee;
+                let event = FeeChanged {
+                    counter: counter_key,
+                    fee,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetTokenGate {
+                gate_mint,
+                min_balance,
+            } => {
+                let counter = &mut ctx.accounts.counter;
+                counter.gate_mint = gate_mint.unwrap_or_default();
+                counter.min_balance = min_balance;
+                let event = TokenGateChanged {
+                    counter: counter_key,
+                    gate_mint,
+                    min_balance,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetReward { amount } => {
+                ctx.accounts.counter.update_reward(amount)?;
+                let event = RewardChanged {
+                    counter: counter_key,
+                    amount,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::SetCallerProgram { program } => {
+                ctx.accounts.counter.caller_program = program.unwrap_or_default();
+                let event = CallerProgramChanged {
+                    counter: counter_key,
+                    program,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::AddOperator {
+                operator,
+                permissions,
+            } => {
+                ctx.accounts
+                    .operators
+                    .as_mut()
+                    .ok_or(CounterError::ProposalAccountsRequired)?
+                    .add(operator, permissions)?;
+                let event = OperatorChanged {
+                    counter: counter_key,
+                    operator,
+                    permissions,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::RemoveOperator { operator } => {
+                ctx.accounts
+                    .operators
+                    .as_mut()
+                    .ok_or(CounterError::ProposalAccountsRequired)?
+                    .remove(&operator)?;
+                let event = OperatorChanged {
+                    counter: counter_key,
+                    operator,
+                    permissions: 0,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Withdraw { amount, recipient } => {
+                let (Some(treasury), Some(account)) =
+                    (&ctx.accounts.treasury, &ctx.accounts.recipient)
+                else {
+                    return err!(CounterError::ProposalAccountsRequired);
+                };
+                require_keys_eq!(
+                    account.key(),
+                    recipient,
+                    CounterError::ProposalAccountsRequired
+                );
+                withdraw_fees(&treasury.to_account_info(), account, amount)?;
+                let event = FeesWithdrawn {
+                    counter: counter_key,
+                    recipient,
+                    amount,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::ScheduleSet { value, delay } => {
+                let accounts = &mut *ctx.accounts;
+                let (Some(pending_set), Some(bump)) =
+                    (accounts.pending_set.as_mut(), ctx.bumps.pending_set)
+                else {
+                    return err!(CounterError::ProposalAccountsRequired);
+                };
+                let execute_after = pending_set.schedule(&accounts.counter, value, delay, bump)?;
+                msg!(
+                    "PDA {} (id {}) set to {} scheduled for: {}",
+                    counter_key,
+                    counter_id,
+                    value,
+                    execute_after
+                );
+                let event = SetScheduled {
+                    counter: counter_key,
+                    value,
+                    execute_after,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::CancelSet => {
+                let accounts = &mut *ctx.accounts;
+                let pending_set = accounts
+                    .pending_set
+                    .as_mut()
+                    .ok_or(CounterError::ProposalAccountsRequired)?;
+                // init_if_needed: an empty pending set was only just created
+                require_keys_eq!(
+                    pending_set.counter,
+                    counter_key,
+                    ErrorCode::AccountNotInitialized
+                );
+                let value = pending_set.value;
+                pending_set.close(accounts.multisig.to_account_info())?;
+                msg!(
+                    "PDA {} (id {}) scheduled set to {} cancelled",
+                    counter_key,
+                    counter_id,
+                    value
+                );
+                let event = SetCancelled {
+                    counter: counter_key,
+                    value,
+                };
+                emit_event!(ctx, event);
+            }
+            MultisigAction::Close { recipient } => {
+                let accounts = &ctx.accounts;
+                let account = accounts
+                    .recipient
+                    .as_ref()
+                    .filter(|account| account.key() == recipient)
+                    .ok_or(CounterError::ProposalAccountsRequired)?;
+                let (dependents, contributions) = ctx
+                    .remaining_accounts
+                    .split_at_checked(4)
+                    .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+                let seeds = [
+                    HISTORY_SEED,
+                    OPERATORS_SEED,
+                    TREASURY_SEED,
+                    PENDING_SET_SEED,
+                ];
+                for (info, seed) in dependents.iter().zip(seeds) {
+                    let (address, _) =
+                        Pubkey::find_program_address(&[seed, counter_key.as_ref()], &crate::ID);
+                    require_keys_eq!(info.key(), address, ErrorCode::ConstraintSeeds);
+                    if info.owner == &crate::ID {
+                        close_account(info, account)?;
+                    }
+                }
+                close_contributions(&counter_key, contributions)?;
+                close_account(&accounts.multisig.to_account_info(), account)?;
+                close_account(&accounts.counter.to_account_info(), account)?;
+                msg!(
+                    "PDA {} (id {}) closed, rent returned to {}",
+                    counter_key,
+                    counter_id,
+                    recipient
+                );
+                let event = CounterClosed {
+                    counter: counter_key,
+                    recipient,
+                };
+                emit_event!(ctx, event);
+            }
         }
-        msg!("PDA {} count: {}", counter.key(), counter.count);
         Ok(())
     }
 
-    /// Decrement the counter by 1
-    pub fn decrement(ctx: Context<Update>) -> Result<()> {
+    /// Freeze or unfreeze the counter
+    /// While frozen, increment, decrement and set fail with Paused
+    pub fn set_frozen(ctx: Context<SetFrozen>, counter_id: u64, frozen: bool) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
-        require!(counter.count > 0, CounterError::CounterUnderflow);
-        counter.count = counter.count.checked_sub(1).unwrap();
-        msg!("PDA {} count: {}", counter.key(), counter.count);
+        counter.frozen = frozen;
+        msg!(
+            "PDA {} (id {}) frozen: {}",
+            counter.key(),
+            counter_id,
+            frozen
+        );
+        let event = CounterFrozen {
+            counter: counter.key(),
+            frozen,
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 
-    /// Set the counter to a specific value
-    pub fn set(ctx: Context<Update>, value: u64) -> Result<()> {
+    /// Limit how often increment, decrement and set may be called on the counter
+    /// Every signer is held to it, the authority included; see RateLimit for the fields
+    pub fn set_rate_limit(
+        ctx: Context<SetRateLimit>,
+        counter_id: u64,
+        rate_limit: RateLimit,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_rate_limit(rate_limit)?;
+        msg!(
+            "PDA {} (id {}) rate limit: {} slots, {} seconds, {} per {} seconds",
+            counter.key(),
+            counter_id,
+            rate_limit.min_slots,
+            rate_limit.min_seconds,
+            rate_limit.max_per_window,
+            rate_limit.window_seconds
+        );
+        let event = RateLimitChanged {
+            counter: counter.key(),
+            rate_limit,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Reset the counter at every boundary of `reset_period`
+    /// The reset happens lazily, on the first update in a new period
+    pub fn set_reset_period(
+        ctx: Context<SetResetPeriod>,
+        counter_id: u64,
+        reset_period: ResetPeriod,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_reset_period(reset_period, &Clock::get()?)?;
+        msg!(
+            "PDA {} (id {}) reset period changed, current period started at {}",
+            counter.key(),
+            counter_id,
+            counter.period_start
+        );
+        let event = ResetPeriodChanged {
+            counter: counter.key(),
+            reset_period,
+            period_start: counter.period_start,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Open the counter to increments from any signer, or close it again
+    /// Signers with no other rights on the counter must pass their Contribution,
+    /// created via init_contribution, which records their share
+    pub fn set_public(ctx: Context<SetPublic>, counter_id: u64, public: bool) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.public = public;
+        msg!(
+            "PDA {} (id {}) public: {}",
+            counter.key(),
+            counter_id,
+            public
+        );
+        let event = CounterPublicChanged {
+            counter: counter.key(),
+            public,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Charge `fee` lamports for every increment and set on the counter, 0 to make
+    /// them free again; decrements are never charged
+    /// The signer pays into the counter's Treasury, created via init_treasury
+    pub fn set_fee(ctx: Context<SetFee>, counter_id: u64, fee: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.fee = fee;
+        msg!("PDA {} (id {}) fee: {}", counter.key(), counter_id, fee);
+        let event = FeeChanged {
+            counter: counter.key(),
+            fee,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Only let holders of `gate_mint` increment the counter, None to lift the gate
+    /// Incrementing signers must pass a token account of the mint they own with a
+    /// balance of at least `min_balance`, or of 1 when it is 0
+    /// Accounts of both the SPL Token and the Token-2022 program are accepted
+    pub fn set_token_gate(
+        ctx: Context<SetTokenGate>,
+        counter_id: u64,
+        gate_mint: Option<Pubkey>,
+        min_balance: u64,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.gate_mint = gate_mint.unwrap_or_default();
+        counter.min_balance = min_balance;
+        msg!(
+            "PDA {} (id {}) gate mint: {}, min balance: {}",
+            counter.key(),
+            counter_id,
+            counter.gate_mint,
+            min_balance
+        );
+        let event = TokenGateChanged {
+            counter: counter.key(),
+            gate_mint,
+            min_balance,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the counter's Token-2022 reward mint, a PDA that is its own mint authority
+    /// Nothing is minted until an amount is set via set_reward
+    pub fn init_reward_mint(
+        ctx: Context<InitRewardMint>,
+        counter_id: u64,
+        decimals: u8,
+    ) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.reward_mint = ctx.accounts.reward_mint.key();
+        counter.reward_bump = ctx.bumps.reward_mint;
+        msg!(
+            "PDA {} (id {}) reward mint created at {}",
+            counter.key(),
+            counter_id,
+            counter.reward_mint
+        );
+        let event = RewardMintInitialized {
+            counter: counter.key(),
+            mint: counter.reward_mint,
+            decimals,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Mint `amount` tokens of the reward mint to the signer on every increment, 0 to stop
+    /// Incrementing signers must then pass the reward mint, their Token-2022 account
+    /// of it and the Token-2022 program
+    pub fn set_reward(ctx: Context<SetReward>, counter_id: u64, amount: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.update_reward(amount)?;
+        msg!(
+            "PDA {} (id {}) reward: {}",
+            counter.key(),
+            counter_id,
+            amount
+        );
+        let event = RewardChanged {
+            counter: counter.key(),
+            amount,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Let `program` increment the counter through CPI, None to revoke it
+    /// The program signs with its PDA of [CALLER_SIGNER_SEED, counter address]
+    pub fn set_caller_program(
+        ctx: Context<SetCallerProgram>,
+        counter_id: u64,
+        program: Option<Pubkey>,
+    ) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
-        counter.count = value;
-        msg!("PDA {} count: {}", counter.key(), counter.count);
+        counter.caller_program = program.unwrap_or_default();
+        msg!(
+            "PDA {} (id {}) caller program: {}",
+            counter.key(),
+            counter_id,
+            counter.caller_program
+        );
+        let event = CallerProgramChanged {
+            counter: counter.key(),
+            program,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the account tracking the contributor's increments to a public counter
+    pub fn init_contribution(ctx: Context<InitContribution>, counter_id: u64) -> Result<()> {
+        let contribution = &mut ctx.accounts.contribution;
+        contribution.counter = ctx.accounts.counter.key();
+        contribution.contributor = ctx.accounts.contributor.key();
+        contribution.amount = 0;
+        contribution.updates = 0;
+        contribution.bump = ctx.bumps.contribution;
+        msg!(
+            "PDA {} (id {}) contribution of {} created at {}",
+            contribution.counter,
+            counter_id,
+            contribution.contributor,
+            contribution.key()
+        );
+        let event = ContributionInitialized {
+            counter: contribution.counter,
+            contributor: contribution.contributor,
+            contribution: contribution.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the authority's leaderboard, ranking the counters submitted to it by count
+    pub fn init_leaderboard(ctx: Context<InitLeaderboard>) -> Result<()> {
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.authority = ctx.accounts.authority.key();
+        leaderboard.entries = Vec::new();
+        leaderboard.bump = ctx.bumps.leaderboard;
+        msg!(
+            "Leaderboard of {} created at {}",
+            leaderboard.authority,
+            leaderboard.key()
+        );
+        let event = LeaderboardInitialized {
+            leaderboard: leaderboard.key(),
+            authority: leaderboard.authority,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Rank a counter on a leaderboard by its current count
+    /// Only the leaderboard's authority may submit, the count is read from the counter
+    /// itself; a counter already on the board is moved to its new place
+    pub fn submit_score(ctx: Context<SubmitScore>, counter_id: u64) -> Result<()> {
+        let counter = &ctx.accounts.counter;
+        let entry = LeaderboardEntry {
+            counter: counter.key(),
+            authority: counter.authority,
+            count: counter.count,
+        };
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        let rank = leaderboard.submit(entry);
+        msg!(
+            "PDA {} (id {}) submitted {} to leaderboard {}, rank {:?}",
+            entry.counter,
+            counter_id,
+            entry.count,
+            leaderboard.key(),
+            rank
+        );
+        let event = ScoreSubmitted {
+            leaderboard: leaderboard.key(),
+            counter: entry.counter,
+            authority: entry.authority,
+            count: entry.count,
+            rank,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Drop the entries of closed counters, passed as remaining accounts
+    /// Anyone may prune; a counter that still exists keeps its place
+    pub fn prune_leaderboard(ctx: Context<PruneLeaderboard>) -> Result<()> {
+        let leaderboard = ctx.accounts.leaderboard.key();
+        for account in ctx.remaining_accounts {
+            require!(
+                account.owner == &system_program::ID && account.data_is_empty(),
+                CounterError::CounterNotClosed
+            );
+            if !ctx.accounts.leaderboard.remove(account.key) {
+                continue;
+            }
+            msg!(
+                "PDA {} pruned from leaderboard {}",
+                account.key(),
+                leaderboard
+            );
+            let event = ScorePruned {
+                leaderboard,
+                counter: account.key(),
+            };
+            emit_event!(ctx, event);
+        }
+        Ok(())
+    }
+
+    /// Create the treasury collecting the counter's fees
+    pub fn init_treasury(ctx: Context<InitTreasury>, counter_id: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.counter = ctx.accounts.counter.key();
+        treasury.collected = 0;
+        treasury.bump = ctx.bumps.treasury;
+        msg!(
+            "PDA {} (id {}) treasury created at {}",
+            treasury.counter,
+            counter_id,
+            treasury.key()
+        );
+        let event = TreasuryInitialized {
+            counter: treasury.counter,
+            treasury: treasury.key(),
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Move `amount` of the collected fees to the recipient
+    /// Either the counter's authority or the program admin may withdraw; the treasury
+    /// keeps enough lamports to stay rent-exempt
+    pub fn withdraw(ctx: Context<Withdraw>, counter_id: u64, amount: u64) -> Result<()> {
+        let recipient = ctx.accounts.recipient.to_account_info();
+        withdraw_fees(&ctx.accounts.treasury.to_account_info(), &recipient, amount)?;
+        msg!(
+            "PDA {} (id {}) withdrew {} to {}",
+            ctx.accounts.counter.key(),
+            counter_id,
+            amount,
+            recipient.key()
+        );
+        let event = FeesWithdrawn {
+            counter: ctx.accounts.counter.key(),
+            recipient: recipient.key(),
+            amount,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Create the program-wide config with `admin` as the key allowed to pause
+    /// Can only be called once, by the program's upgrade authority
+    pub fn init_config(ctx: Context<InitConfig>, admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = admin;
+        config.paused = false;
+        config.bump = ctx.bumps.config;
+        msg!("Config {} created with admin: {}", config.key(), admin);
+        let event = ConfigUpdated {
+            admin,
+            paused: false,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Pause or resume increment, decrement and set on every counter
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+        msg!("Program paused: {}", paused);
+        let event = ConfigUpdated {
+            admin: config.admin,
+            paused,
+        };
+        emit_event!(ctx, event);
+        Ok(())
+    }
+
+    /// Hand the config over to a new admin
+    pub fn set_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = new_admin;
+        msg!("Config admin set to: {}", new_admin);
+        let event = ConfigUpdated {
+            admin: new_admin,
+            paused: config.paused,
+        };
+        emit_event!(ctx, event);
         Ok(())
     }
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
+#[instruction(counter_id: u64)]
 pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
         space = 8 + Counter::INIT_SPACE,
-        seeds = [authority.key().as_ref()],
+        seeds = [authority.key().as_ref(), &counter_id.to_le_bytes()],
         bump
     )]
     pub counter: Account<'info, Counter>,
 
+    /// Pays for the counter, and becomes its authority unless a multisig is created
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    /// Created, and made the counter's authority, when initialize is passed a multisig
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
+#[cfg_attr(feature = "session-keys", derive(Session))]
+#[instruction(counter_id: u64)]
 pub struct Update<'info> {
     #[account(
         mut,
-        seeds = [authority.key().as_ref()],
-        bump
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = !counter.frozen @ CounterError::Paused
     )]
     pub counter: Account<'info, Counter>,
 
-    pub authority: Signer<'info>,
+    /// The counter's authority, an operator permitted to perform the instruction,
+    /// a session signer acting for the authority, or a member of the multisig
+    /// holding the authority where its update policy allows
+    /// Writable as it pays the counter's fee, even when it isn't the transaction's fee payer
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// Lets a short-lived session key sign in the authority's place
+    #[cfg(feature = "session-keys")]
+    #[session(signer = signer, authority = counter.authority.key())]
+    pub session_token: Option<Account<'info, SessionToken>>,
+
+    /// Holds the program-wide pause, counted as unpaused when left out
+    /// (as before init_config has run)
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ CounterError::Paused
+    )]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Appended to on every change when present
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Required when the signer is an operator rather than the authority
+    #[account(
+        seeds = [OPERATORS_SEED, counter.key().as_ref()],
+        bump = operators.bump
+    )]
+    pub operators: Option<Account<'info, Operators>>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    /// Required from signers incrementing a public counter they hold no other rights on
+    #[account(
+        mut,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), signer.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Option<Account<'info, Contribution>>,
+
+    /// Receives the fee of increments and sets while the counter charges one
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Required for increments while the counter is token gated, an SPL Token or
+    /// Token-2022 account of the gate mint owned by the signer
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for increments while the counter mints rewards, see set_reward
+    #[account(mut, address = counter.reward_mint @ CounterError::InvalidRewardAccount)]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Receives the signer's rewards, a Token-2022 account of the reward mint
+    #[account(mut)]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token2022>>,
+
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct Counter {
-    /// The current count value
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitHistory<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CounterHistory>(),
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, CounterHistory>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct Close<'info> {
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Any account may receive the reclaimed rent
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's history PDA, closed in the handler if it was created
+    #[account(mut, seeds = [HISTORY_SEED, counter.key().as_ref()], bump)]
+    pub history: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's operators PDA, closed in the handler if it was created
+    #[account(mut, seeds = [OPERATORS_SEED, counter.key().as_ref()], bump)]
+    pub operators: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's treasury PDA, closed in the handler if it was created
+    #[account(mut, seeds = [TREASURY_SEED, counter.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: The counter's pending set PDA, closed in the handler if a set is pending
+    #[account(mut, seeds = [PENDING_SET_SEED, counter.key().as_ref()], bump)]
+    pub pending_set: UncheckedAccount<'info>,
+
+    /// CHECK: The multisig PDA of a counter it once held, closed in the handler if it was
+    /// created
+    #[account(mut, seeds = [MULTISIG_SEED, counter.key().as_ref()], bump)]
+    pub multisig: UncheckedAccount<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitOperators<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Operators::INIT_SPACE,
+        seeds = [OPERATORS_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub operators: Account<'info, Operators>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ManageOperators<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        seeds = [OPERATORS_SEED, counter.key().as_ref()],
+        bump = operators.bump
+    )]
+    pub operators: Account<'info, Operators>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Close the contributions of `counter` among `accounts`, each followed by its
+/// contributor, who gets the rent back
+fn close_contributions(counter: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+    for pair in accounts.chunks(2) {
+        let [info, contributor] = pair else {
+            return err!(ErrorCode::AccountNotEnoughKeys);
+        };
+        require_keys_eq!(
+            *info.owner,
+            crate::ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let contribution = Contribution::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(
+            contribution.counter,
+            *counter,
+            CounterError::InvalidContribution
+        );
+        require_keys_eq!(
+            contribution.contributor,
+            contributor.key(),
+            CounterError::InvalidContribution
+        );
+        close_account(info, contributor)?;
+    }
+    Ok(())
+}
+
+/// Move all lamports of `account` to `recipient` and hand it back to the system program
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> Result<()> {
+    let lamports = account.lamports();
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Account context for migrating a counter to the current layout
+/// The counter is taken unchecked since older layouts don't deserialize as Counter
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct Migrate<'info> {
+    /// CHECK: Owner is validated below, discriminator and seeds in the handler
+    #[account(mut, owner = crate::ID)]
+    pub counter: UncheckedAccount<'info>,
+
+    /// The new address of a counter in the original layout, which is closed into it
+    /// Only passed for such counters, by their authority
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [payer.key().as_ref(), &counter_id.to_le_bytes()],
+        bump
+    )]
+    pub relocated: Option<Account<'info, Counter>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.pending_authority == new_authority.key() @ CounterError::NotPendingAuthority
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct CreateProposal<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// A proposal left at this index by a multisig closed with an earlier counter at the
+    /// same address is overwritten
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [
+            PROPOSAL_SEED,
+            multisig.key().as_ref(),
+            &multisig.proposal_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// One of the multisig's signers
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, index: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), &index.to_le_bytes()],
+        bump = proposal.bump,
+        constraint = index < multisig.proposal_count @ CounterError::StaleProposal
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// One of the multisig's signers
+    pub member: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, index: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    /// Must still hold the authority, so proposals die with a handover
+    /// Receives the rent of a cancelled set and is closed by Close
+    #[account(
+        mut,
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::InvalidAuth
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, multisig.key().as_ref(), &index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        constraint = index < multisig.proposal_count @ CounterError::StaleProposal
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal's rent, checked against the proposal
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Counted as unpaused when left out, as in Update
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Pays the counter's fee on count changes, as the signer of an update does
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// Appended to by count changes when present, as in Update
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Required by AddOperator and RemoveOperator
+    #[account(
+        mut,
+        seeds = [OPERATORS_SEED, counter.key().as_ref()],
+        bump = operators.bump
+    )]
+    pub operators: Option<Account<'info, Operators>>,
+
+    /// Receives the fee of count changes while the counter charges one, and pays out
+    /// Withdraw
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    /// Required by ScheduleSet, which creates it if needed, and CancelSet
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = 8 + PendingSet::INIT_SPACE,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub pending_set: Option<Account<'info, PendingSet>>,
+
+    /// CHECK: Required by Withdraw and Close, checked against the proposal's recipient
+    #[account(mut)]
+    pub recipient: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl ExecuteProposal<'_> {
+    /// Apply a count change the way Update does, held back by a freeze or pause
+    fn apply(
+        &mut self,
+        counter_id: u64,
+        op: CounterOp,
+        update: impl FnOnce(&mut Counter) -> Result<()>,
+    ) -> Result<CounterChanged> {
+        require!(
+            !self.counter.frozen && !self.config.as_ref().is_some_and(|config| config.paused),
+            CounterError::Paused
+        );
+        charge_fee(
+            &self.counter,
+            op,
+            &self.executor,
+            self.treasury.as_mut(),
+            &self.system_program,
+        )?;
+        write_count(
+            &mut self.counter,
+            self.history.as_ref(),
+            counter_id,
+            op,
+            self.multisig.key(),
+            update,
+        )
+    }
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ScheduleSet<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PendingSet::INIT_SPACE,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct ExecuteSet<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = !counter.frozen @ CounterError::Paused
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump = pending_set.bump,
+        has_one = authority
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    /// CHECK: Receives the pending set's rent, checked against the pending set
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Counted as unpaused when left out, as in Update
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.paused @ CounterError::Paused
+    )]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// Pays the counter's fee, as the signer of an update does
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// Appended to when present, as in Update
+    #[account(
+        mut,
+        seeds = [HISTORY_SEED, counter.key().as_ref()],
+        bump = history.load()?.bump
+    )]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    /// Receives the fee while the counter charges one, as in Update
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Option<Account<'info, Treasury>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct CancelSet<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_SET_SEED, counter.key().as_ref()],
+        bump = pending_set.bump
+    )]
+    pub pending_set: Account<'info, PendingSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetFrozen<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetRateLimit<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetResetPeriod<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetPublic<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetTokenGate<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetCallerProgram<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64, decimals: u8)]
+pub struct InitRewardMint<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [REWARD_MINT_SEED, counter.key().as_ref()],
+        bump,
+        mint::decimals = decimals,
+        mint::authority = reward_mint,
+        mint::token_program = token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub token_program: Program<'info, Token2022>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SetReward<'info> {
+    #[account(
+        mut,
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitContribution<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.public @ CounterError::CounterNotPublic
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = contributor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [CONTRIBUTION_SEED, counter.key().as_ref(), contributor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitLeaderboard<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account<Counter> checks the counter is owned by this program, its seeds that it
+/// is the counter it claims to be; the leaderboard's authority picks what gets ranked
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct SubmitScore<'info> {
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, leaderboard.authority.as_ref()],
+        bump = leaderboard.bump,
+        has_one = authority @ CounterError::InvalidAuth
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct PruneLeaderboard<'info> {
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, leaderboard.authority.as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct InitTreasury<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump,
+        constraint = counter.is_authority_or_member(&authority.key(), multisig.as_ref())
+            @ CounterError::InvalidAuth
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// The authority, or any one of the signers of the multisig holding it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required when the signer is a member of the multisig holding the authority
+    #[account(
+        seeds = [MULTISIG_SEED, counter.key().as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Option<Account<'info, Multisig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(counter_id: u64)]
+pub struct Withdraw<'info> {
+    #[account(
+        seeds = [counter.seed_authority.as_ref(), &counter_id.to_le_bytes()],
+        bump = counter.bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY_SEED, counter.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Only needed for the admin to withdraw
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Option<Account<'info, ProgramConfig>>,
+
+    /// The counter's authority or the program admin
+    #[account(
+        constraint = signer.key() == counter.authority
+            || config.as_ref().is_some_and(|config| signer.key() == config.admin)
+            @ CounterError::InvalidAuth
+    )]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Any account may receive the withdrawn lamports
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
+
+/// Check the rate limit, run `update` on the counter, bump its sequence and record
+/// the change in `history` when passed: the path every write to the count takes,
+/// from Update, execute_set and execute_proposal alike
+fn write_count(
+    counter: &mut Account<Counter>,
+    history: Option<&AccountLoader<CounterHistory>>,
+    counter_id: u64,
+    op: CounterOp,
+    signer: Pubkey,
+    update: impl FnOnce(&mut Counter) -> Result<()>,
+) -> Result<CounterChanged> {
+    let clock = Clock::get()?;
+    counter.throttle(&clock)?;
+    let old = counter.count;
+    counter.roll_period(&clock);
+    update(counter)?;
+    counter.bump_sequence();
+    msg!(
+        "PDA {} (id {}) count: {}",
+        counter.key(),
+        counter_id,
+        counter.count
+    );
+    if let Some(history) = history {
+        history.load_mut()?.push(HistoryEntry {
+            slot: clock.slot,
+            unix_ts: clock.unix_timestamp,
+            signer,
+            old,
+            new: counter.count,
+            op: op as u8,
+            _padding: [0; 7],
+        });
+    }
+    Ok(CounterChanged {
+        counter: counter.key(),
+        old,
+        new: counter.count,
+        sequence: counter.sequence,
+        op,
+        signer,
+        via_session: false,
+    })
+}
+
+/// Take the counter's fee for `op` from `payer` into its treasury
+fn charge_fee<'info>(
+    counter: &Counter,
+    op: CounterOp,
+    payer: &Signer<'info>,
+    treasury: Option<&mut Account<'info, Treasury>>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let fee = counter.fee;
+    if fee == 0 || op == CounterOp::Decrement {
+        return Ok(());
+    }
+    let treasury = treasury.ok_or(CounterError::TreasuryRequired)?;
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+    treasury.collected = treasury.collected.saturating_add(fee);
+    Ok(())
+}
+
+/// Move `amount` of the fees collected in `treasury` to `recipient`, keeping the
+/// treasury rent-exempt
+fn withdraw_fees(treasury: &AccountInfo, recipient: &AccountInfo, amount: u64) -> Result<()> {
+    let reserve = Rent::get()?.minimum_balance(treasury.data_len());
+    require!(
+        treasury.lamports().saturating_sub(reserve) >= amount,
+        CounterError::InsufficientFunds
+    );
+    move_lamports(treasury, recipient, amount)
+}
+
+/// Move lamports out of an account owned by this program
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// Account context for creating the program config
+/// The upgrade authority is read from the program's ProgramData account
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ CounterError::InvalidAuth
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ CounterError::InvalidAuth
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+impl Update<'_> {
+    /// Run `update` on the counter, log the new value and describe the change as an event
+    fn apply(
+        &mut self,
+        counter_id: u64,
+        op: CounterOp,
+        update: impl FnOnce(&mut Counter) -> Result<()>,
+    ) -> Result<CounterChanged> {
+        self.authorize(op)?;
+        self.check_gate(op)?;
+        charge_fee(
+            &self.counter,
+            op,
+            &self.signer,
+            self.treasury.as_mut(),
+            &self.system_program,
+        )?;
+        self.reward(op)?;
+        let event = write_count(
+            &mut self.counter,
+            self.history.as_ref(),
+            counter_id,
+            op,
+            self.signer.key(),
+            update,
+        )?;
+        Ok(CounterChanged {
+            via_session: self.via_session(),
+            ..event
+        })
+    }
+
+    /// Whether the signer acted through a session token, which `session_auth_or`
+    /// has already validated against the counter's authority
+    fn via_session(&self) -> bool {
+        #[cfg(feature = "session-keys")]
+        return self.session_token.is_some();
+        #[cfg(not(feature = "session-keys"))]
+        false
+    }
+
+    /// Whether the signer is the counter's authority, the caller program's PDA, a listed
+    /// operator, a member of its multisig or anyone at all on a public counter, the
+    /// check `session_auth_or` falls back to without a session token
+    #[cfg(feature = "session-keys")]
+    fn may_sign(&self) -> bool {
+        let signer = self.signer.key();
+        signer == self.counter.authority
+            || self.counter.public
+            || self.is_caller_signer()
+            || self
+                .operators
+                .as_ref()
+                .is_some_and(|operators| operators.find(&signer).is_some())
+            || self
+                .authority_multisig()
+                .is_some_and(|multisig| multisig.is_signer(&signer))
+    }
+
+    /// Whether the signer is the caller program's PDA for this counter, which can
+    /// only sign through CPI from that program
+    fn is_caller_signer(&self) -> bool {
+        let program = self.counter.caller_program;
+        program != Pubkey::default()
+            && self.signer.key()
+                == Pubkey::find_program_address(
+                    &[CALLER_SIGNER_SEED, self.counter.key().as_ref()],
+                    &program,
+                )
+                .0
+    }
+
+    /// The multisig passed along, if it holds the counter's authority
+    fn authority_multisig(&self) -> Option<&Multisig> {
+        self.multisig
+            .as_ref()
+            .filter(|multisig| multisig.key() == self.counter.authority)
+            .map(|multisig| &**multisig)
+    }
+
+    /// Check that the signer is the counter's authority, a session signer acting
+    /// for it, the caller program's PDA incrementing, a multisig member allowed to
+    /// `op` without a proposal, an operator allowed to `op`, or anyone with a
+    /// contribution incrementing a public counter
+    fn authorize(&self, op: CounterOp) -> Result<()> {
+        let signer = self.signer.key();
+        if signer == self.counter.authority || self.via_session() {
+            return Ok(());
+        }
+        if self.is_caller_signer() {
+            require!(
+                op == CounterOp::Increment,
+                CounterError::CallerMayOnlyIncrement
+            );
+            return Ok(());
+        }
+        if let Some(multisig) = self
+            .authority_multisig()
+            .filter(|multisig| multisig.is_signer(&signer))
+        {
+            require!(
+                multisig.config.allows_direct(op),
+                CounterError::MultisigProposalRequired
+            );
+            return Ok(());
+        }
+        match self
+            .operators
+            .as_ref()
+            .and_then(|operators| operators.find(&signer))
+        {
+            Some(operator) => require!(
+                operator.permissions & op.permission() != 0,
+                CounterError::MissingPermission
+            ),
+            None if self.counter.public && op == CounterOp::Increment => require!(
+                self.contribution.is_some(),
+                CounterError::ContributionRequired
+            ),
+            None => return err!(CounterError::InvalidAuth),
+        }
+        Ok(())
+    }
+
+    /// Check that an incrementing signer holds enough of the counter's gate mint,
+    /// through the authority's token account when acting with a session key
+    fn check_gate(&self, op: CounterOp) -> Result<()> {
+        let counter = &self.counter;
+        if counter.gate_mint == Pubkey::default() || op != CounterOp::Increment {
+            return Ok(());
+        }
+        let token = self
+            .gate_token_account
+            .as_ref()
+            .ok_or(CounterError::GateTokenRequired)?;
+        require!(
+            token.mint == counter.gate_mint && token.owner == self.holder(),
+            CounterError::InvalidGateToken
+        );
+        require!(
+            token.amount >= counter.min_balance.max(1),
+            CounterError::GateBalanceTooLow
+        );
+        Ok(())
+    }
+
+    /// Owner of the signer's token accounts: the authority when acting with a session key
+    fn holder(&self) -> Pubkey {
+        if self.via_session() {
+            self.counter.authority
+        } else {
+            self.signer.key()
+        }
+    }
+
+    /// Mint the counter's reward for `op` to the signer's token account
+    fn reward(&self, op: CounterOp) -> Result<()> {
+        let counter = &self.counter;
+        if counter.reward_amount == 0 || op != CounterOp::Increment {
+            return Ok(());
+        }
+        let (Some(mint), Some(to), Some(token_program)) = (
+            &self.reward_mint,
+            &self.reward_token_account,
+            &self.token_program,
+        ) else {
+            return err!(CounterError::RewardAccountsRequired);
+        };
+        require!(
+            to.mint == mint.key() && to.owner == self.holder(),
+            CounterError::InvalidRewardAccount
+        );
+        let counter_key = counter.key();
+        let seeds: &[&[u8]] = &[
+            REWARD_MINT_SEED,
+            counter_key.as_ref(),
+            &[counter.reward_bump],
+        ];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                MintTo {
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            counter.reward_amount,
+        )
+    }
+
+    /// Credit `amount` to the signer's contribution when one was passed
+    fn contribute(&mut self, amount: u64) {
+        if let Some(contribution) = &mut self.contribution {
+            contribution.record(amount);
+        }
+    }
+}
+
+// The account traits are implemented by hand rather than with `#[account]` so that
+// a counter still in an older layout fails with AccountNotMigrated instead of a
+// generic deserialization error
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Counter {
+    /// Layout version of the account, see Counter::VERSION
+    pub version: u8,
+    /// The current count value
+    pub count: u64,
+    /// The authority who can update the counter
+    pub authority: Pubkey,
+    /// Id distinguishing this counter among the authority's counters (part of the PDA seeds)
+    pub counter_id: u64,
+    /// The original authority whose key is part of the PDA seeds
+    /// Kept separately so the address stays stable after an authority handover
+    pub seed_authority: Pubkey,
+    /// The canonical bump of the counter PDA
+    pub bump: u8,
+    /// Authority proposed via propose_authority, waiting for accept_authority
+    /// Pubkey::default() when no transfer is pending
+    pub pending_authority: Pubkey,
+    /// Lowest value the counter may hold
+    pub min: u64,
+    /// Highest value the counter may hold
+    pub max: u64,
+    /// What happens when an update would leave `min..=max`
+    pub overflow_policy: OverflowPolicy,
+    /// Set by the authority to reject increment, decrement and set
+    pub frozen: bool,
+    /// Bumped by every write to `count`, so clients can tell whether the value they
+    /// read is still current
+    pub sequence: u64,
+    /// How often the counter may be updated, all zeroes for no limit
+    pub rate_limit: RateLimit,
+    /// Slot of the last increment, decrement or set
+    pub last_update_slot: u64,
+    /// Unix timestamp of the last increment, decrement or set
+    pub last_update_ts: i64,
+    /// Unix timestamp the current rate limit window started at
+    pub window_start: i64,
+    /// Updates made in the current rate limit window
+    pub window_count: u64,
+    /// How often the count resets, Never unless set via set_reset_period
+    pub reset_period: ResetPeriod,
+    /// Start of the reset period the count belongs to, see ResetPeriod::period_start
+    pub period_start: i64,
+    /// Final count of the previous reset period
+    pub last_period_count: u64,
+    /// Whether any signer may increment, see set_public
+    pub public: bool,
+    /// Lamports charged for every increment and set, see set_fee
+    pub fee: u64,
+    /// Mint whose holders may increment, Pubkey::default() for none, see set_token_gate
+    pub gate_mint: Pubkey,
+    /// Balance of gate_mint an incrementing signer must hold, at least 1
+    pub min_balance: u64,
+    /// Token-2022 mint increments are rewarded in, Pubkey::default() for none,
+    /// see init_reward_mint
+    pub reward_mint: Pubkey,
+    /// Reward tokens minted to the signer on every increment, see set_reward
+    pub reward_amount: u64,
+    /// Bump of the reward mint, which signs as its own mint authority
+    pub reward_bump: u8,
+    /// Program whose caller signer PDA may increment, Pubkey::default() for none,
+    /// see set_caller_program
+    pub caller_program: Pubkey,
+}
+
+impl Counter {
+    /// Current layout version, bumped whenever a field is appended
+    /// Appended fields must treat all-zero bytes as their default, which migrate fills in
+    pub const VERSION: u8 = 10;
+
+    /// Data length of counters in the original layout, `{count, authority}` with no
+    /// version byte, which lived at the PDA of `[authority]` alone
+    const BASELINE_LEN: usize = 8 + 8 + 32;
+
+    /// Layout version of raw account data (discriminator included)
+    /// The original layout counts as version 0
+    fn stored_version(data: &[u8]) -> u8 {
+        if data.len() == Self::BASELINE_LEN {
+            0
+        } else {
+            data.get(8).copied().unwrap_or_default()
+        }
+    }
+
+    /// Decode account data stored in any layout up to the current one
+    /// Returns the stored version along with the counter in the current layout
+    pub fn upgrade(data: &[u8]) -> Result<(u8, Self)> {
+        require!(
+            data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let version = Self::stored_version(data);
+        let body = &data[8..];
+        // Version 0 has no version byte but starts with the same fields, every later
+        // version only appends fields
+        let start = 8 + usize::from(version == 0);
+        let mut current = [0; 8 + Self::INIT_SPACE];
+        require!(
+            version <= Self::VERSION && start + body.len() <= current.len(),
+            ErrorCode::AccountDidNotDeserialize
+        );
+        current[start..start + body.len()].copy_from_slice(body);
+        current[8] = Self::VERSION;
+        let mut counter = Self::try_deserialize_unchecked(&mut &current[..])?;
+        if version == 0 {
+            // The original layout counted from 0 up to u64::MAX and failed past either
+            // end; counter_id and bump are only known once migrate moves it
+            counter.seed_authority = counter.authority;
+            counter.max = u64::MAX;
+            counter.overflow_policy = OverflowPolicy::Error;
+        }
+        Ok((version, counter))
+    }
+
+    /// Address of a counter in the original layout, the PDA of its authority alone
+    pub fn baseline_address(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[authority.as_ref()], &crate::ID).0
+    }
+
+    /// Add `amount` following the counter's bounds and overflow policy
+    pub fn add(&mut self, amount: u64) -> Result<()> {
+        let next = self.count as u128 + amount as u128;
+        self.count = if next <= self.max as u128 {
+            next as u64
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Wrap => self.wrap(next - self.min as u128),
+                OverflowPolicy::Saturate => self.max,
+                OverflowPolicy::Error => return err!(CounterError::Overflow),
+            }
+        };
+        Ok(())
+    }
+
+    /// Subtract `amount` following the counter's bounds and overflow policy
+    pub fn sub(&mut self, amount: u64) -> Result<()> {
+        let offset = self.count - self.min;
+        self.count = if amount <= offset {
+            self.count - amount
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Wrap => {
+                    // Step back by `amount` modulo the span to stay non-negative
+                    let span = self.span();
+                    self.wrap(offset as u128 + span - amount as u128 % span)
+                }
+                OverflowPolicy::Saturate => self.min,
+                OverflowPolicy::Error => return err!(CounterError::CounterUnderflow),
+            }
+        };
+        Ok(())
+    }
+
+    /// Set the count to `value`, which must lie within `min..=max`
+    pub fn set(&mut self, value: u64) -> Result<()> {
+        require!(
+            (self.min..=self.max).contains(&value),
+            CounterError::ValueOutOfRange
+        );
+        self.count = value;
+        Ok(())
+    }
+
+    /// Set the count to `new` if it is currently `expected`
+    pub fn compare_and_set(&mut self, expected: u64, new: u64) -> Result<()> {
+        require_eq!(self.count, expected, CounterError::CountMismatch);
+        self.set(new)
+    }
+
+    /// Check the rate limit at `clock` and record an update against it
+    pub fn throttle(&mut self, clock: &Clock) -> Result<()> {
+        let limit = self.rate_limit;
+        let now = clock.unix_timestamp;
+        require!(
+            clock.slot.saturating_sub(self.last_update_slot) >= limit.min_slots
+                && now.saturating_sub(self.last_update_ts) >= limit.min_seconds as i64,
+            CounterError::RateLimited
+        );
+        if limit.max_per_window != 0 {
+            if now.saturating_sub(self.window_start) >= limit.window_seconds as i64 {
+                self.window_start = now;
+                self.window_count = 0;
+            }
+            require!(
+                self.window_count < limit.max_per_window,
+                CounterError::RateLimited
+            );
+            self.window_count += 1;
+        }
+        self.last_update_slot = clock.slot;
+        self.last_update_ts = now;
+        Ok(())
+    }
+
+    /// Reset the count to `min` if `clock` has moved into a new reset period
+    /// The count it had is kept in `last_period_count`
+    pub fn roll_period(&mut self, clock: &Clock) {
+        if let Some(start) = self.reset_period.period_start(clock) {
+            if start != self.period_start {
+                self.last_period_count = self.count;
+                self.count = self.min;
+                self.period_start = start;
+            }
+        }
+    }
+
+    /// Record a write to the counter
+    pub fn bump_sequence(&mut self) {
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+
+    /// Store a rate limit after validating it
+    pub fn update_rate_limit(&mut self, rate_limit: RateLimit) -> Result<()> {
+        rate_limit.validate()?;
+        self.rate_limit = rate_limit;
+        Ok(())
+    }
+
+    /// Store a reset period after validating it
+    /// The period in progress counts as started, so the current value is kept
+    pub fn update_reset_period(&mut self, reset_period: ResetPeriod, clock: &Clock) -> Result<()> {
+        reset_period.validate()?;
+        self.reset_period = reset_period;
+        self.period_start = reset_period.period_start(clock).unwrap_or_default();
+        Ok(())
+    }
+
+    /// Store the amount minted per increment, which needs a reward mint
+    pub fn update_reward(&mut self, amount: u64) -> Result<()> {
+        require!(
+            self.reward_mint != Pubkey::default(),
+            CounterError::RewardMintRequired
+        );
+        self.reward_amount = amount;
+        Ok(())
+    }
+
+    /// Whether `key` is the authority, or one of the signers of `multisig` while it
+    /// holds the authority
+    pub fn is_authority_or_member(
+        &self,
+        key: &Pubkey,
+        multisig: Option<&Account<Multisig>>,
+    ) -> bool {
+        *key == self.authority
+            || multisig
+                .is_some_and(|multisig| multisig.key() == self.authority && multisig.is_signer(key))
+    }
+
+    /// Number of distinct values in `min..=max`
+    fn span(&self) -> u128 {
+        (self.max - self.min) as u128 + 1
+    }
+
+    /// Map an offset from `min` back into `min..=max`
+    fn wrap(&self, offset: u128) -> u64 {
+        (self.min as u128 + offset % self.span()) as u64
+    }
+
+    /// Check that `key` is the PDA derived from this counter's stored seeds
+    /// Used where the counter can't be validated by a seeds constraint
+    pub fn verify_address(&self, key: &Pubkey, counter_id: u64) -> Result<()> {
+        let expected = Pubkey::create_program_address(
+            &[
+                self.seed_authority.as_ref(),
+                &counter_id.to_le_bytes(),
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(expected, *key, ErrorCode::ConstraintSeeds);
+        Ok(())
+    }
+}
+
+/// Fixed-size ring buffer of the most recent changes to a counter
+#[account(zero_copy)]
+pub struct CounterHistory {
+    /// The counter whose changes are recorded
+    pub counter: Pubkey,
+    /// Number of entries ever appended; the oldest are overwritten once `entries` is full
+    pub total: u64,
+    /// The canonical bump of the history PDA
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    /// Entry `i` of the ring lives at `entries[i % entries.len()]`
+    pub entries: [HistoryEntry; 32],
+}
+
+impl CounterHistory {
+    /// Append `entry`, overwriting the oldest one when the buffer is full
+    pub fn push(&mut self, entry: HistoryEntry) {
+        let index = (self.total % self.entries.len() as u64) as usize;
+        self.entries[index] = entry;
+        self.total += 1;
+    }
+}
+
+/// A contributor's share of a public counter, a PDA per (counter, contributor)
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    /// The counter contributed to
+    pub counter: Pubkey,
+    /// The signer whose increments are tracked
+    pub contributor: Pubkey,
+    /// Sum of the amounts the contributor incremented by
+    pub amount: u64,
+    /// Number of increments the contributor made
+    pub updates: u64,
+    /// The canonical bump of the contribution PDA
+    pub bump: u8,
+}
+
+impl Contribution {
+    /// Credit one increment by `amount`
+    pub fn record(&mut self, amount: u64) {
+        self.amount = self.amount.saturating_add(amount);
+        self.updates += 1;
+    }
+}
+
+/// Top counters by count, a PDA per authority
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    /// The key that created the leaderboard (part of the PDA seeds)
+    pub authority: Pubkey,
+    /// Highest counts first, ties in order of submission
+    #[max_len(MAX_LEADERBOARD_ENTRIES)]
+    pub entries: Vec<LeaderboardEntry>,
+    /// The canonical bump of the leaderboard PDA
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    /// Put `entry` in its place by count, replacing any earlier entry of its counter
+    /// Returns its zero-based rank, or None if it didn't make the board
+    pub fn submit(&mut self, entry: LeaderboardEntry) -> Option<u8> {
+        self.entries
+            .retain(|existing| existing.counter != entry.counter);
+        let rank = self
+            .entries
+            .partition_point(|existing| existing.count >= entry.count);
+        if rank >= MAX_LEADERBOARD_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_LEADERBOARD_ENTRIES);
+        Some(rank as u8)
+    }
+
+    /// Drop the entry of `counter`, returning whether it was on the board
+    pub fn remove(&mut self, counter: &Pubkey) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.counter != *counter);
+        self.entries.len() < len
+    }
+}
+
+/// A counter's place on a leaderboard
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct LeaderboardEntry {
+    pub counter: Pubkey,
+    /// The counter's authority at the time of submission
+    pub authority: Pubkey,
     pub count: u64,
-    /// The authority who can update the counter
+}
+
+/// Fees collected for a counter, a PDA per counter holding them as lamports
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    /// The counter whose fees are collected
+    pub counter: Pubkey,
+    /// Total fees ever paid in, withdrawals aside
+    pub collected: u64,
+    /// The canonical bump of the treasury PDA
+    pub bump: u8,
+}
+
+/// A single recorded change
+#[zero_copy]
+pub struct HistoryEntry {
+    pub slot: u64,
+    pub unix_ts: i64,
+    /// The key that signed the update
+    pub signer: Pubkey,
+    pub old: u64,
+    pub new: u64,
+    /// CounterOp as its discriminant (0 increment, 1 decrement, 2 set, 3 compare_and_set)
+    pub op: u8,
+    pub _padding: [u8; 7],
+}
+
+impl Discriminator for Counter {
+    /// The discriminator `#[account]` derived, so existing counters keep matching
+    const DISCRIMINATOR: &'static [u8] = &[255, 176, 4, 245, 188, 253, 124, 25];
+}
+
+impl Owner for Counter {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+impl AccountSerialize for Counter {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        writer
+            .write_all(Self::DISCRIMINATOR)
+            .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        AnchorSerialize::serialize(self, writer).map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+        Ok(())
+    }
+}
+
+impl AccountDeserialize for Counter {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        require!(
+            buf.len() >= Self::DISCRIMINATOR.len(),
+            ErrorCode::AccountDiscriminatorNotFound
+        );
+        require!(
+            buf.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            Self::stored_version(buf) == Self::VERSION,
+            CounterError::AccountNotMigrated
+        );
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = &buf[Self::DISCRIMINATOR.len()..];
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+/// Keys other than the authority allowed to update a counter
+#[account]
+#[derive(InitSpace)]
+pub struct Operators {
+    /// The counter the operators may update
+    pub counter: Pubkey,
+    /// The canonical bump of the operators PDA
+    pub bump: u8,
+    #[max_len(MAX_OPERATORS)]
+    pub operators: Vec<Operator>,
+}
+
+impl Operators {
+    /// The entry for `key`, if it is listed
+    pub fn find(&self, key: &Pubkey) -> Option<&Operator> {
+        self.operators.iter().find(|operator| operator.key == *key)
+    }
+
+    /// List `key` with `permissions`, replacing the permissions of an existing entry
+    pub fn add(&mut self, key: Pubkey, permissions: u8) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !Operator::ALL == 0,
+            CounterError::InvalidPermissions
+        );
+        if let Some(operator) = self
+            .operators
+            .iter_mut()
+            .find(|operator| operator.key == key)
+        {
+            operator.permissions = permissions;
+            return Ok(());
+        }
+        require!(
+            self.operators.len() < MAX_OPERATORS,
+            CounterError::TooManyOperators
+        );
+        self.operators.push(Operator { key, permissions });
+        Ok(())
+    }
+
+    /// Delist `key`
+    pub fn remove(&mut self, key: &Pubkey) -> Result<()> {
+        let index = self
+            .operators
+            .iter()
+            .position(|operator| operator.key == *key)
+            .ok_or(CounterError::OperatorNotFound)?;
+        self.operators.swap_remove(index);
+        Ok(())
+    }
+}
+
+/// A key allowed to perform some updates on a counter
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Operator {
+    pub key: Pubkey,
+    /// Mask of Operator::INCREMENT, Operator::DECREMENT and Operator::SET
+    pub permissions: u8,
+}
+
+impl Operator {
+    /// Allows increment and increment_by
+    pub const INCREMENT: u8 = 1 << 0;
+    /// Allows decrement and decrement_by
+    pub const DECREMENT: u8 = 1 << 1;
+    /// Allows set and compare_and_set
+    pub const SET: u8 = 1 << 2;
+    /// Every permission
+    pub const ALL: u8 = Self::INCREMENT | Self::DECREMENT | Self::SET;
+}
+
+/// M-of-N signer set holding a counter's authority, a PDA per counter
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    /// The counter whose authority this is
+    pub counter: Pubkey,
+    pub config: MultisigConfig,
+    /// Number of proposals ever created, the index of the next one
+    pub proposal_count: u64,
+    /// The canonical bump of the multisig PDA
+    pub bump: u8,
+}
+
+impl Multisig {
+    /// Position of `key` among the signers, if it is one
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.config.signers.iter().position(|signer| signer == key)
+    }
+
+    /// Whether `key` is one of the signers
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signer_index(key).is_some()
+    }
+}
+
+/// Signers, threshold and update policy of a multisig, as passed to initialize
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct MultisigConfig {
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    /// Approvals a proposal needs before it can be executed
+    pub threshold: u8,
+    /// Who may increment and decrement the counter
+    pub update_policy: MultisigUpdatePolicy,
+}
+
+impl MultisigConfig {
+    /// Require 1..=MAX_MULTISIG_SIGNERS distinct signers and a threshold within their number
+    fn validate(&self) -> Result<()> {
+        let signers = &self.signers;
+        require!(
+            (1..=MAX_MULTISIG_SIGNERS).contains(&signers.len())
+                && (1..=signers.len()).contains(&usize::from(self.threshold))
+                && signers
+                    .iter()
+                    .enumerate()
+                    .all(|(i, signer)| !signers[..i].contains(signer)),
+            CounterError::InvalidMultisig
+        );
+        Ok(())
+    }
+
+    /// Whether a single signer may perform `op` without going through a proposal
+    pub fn allows_direct(&self, op: CounterOp) -> bool {
+        match op {
+            CounterOp::Increment | CounterOp::Decrement => {
+                self.update_policy == MultisigUpdatePolicy::AnySigner
+            }
+            CounterOp::Set | CounterOp::CompareAndSet => false,
+        }
+    }
+}
+
+/// How increment and decrement are authorized on a multisig counter
+/// Set and authority changes always go through proposals
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MultisigUpdatePolicy {
+    /// Any single signer may increment and decrement directly
+    AnySigner,
+    /// Increment and decrement need an executed proposal as well
+    Proposal,
+}
+
+/// A change to a multisig counter waiting for approvals, a PDA per proposal index
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    /// The multisig the proposal belongs to
+    pub multisig: Pubkey,
+    /// Position among the multisig's proposals (part of the PDA seeds)
+    pub index: u64,
+    /// The signer who created the proposal, refunded when it is executed
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    /// Bit `i` is set once the multisig's signer `i` approved
+    pub approvals: u8,
+    /// The canonical bump of the proposal PDA
+    pub bump: u8,
+}
+
+impl Proposal {
+    /// Record the approval of `key`, which must be one of the multisig's signers
+    pub fn approve(&mut self, multisig: &Multisig, key: &Pubkey) -> Result<()> {
+        let index = multisig
+            .signer_index(key)
+            .ok_or(CounterError::NotMultisigSigner)?;
+        self.approvals |= 1 << index;
+        Ok(())
+    }
+
+    /// Number of signers who approved
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+}
+
+/// What a proposal does once executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MultisigAction {
+    /// Set the counter to a value within its bounds
+    Set { value: u64 },
+    /// Increment the counter, following its overflow policy
+    Increment { amount: u64 },
+    /// Decrement the counter, following its overflow policy
+    Decrement { amount: u64 },
+    /// Propose a new authority, which then accepts with accept_authority
+    ProposeAuthority { new_authority: Pubkey },
+    /// As set_frozen
+    SetFrozen { frozen: bool },
+    /// As set_rate_limit
+    SetRateLimit { rate_limit: RateLimit },
+    /// As set_reset_period
+    SetResetPeriod { reset_period: ResetPeriod },
+    /// As set_public
+    SetPublic { public: bool },
+    /// As set_fee
+    SetFee { fee: u64 },
+    /// As set_token_gate
+    SetTokenGate {
+        gate_mint: Option<Pubkey>,
+        min_balance: u64,
+    },
+    /// As set_reward
+    SetReward { amount: u64 },
+    /// As set_caller_program
+    SetCallerProgram { program: Option<Pubkey> },
+    /// As add_operator, executed with the counter's Operators
+    AddOperator { operator: Pubkey, permissions: u8 },
+    /// As remove_operator, executed with the counter's Operators
+    RemoveOperator { operator: Pubkey },
+    /// As withdraw, executed with the counter's Treasury and `recipient`
+    Withdraw { amount: u64, recipient: Pubkey },
+    /// As schedule_set, executed with the counter's PendingSet, whose rent the executor
+    /// pays and the multisig gets back
+    ScheduleSet { value: u64, delay: u64 },
+    /// As cancel_set, executed with the counter's PendingSet
+    CancelSet,
+    /// As close, executed with `recipient` and the counter's history, operators,
+    /// treasury and pending set as remaining accounts, followed by any contributions to
+    /// close; the multisig is closed as well
+    Close { recipient: Pubkey },
+}
+
+/// A set waiting out its delay, a PDA per counter
+#[account]
+#[derive(InitSpace)]
+pub struct PendingSet {
+    /// The counter the set applies to
+    pub counter: Pubkey,
+    /// The authority that scheduled the set, refunded when it is executed or cancelled
+    pub authority: Pubkey,
+    /// The value the counter will be set to
+    pub value: u64,
+    /// Unix timestamp from which the set can be executed
+    pub execute_after: i64,
+    /// The canonical bump of the pending set PDA
+    pub bump: u8,
+}
+
+impl PendingSet {
+    /// Schedule a set of `counter` to `value` in `delay` seconds, refunded to its authority
+    /// Returns the timestamp from which it can be executed
+    fn schedule(
+        &mut self,
+        counter: &Account<Counter>,
+        value: u64,
+        delay: u64,
+        bump: u8,
+    ) -> Result<i64> {
+        require!(
+            (counter.min..=counter.max).contains(&value),
+            CounterError::ValueOutOfRange
+        );
+        // init_if_needed: a pending set that was neither executed nor cancelled is still live
+        require_keys_eq!(
+            self.counter,
+            Pubkey::default(),
+            CounterError::SetAlreadyPending
+        );
+        let execute_after = Clock::get()?
+            .unix_timestamp
+            .checked_add_unsigned(delay)
+            .ok_or(CounterError::Overflow)?;
+        self.counter = counter.key();
+        self.authority = counter.authority;
+        self.value = value;
+        self.execute_after = execute_after;
+        self.bump = bump;
+        Ok(execute_after)
+    }
+}
+
+/// Limits on how often a counter may be updated, set via set_rate_limit
+/// Each zero field disables its own check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct RateLimit {
+    /// Slots that must pass between two updates
+    pub min_slots: u64,
+    /// Seconds that must pass between two updates, at the Clock's whole-second resolution
+    pub min_seconds: u64,
+    /// Most updates within a window of `window_seconds`
+    pub max_per_window: u64,
+    /// Length of the window `max_per_window` applies to
+    pub window_seconds: u64,
+}
+
+impl RateLimit {
+    /// Reject a per-window cap without a window to apply it to, and durations that
+    /// don't fit a timestamp
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.max_per_window == 0 || self.window_seconds != 0,
+            CounterError::InvalidRateLimit
+        );
+        require!(
+            i64::try_from(self.min_seconds).is_ok() && i64::try_from(self.window_seconds).is_ok(),
+            CounterError::InvalidRateLimit
+        );
+        Ok(())
+    }
+}
+
+/// Boundaries at which a counter resets, set via set_reset_period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum ResetPeriod {
+    /// Keep counting forever
+    #[default]
+    Never,
+    /// Reset at midnight UTC
+    DailyUtc,
+    /// Reset when the Solana epoch changes
+    Epoch,
+    /// Reset every `seconds`, counted from the Unix epoch
+    Seconds { seconds: u64 },
+}
+
+impl ResetPeriod {
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+    /// Reject periods that never end or whose length doesn't fit a timestamp
+    fn validate(&self) -> Result<()> {
+        if let ResetPeriod::Seconds { seconds } = *self {
+            require!(
+                seconds != 0 && i64::try_from(seconds).is_ok(),
+                CounterError::InvalidResetPeriod
+            );
+        }
+        Ok(())
+    }
+
+    /// Start of the period `clock` falls in, None for Never
+    /// This is the epoch number for Epoch and a Unix timestamp otherwise
+    pub fn period_start(&self, clock: &Clock) -> Option<i64> {
+        let now = clock.unix_timestamp;
+        match *self {
+            ResetPeriod::Never => None,
+            ResetPeriod::DailyUtc => Some(now - now.rem_euclid(Self::SECONDS_PER_DAY)),
+            ResetPeriod::Epoch => Some(clock.epoch as i64),
+            ResetPeriod::Seconds { seconds } => Some(now - now.rem_euclid(seconds as i64)),
+        }
+    }
+}
+
+/// Program-wide settings, a singleton PDA
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    /// The key allowed to pause the program and hand over the config
+    pub admin: Pubkey,
+    /// While set, increment, decrement and set fail on every counter
+    pub paused: bool,
+    /// The canonical bump of the config PDA
+    pub bump: u8,
+}
+
+/// Behaviour of increment and decrement at the counter's bounds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OverflowPolicy {
+    /// Continue from the opposite bound
+    Wrap,
+    /// Stop at the bound
+    Saturate,
+    /// Fail with Overflow / CounterUnderflow
+    Error,
+}
+
+/// Kind of update recorded in CounterChanged and HistoryEntry
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CounterOp {
+    Increment,
+    Decrement,
+    Set,
+    CompareAndSet,
+}
+
+impl CounterOp {
+    /// The operator permission needed to perform this update
+    pub fn permission(self) -> u8 {
+        match self {
+            CounterOp::Increment => Operator::INCREMENT,
+            CounterOp::Decrement => Operator::DECREMENT,
+            CounterOp::Set | CounterOp::CompareAndSet => Operator::SET,
+        }
+    }
+}
+
+#[event]
+pub struct CounterInitialized {
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub counter_id: u64,
+    pub count: u64,
+}
+
+#[event]
+pub struct CounterChanged {
+    pub counter: Pubkey,
+    pub old: u64,
+    pub new: u64,
+    /// The counter's sequence after this change
+    pub sequence: u64,
+    pub op: CounterOp,
+    pub signer: Pubkey,
+    /// Whether the signer acted through a session token rather than as the authority
+    pub via_session: bool,
+}
+
+#[event]
+pub struct HistoryInitialized {
+    pub counter: Pubkey,
+    pub history: Pubkey,
+}
+
+#[event]
+pub struct OperatorsInitialized {
+    pub counter: Pubkey,
+    pub operators: Pubkey,
+}
+
+#[event]
+pub struct OperatorChanged {
+    pub counter: Pubkey,
+    pub operator: Pubkey,
+    /// The operator's new permission mask, 0 when it was removed
+    pub permissions: u8,
+}
+
+#[event]
+pub struct MultisigCreated {
+    pub counter: Pubkey,
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub counter: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    /// Number of signers who approved so far
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub counter: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub action: MultisigAction,
+}
+
+#[event]
+pub struct CounterClosed {
+    pub counter: Pubkey,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct CounterMigrated {
+    pub counter: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct SetScheduled {
+    pub counter: Pubkey,
+    pub value: u64,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct SetCancelled {
+    pub counter: Pubkey,
+    pub value: u64,
+}
+
+#[event]
+pub struct CounterFrozen {
+    pub counter: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct RateLimitChanged {
+    pub counter: Pubkey,
+    pub rate_limit: RateLimit,
+}
+
+#[event]
+pub struct ResetPeriodChanged {
+    pub counter: Pubkey,
+    pub reset_period: ResetPeriod,
+    pub period_start: i64,
+}
+
+#[event]
+pub struct CounterPublicChanged {
+    pub counter: Pubkey,
+    pub public: bool,
+}
+
+#[event]
+pub struct ContributionInitialized {
+    pub counter: Pubkey,
+    pub contributor: Pubkey,
+    pub contribution: Pubkey,
+}
+
+#[event]
+pub struct LeaderboardInitialized {
+    pub leaderboard: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ScoreSubmitted {
+    pub leaderboard: Pubkey,
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    /// Zero-based place on the board, None if the count didn't make it
+    pub rank: Option<u8>,
+}
+
+#[event]
+pub struct ScorePruned {
+    pub leaderboard: Pubkey,
+    pub counter: Pubkey,
+}
+
+#[event]
+pub struct FeeChanged {
+    pub counter: Pubkey,
+    pub fee: u64,
+}
+
+#[event]
+pub struct TreasuryInitialized {
+    pub counter: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub counter: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TokenGateChanged {
+    pub counter: Pubkey,
+    pub gate_mint: Option<Pubkey>,
+    pub min_balance: u64,
+}
+
+#[event]
+pub struct RewardMintInitialized {
+    pub counter: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct RewardChanged {
+    pub counter: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CallerProgramChanged {
+    pub counter: Pubkey,
+    pub program: Option<Pubkey>,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub counter: Pubkey,
     pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub counter: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
 }
 
 #[error_code]
 pub enum CounterError {
-    #[msg("Counter cannot go below zero")]
+    #[msg("Counter cannot go below its minimum")]
     CounterUnderflow,
+    #[msg("Invalid authentication")]
+    InvalidAuth,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Counter cannot go above its maximum")]
+    Overflow,
+    #[msg("Value is outside the counter's min..=max bounds")]
+    ValueOutOfRange,
+    #[msg("Counter min must not exceed max")]
+    InvalidBounds,
+    #[msg("Counter uses an older account layout, run migrate first")]
+    AccountNotMigrated,
+    #[msg("Counter updates are paused")]
+    Paused,
+    #[msg("Counter does not hold the expected value")]
+    CountMismatch,
+    #[msg("Operator is not permitted to perform this update")]
+    MissingPermission,
+    #[msg("Permissions must be a non-empty mask of increment, decrement and set")]
+    InvalidPermissions,
+    #[msg("Counter already lists the maximum number of operators")]
+    TooManyOperators,
+    #[msg("Key is not a listed operator")]
+    OperatorNotFound,
+    #[msg("Multisig needs 1 to 8 distinct signers and a threshold within their number")]
+    InvalidMultisig,
+    #[msg("Signer is not one of the multisig's signers")]
+    NotMultisigSigner,
+    #[msg("This update needs an executed multisig proposal")]
+    MultisigProposalRequired,
+    #[msg("Proposal does not have enough approvals yet")]
+    ThresholdNotMet,
+    #[msg("Scheduled set cannot be executed before its delay has passed")]
+    SetNotReady,
+    #[msg("Counter already has a scheduled set, execute or cancel it first")]
+    SetAlreadyPending,
+    #[msg("Counter was updated too recently, try again later")]
+    RateLimited,
+    #[msg("A per-window cap needs a non-zero window")]
+    InvalidRateLimit,
+    #[msg("Reset period must be between 1 second and i64::MAX seconds")]
+    InvalidResetPeriod,
+    #[msg("Counter is not public")]
+    CounterNotPublic,
+    #[msg("Increments to a public counter must pass the signer's contribution")]
+    ContributionRequired,
+    #[msg("Counter charges a fee, pass its treasury")]
+    TreasuryRequired,
+    #[msg("Not enough lamports to cover the amount")]
+    InsufficientFunds,
+    #[msg("Counter is token gated, pass a token account of its gate mint")]
+    GateTokenRequired,
+    #[msg("Token account is not of the gate mint or not owned by the signer")]
+    InvalidGateToken,
+    #[msg("Token account holds less than the counter's minimum balance")]
+    GateBalanceTooLow,
+    #[msg("Counter has no reward mint, create one via init_reward_mint")]
+    RewardMintRequired,
+    #[msg("Counter mints rewards, pass its reward mint, a token account and the token program")]
+    RewardAccountsRequired,
+    #[msg("Token account is not of the reward mint or not owned by the signer")]
+    InvalidRewardAccount,
+    #[msg("Caller programs may only increment the counter")]
+    CallerMayOnlyIncrement,
+    #[msg("Only counters in the original layout, and all of them, move to a relocated address")]
+    RelocationMismatch,
+    #[msg("Contribution belongs to another counter or contributor")]
+    InvalidContribution,
+    #[msg("Proposal needs the counter's operators, treasury or pending set, or the recipient")]
+    ProposalAccountsRequired,
+    #[msg("Proposal was created under a multisig that has since been closed")]
+    StaleProposal,
+    #[msg("Only entries of closed counters can be pruned from a leaderboard")]
+    CounterNotClosed,
 }
@@ -1,5 +1,14 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  createAccount,
+  createMint,
+  getAccount,
+  getMint,
+  mintTo,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { Keypair } from "@solana/web3.js";
 import { expect } from "chai";
 import { Counter } from "../target/types/counter";
@@ -12,21 +21,61 @@ describe("counter", () => {
   const program = anchor.workspace.counter as Program<Counter>;
   const authority = provider.wallet;
 
-  // Derive PDA using user's public key
-  const [counterPDA] = anchor.web3.PublicKey.findProgramAddressSync(
-    [authority.publicKey.toBuffer()],
+  // Each authority can own many counters, distinguished by a u64 id
+  const counterId = new anchor.BN(0);
+
+  // min, max and overflow policy matching the demo behaviour (wrap above 1000)
+  const defaultBounds: [anchor.BN, anchor.BN, { wrap: {} }] = [
+    new anchor.BN(0),
+    new anchor.BN(1000),
+    { wrap: {} },
+  ];
+
+  // Derive PDA using user's public key and the counter id
+  const deriveCounterPDA = (owner: anchor.web3.PublicKey, id: anchor.BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const counterPDA = deriveCounterPDA(authority.publicKey, counterId);
+
+  // Singleton program config holding the global pause switch
+  const [configPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
     program.programId
   );
 
   console.log("Program ID: ", program.programId.toString());
   console.log("Counter PDA: ", counterPDA.toString());
 
+  // Updates read the program config, so make sure it exists and isn't paused
+  before(async () => {
+    const config = await program.account.programConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initConfig(authority.publicKey)
+        .accounts({
+          payer: authority.publicKey,
+        })
+        .rpc();
+    } else if (config.paused) {
+      await program.methods
+        .setPaused(false)
+        .accounts({
+          admin: authority.publicKey,
+        })
+        .rpc();
+    }
+  });
+
   describe("initialize", () => {
     it("initializes a counter with count 0", async () => {
       const tx = await program.methods
-        .initialize()
+        .initialize(counterId, ...defaultBounds, null)
         .accounts({
           authority: authority.publicKey,
+          multisig: null,
         })
         .rpc();
 
@@ -34,10 +83,45 @@ describe("counter", () => {
 
       const counterAccount = await program.account.counter.fetch(counterPDA);
 
+      expect(counterAccount.version).to.equal(10);
       expect(counterAccount.count.toNumber()).to.equal(0);
       expect(counterAccount.authority.toBase58()).to.equal(
         authority.publicKey.toBase58()
       );
+      expect(counterAccount.counterId.toNumber()).to.equal(0);
+    });
+
+    it("initializes an independent second counter for the same authority", async () => {
+      const secondId = new anchor.BN(1);
+      const secondPDA = deriveCounterPDA(authority.publicKey, secondId);
+
+      await program.methods
+        .initialize(secondId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await program.methods
+        .set(secondId, new anchor.BN(7))
+        .accountsPartial({
+          counter: secondPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+      const second = await program.account.counter.fetch(secondPDA);
+      const first = await program.account.counter.fetch(counterPDA);
+      expect(second.count.toNumber()).to.equal(7);
+      expect(second.counterId.toNumber()).to.equal(1);
+      expect(first.count.toNumber()).to.equal(0);
     });
   });
 
@@ -47,9 +131,16 @@ describe("counter", () => {
       const initialCount = counterBefore.count.toNumber();
 
       await program.methods
-        .increment()
-        .accounts({
-          authority: authority.publicKey,
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
         })
         .rpc();
 
@@ -64,9 +155,16 @@ describe("counter", () => {
       // Increment 3 times
       for (let i = 0; i < 3; i++) {
         await program.methods
-          .increment()
-          .accounts({
-            authority: authority.publicKey,
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
           })
           .rpc();
       }
@@ -84,9 +182,16 @@ describe("counter", () => {
       // Ensure we have something to decrement
       if (initialCount === 0) {
         await program.methods
-          .increment()
-          .accounts({
-            authority: authority.publicKey,
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
           })
           .rpc();
       }
@@ -95,9 +200,16 @@ describe("counter", () => {
 
       // Then decrement
       await program.methods
-        .decrement()
-        .accounts({
-          authority: authority.publicKey,
+        .decrement(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
         })
         .rpc();
 
@@ -109,9 +221,16 @@ describe("counter", () => {
   describe("set", () => {
     it("sets the counter to a specific value", async () => {
       await program.methods
-        .set(new anchor.BN(42))
-        .accounts({
-          authority: authority.publicKey,
+        .set(counterId, new anchor.BN(42))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
         })
         .rpc();
 
@@ -120,30 +239,2854 @@ describe("counter", () => {
     });
   });
 
-  describe("authority validation", () => {
-    it("fails when non-authority tries to increment", async () => {
-      // Create a fake authority
-      const fakeAuthority = Keypair.generate();
+  describe("events", () => {
+    it("emits CounterChanged with the old and new count", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+      const tx = await program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc({ commitment: "confirmed" });
 
-      // Derive PDA for fake authority
-      const [fakePDA] = anchor.web3.PublicKey.findProgramAddressSync(
-        [fakeAuthority.publicKey.toBuffer()],
-        program.programId
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(
+        program.programId,
+        new anchor.BorshCoder(program.idl)
+      );
+      const events = [...parser.parseLogs(txDetails.meta.logMessages)];
+      const changed = events.find((e) => e.name === "counterChanged");
+
+      expect(changed).to.not.be.undefined;
+      expect(changed.data.old.toNumber()).to.equal(before.count.toNumber());
+      expect(changed.data.new.toNumber()).to.equal(
+        before.count.toNumber() + 1
+      );
+      expect(changed.data.op).to.deep.equal({ increment: {} });
+      expect(changed.data.signer.toBase58()).to.equal(
+        authority.publicKey.toBase58()
       );
+    });
+  });
+
+  describe("compare_and_set", () => {
+    it("sets the counter when it holds the expected value", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      await program.methods
+        .compareAndSet(counterId, before.count, new anchor.BN(7))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.count.toNumber()).to.equal(7);
+      expect(after.sequence.toNumber()).to.equal(before.sequence.toNumber() + 1);
+    });
+
+    it("fails with CountMismatch when the counter has moved on", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      try {
+        await program.methods
+          .compareAndSet(counterId, before.count.addn(1), new anchor.BN(0))
+          .accountsPartial({
+            counter: counterPDA,
+            signer: authority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CountMismatch");
+      }
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.count.toNumber()).to.equal(before.count.toNumber());
+      expect(after.sequence.toNumber()).to.equal(before.sequence.toNumber());
+    });
+  });
+
+  describe("increment_by / decrement_by", () => {
+    it("moves the counter by an arbitrary amount", async () => {
+      await program.methods
+        .set(counterId, new anchor.BN(100))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+      await program.methods
+        .incrementBy(counterId, new anchor.BN(25))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(125);
+
+      await program.methods
+        .decrementBy(counterId, new anchor.BN(20))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(105);
+    });
+
+    it("wraps large amounts around the bounds", async () => {
+      // 105 + 1000 on a 0..=1000 wrapping counter lands on 104
+      await program.methods
+        .incrementBy(counterId, new anchor.BN(1000))
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(counterPDA)).count.toNumber()
+      ).to.equal(104);
+    });
+  });
+
+  describe("bounds", () => {
+    // Creates a counter limited to 10..=12 with the given overflow policy
+    const initBounded = async (id: anchor.BN, overflowPolicy: any) => {
+      const pda = deriveCounterPDA(authority.publicKey, id);
+      await program.methods
+        .initialize(id, new anchor.BN(10), new anchor.BN(12), overflowPolicy, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      return pda;
+    };
+
+    const step = (
+      method: "increment" | "decrement",
+      id: anchor.BN,
+      pda: anchor.web3.PublicKey
+    ) =>
+      program.methods[method](id)
+        .accountsPartial({
+          counter: pda,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const count = (account: { count: anchor.BN }) => account.count.toNumber();
+
+    it("starts at min and wraps at the bounds", async () => {
+      const id = new anchor.BN(10);
+      const pda = await initBounded(id, { wrap: {} });
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      for (let i = 0; i < 3; i++) {
+        await step("increment", id, pda);
+      }
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      await step("decrement", id, pda);
+      expect(count(await program.account.counter.fetch(pda))).to.equal(12);
+    });
+
+    it("saturates at the bounds", async () => {
+      const id = new anchor.BN(11);
+      const pda = await initBounded(id, { saturate: {} });
+
+      await step("decrement", id, pda);
+      expect(count(await program.account.counter.fetch(pda))).to.equal(10);
+
+      for (let i = 0; i < 3; i++) {
+        await step("increment", id, pda);
+      }
+      expect(count(await program.account.counter.fetch(pda))).to.equal(12);
+    });
+
+    it("fails at the bounds with the error policy", async () => {
+      const id = new anchor.BN(12);
+      const pda = await initBounded(id, { error: {} });
+
+      try {
+        await step("decrement", id, pda);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterUnderflow");
+      }
+
+      await step("increment", id, pda);
+      await step("increment", id, pda);
+      try {
+        await step("increment", id, pda);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Overflow");
+      }
+    });
+
+    it("rejects set outside the bounds", async () => {
+      const id = new anchor.BN(13);
+      const pda = await initBounded(id, { wrap: {} });
+
+      try {
+        await program.methods
+          .set(id, new anchor.BN(13))
+          .accountsPartial({
+            counter: pda,
+            signer: authority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ValueOutOfRange");
+      }
+    });
 
-      // Try to increment with fake authority - should fail because PDA doesn't exist
+    it("rejects min greater than max", async () => {
       try {
         await program.methods
-          .increment()
+          .initialize(new anchor.BN(14), new anchor.BN(5), new anchor.BN(4), { wrap: {} }, null)
           .accounts({
-            authority: fakeAuthority.publicKey,
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidBounds");
+      }
+    });
+  });
+
+  describe("close", () => {
+    it("closes a counter and returns the rent to the recipient", async () => {
+      const closeId = new anchor.BN(2);
+      const closePDA = deriveCounterPDA(authority.publicKey, closeId);
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      const rent = await provider.connection.getBalance(closePDA);
+      const recipient = Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .rpc();
+
+      expect(await program.account.counter.fetchNullable(closePDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("refunds the contributions closed along with the counter", async () => {
+      const closeId = new anchor.BN(31);
+      const closePDA = deriveCounterPDA(authority.publicKey, closeId);
+      const contributor = Keypair.generate();
+      const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("contribution"),
+          closePDA.toBuffer(),
+          contributor.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setPublic(closeId, true)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+      await program.methods
+        .initContribution(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          contributor: contributor.publicKey,
+        })
+        .signers([contributor])
+        .rpc();
+
+      const contributionRent =
+        await provider.connection.getBalance(contributionPDA);
+      const before = await provider.connection.getBalance(contributor.publicKey);
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: contributionPDA, isSigner: false, isWritable: true },
+          { pubkey: contributor.publicKey, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(contributionPDA)).to.be.null;
+      expect(await provider.connection.getBalance(contributor.publicKey)).to.equal(
+        before + contributionRent
+      );
+    });
+
+    it("drops a pending set along with the counter", async () => {
+      const closeId = new anchor.BN(34);
+      const closePDA = deriveCounterPDA(authority.publicKey, closeId);
+      const [pendingSetPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pending_set"), closePDA.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .scheduleSet(closeId, new anchor.BN(5), new anchor.BN(60 * 60))
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const rent =
+        (await provider.connection.getBalance(closePDA)) +
+        (await provider.connection.getBalance(pendingSetPDA));
+      const recipient = Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(pendingSetPDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("closes the treasury along with the counter", async () => {
+      const closeId = new anchor.BN(32);
+      const closePDA = deriveCounterPDA(authority.publicKey, closeId);
+      const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), closePDA.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(closeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .initTreasury(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      const rent =
+        (await provider.connection.getBalance(closePDA)) +
+        (await provider.connection.getBalance(treasuryPDA));
+      const recipient = Keypair.generate().publicKey;
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(treasuryPDA)).to.be.null;
+      expect(await provider.connection.getBalance(recipient)).to.equal(rent);
+    });
+
+    it("does not hand the operators over to a counter created again", async () => {
+      const closeId = new anchor.BN(35);
+      const closePDA = deriveCounterPDA(authority.publicKey, closeId);
+      const [operatorsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("operators"), closePDA.toBuffer()],
+        program.programId
+      );
+      const operator = Keypair.generate();
+
+      const initialize = async () => {
+        await program.methods
+          .initialize(closeId, ...defaultBounds, null)
+          .accounts({
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        await program.methods
+          .initOperators(closeId)
+          .accountsPartial({
+            counter: closePDA,
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+      };
+
+      await initialize();
+      await program.methods
+        .addOperator(closeId, operator.publicKey, 1)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .close(closeId)
+        .accountsPartial({
+          counter: closePDA,
+          authority: authority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(operatorsPDA)).to.be.null;
+
+      await initialize();
+
+      try {
+        await program.methods
+          .increment(closeId)
+          .accountsPartial({
+            counter: closePDA,
+            signer: operator.publicKey,
+            history: null,
+            operators: operatorsPDA,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
+          })
+          .signers([operator])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("history", () => {
+    it("records every change in the ring buffer", async () => {
+      const historyId = new anchor.BN(4);
+      const historyCounter = deriveCounterPDA(authority.publicKey, historyId);
+      const [historyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("history"), historyCounter.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initialize(historyId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await program.methods
+        .initHistory(historyId)
+        .accountsPartial({
+          counter: historyCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await program.methods
+        .increment(historyId)
+        .accountsPartial({
+          counter: historyCounter,
+          signer: authority.publicKey,
+          history: historyPDA,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+      await program.methods
+        .set(historyId, new anchor.BN(9))
+        .accountsPartial({
+          counter: historyCounter,
+          signer: authority.publicKey,
+          history: historyPDA,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.counter.toBase58()).to.equal(historyCounter.toBase58());
+      expect(history.total.toNumber()).to.equal(2);
+
+      const [first, second] = history.entries;
+      expect(first.old.toNumber()).to.equal(0);
+      expect(first.new.toNumber()).to.equal(1);
+      expect(first.op).to.equal(0);
+      expect(first.signer.toBase58()).to.equal(authority.publicKey.toBase58());
+      expect(first.slot.toNumber()).to.be.greaterThan(0);
+      expect(second.old.toNumber()).to.equal(1);
+      expect(second.new.toNumber()).to.equal(9);
+      expect(second.op).to.equal(2);
+    });
+
+    it("overwrites the oldest entries once full", async () => {
+      const historyId = new anchor.BN(4);
+      const historyCounter = deriveCounterPDA(authority.publicKey, historyId);
+      const [historyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("history"), historyCounter.toBuffer()],
+        program.programId
+      );
+      const capacity = (
+        await program.account.counterHistory.fetch(historyPDA)
+      ).entries.length;
+
+      for (let i = 0; i < capacity - 1; i++) {
+        await program.methods
+          .increment(historyId)
+          .accountsPartial({
+            counter: historyCounter,
+            signer: authority.publicKey,
+            history: historyPDA,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
+          })
+          .rpc();
+      }
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      const { count } = await program.account.counter.fetch(historyCounter);
+      expect(history.total.toNumber()).to.equal(capacity + 1);
+      // Entry 0 (the first increment) was replaced by the newest one
+      expect(history.entries[0].old.toNumber()).to.equal(count.toNumber() - 1);
+      expect(history.entries[0].new.toNumber()).to.equal(count.toNumber());
+    });
+  });
+
+  describe("authority validation", () => {
+    it("fails when non-authority tries to increment", async () => {
+      // Create a fake authority
+      const fakeAuthority = Keypair.generate();
+
+      // Try to increment someone else's counter - should fail the authority check
+      try {
+        await program.methods
+          .increment(counterId)
+          .accountsPartial({
+            counter: counterPDA,
+            signer: fakeAuthority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
+          })
+          .signers([fakeAuthority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("migrate", () => {
+    it("leaves a counter already in the current layout unchanged", async () => {
+      const before = await program.account.counter.fetch(counterPDA);
+
+      await program.methods
+        .migrate(counterId)
+        .accounts({
+          counter: counterPDA,
+          relocated: null,
+          payer: authority.publicKey,
+        })
+        .rpc();
+
+      const after = await program.account.counter.fetch(counterPDA);
+      expect(after.version).to.equal(10);
+      expect(after.count.toNumber()).to.equal(before.count.toNumber());
+    });
+
+    it("rejects a counter id that does not match the address", async () => {
+      try {
+        await program.methods
+          .migrate(new anchor.BN(999))
+          .accounts({
+            counter: counterPDA,
+            relocated: null,
+            payer: authority.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    describe("from the original layout", () => {
+      // Preloaded from tests/fixtures by the local validator (see Anchor.toml): a
+      // 48-byte {count: 42, authority} counter at the PDA of its authority alone
+      const baselineAuthority = Keypair.fromSecretKey(
+        Uint8Array.from(require("./fixtures/baseline-authority.json"))
+      );
+      const [baselinePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [baselineAuthority.publicKey.toBuffer()],
+        program.programId
+      );
+      const relocatedId = new anchor.BN(0);
+      const [relocatedPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          baselineAuthority.publicKey.toBuffer(),
+          relocatedId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      before(async function () {
+        const info = await provider.connection.getAccountInfo(baselinePDA);
+        if (!info) {
+          // Only the local validator loads the fixture
+          this.skip();
+        }
+        expect(info.data.length).to.equal(48);
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: authority.publicKey,
+              toPubkey: baselineAuthority.publicKey,
+              lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+            })
+          )
+        );
+      });
+
+      it("requires the relocated address", async () => {
+        try {
+          await program.methods
+            .migrate(relocatedId)
+            .accounts({
+              counter: baselinePDA,
+              relocated: null,
+              payer: baselineAuthority.publicKey,
+            })
+            .signers([baselineAuthority])
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("RelocationMismatch");
+        }
+      });
+
+      it("only lets its authority move it", async () => {
+        const otherId = new anchor.BN(77);
+        const [otherPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+          [authority.publicKey.toBuffer(), otherId.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        try {
+          await program.methods
+            .migrate(otherId)
+            .accountsPartial({
+              counter: baselinePDA,
+              relocated: otherPDA,
+              payer: authority.publicKey,
+            })
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidAuth");
+        }
+      });
+
+      it("moves it to the counter id's address in the current layout", async () => {
+        await program.methods
+          .migrate(relocatedId)
+          .accountsPartial({
+            counter: baselinePDA,
+            relocated: relocatedPDA,
+            payer: baselineAuthority.publicKey,
+          })
+          .signers([baselineAuthority])
+          .rpc();
+
+        const migrated = await program.account.counter.fetch(relocatedPDA);
+        expect(migrated.version).to.equal(10);
+        expect(migrated.count.toNumber()).to.equal(42);
+        expect(migrated.authority.toBase58()).to.equal(
+          baselineAuthority.publicKey.toBase58()
+        );
+        expect(migrated.seedAuthority.toBase58()).to.equal(
+          baselineAuthority.publicKey.toBase58()
+        );
+        expect(migrated.counterId.toNumber()).to.equal(0);
+        expect(migrated.min.toNumber()).to.equal(0);
+        expect(migrated.max.toString()).to.equal("18446744073709551615");
+        expect(migrated.overflowPolicy).to.deep.equal({ error: {} });
+        expect(await provider.connection.getAccountInfo(baselinePDA)).to.be.null;
+      });
+    });
+  });
+
+  describe("timelocked set", () => {
+    const timelockId = new anchor.BN(15);
+    const timelockCounter = deriveCounterPDA(authority.publicKey, timelockId);
+    const [pendingSetPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_set"), timelockCounter.toBuffer()],
+      program.programId
+    );
+
+    const scheduleSet = (value: number, delay: number) =>
+      program.methods
+        .scheduleSet(timelockId, new anchor.BN(value), new anchor.BN(delay))
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const [historyPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("history"), timelockCounter.toBuffer()],
+      program.programId
+    );
+
+    const executeSet = (
+      executor: Keypair,
+      history: anchor.web3.PublicKey | null = null
+    ) =>
+      program.methods
+        .executeSet(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+          executor: executor.publicKey,
+          history,
+          treasury: null,
+        })
+        .signers([executor])
+        .rpc();
+
+    const setRateLimit = (minSlots: number) =>
+      program.methods
+        .setRateLimit(timelockId, {
+          minSlots: new anchor.BN(minSlots),
+          minSeconds: new anchor.BN(0),
+          maxPerWindow: new anchor.BN(0),
+          windowSeconds: new anchor.BN(0),
+        })
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(timelockId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("refuses to execute before the delay has passed", async () => {
+      await scheduleSet(5, 60 * 60);
+
+      const pendingSet = await program.account.pendingSet.fetch(pendingSetPDA);
+      expect(pendingSet.value.toNumber()).to.equal(5);
+
+      try {
+        await executeSet(Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SetNotReady");
+      }
+    });
+
+    it("allows a single pending set per counter", async () => {
+      try {
+        await scheduleSet(6, 0);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("SetAlreadyPending");
+      }
+    });
+
+    it("lets the authority cancel a pending set", async () => {
+      await program.methods
+        .cancelSet(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("lets anyone execute once the delay has passed", async () => {
+      await scheduleSet(7, 0);
+      await executeSet(Keypair.generate());
+
+      const counterAccount = await program.account.counter.fetch(
+        timelockCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(7);
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("holds an executed set to the rate limit", async () => {
+      await setRateLimit(1_000_000);
+      await scheduleSet(8, 0);
+      try {
+        await executeSet(Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+      await setRateLimit(0);
+    });
+
+    it("records an executed set in the history", async () => {
+      await program.methods
+        .initHistory(timelockId)
+        .accountsPartial({
+          counter: timelockCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      const executor = Keypair.generate();
+      await executeSet(executor, historyPDA);
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.total.toNumber()).to.equal(1);
+      const [entry] = history.entries;
+      expect(entry.old.toNumber()).to.equal(7);
+      expect(entry.new.toNumber()).to.equal(8);
+      expect(entry.op).to.equal(2);
+      expect(entry.signer.toBase58()).to.equal(executor.publicKey.toBase58());
+    });
+  });
+
+  describe("rate limit", () => {
+    const limitedId = new anchor.BN(16);
+    const limitedCounter = deriveCounterPDA(authority.publicKey, limitedId);
+
+    const increment = () =>
+      program.methods.increment(limitedId).accountsPartial({
+        counter: limitedCounter,
+        signer: authority.publicKey,
+        history: null,
+        operators: null,
+        sessionToken: null,
+        multisig: null,
+        contribution: null,
+        treasury: null,
+      });
+
+    // Two increments in a single transaction, so both land in the same slot
+    const incrementTwice = async () =>
+      increment()
+        .preInstructions([await increment().instruction()])
+        .rpc();
+
+    const setRateLimit = (
+      minSlots: number,
+      minSeconds: number | string,
+      maxPerWindow: number,
+      windowSeconds: number | string
+    ) =>
+      program.methods
+        .setRateLimit(limitedId, {
+          minSlots: new anchor.BN(minSlots),
+          minSeconds: new anchor.BN(minSeconds),
+          maxPerWindow: new anchor.BN(maxPerWindow),
+          windowSeconds: new anchor.BN(windowSeconds),
+        })
+        .accountsPartial({
+          counter: limitedCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(limitedId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("rejects a per-window cap without a window", async () => {
+      try {
+        await setRateLimit(0, 0, 2, 0);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidRateLimit");
+      }
+    });
+
+    it("rejects durations that don't fit a timestamp", async () => {
+      const pastI64 = "9223372036854775808";
+      for (const [minSeconds, windowSeconds] of [
+        [pastI64, 0],
+        [0, pastI64],
+      ]) {
+        try {
+          await setRateLimit(0, minSeconds, 0, windowSeconds);
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidRateLimit");
+        }
+      }
+    });
+
+    it("rejects a second update in the same slot", async () => {
+      await setRateLimit(1, 0, 0, 0);
+
+      try {
+        await incrementTwice();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+
+      await increment().rpc();
+      const counterAccount = await program.account.counter.fetch(
+        limitedCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(1);
+      expect(counterAccount.lastUpdateSlot.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("caps updates per window", async () => {
+      await setRateLimit(0, 0, 2, 60 * 60);
+
+      await incrementTwice();
+      try {
+        await increment().rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+
+      const counterAccount = await program.account.counter.fetch(
+        limitedCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(3);
+      expect(counterAccount.windowCount.toNumber()).to.equal(2);
+    });
+
+    it("lifts the limit when reset to zero", async () => {
+      await setRateLimit(0, 0, 0, 0);
+      await incrementTwice();
+      expect(
+        (await program.account.counter.fetch(limitedCounter)).count.toNumber()
+      ).to.equal(5);
+    });
+  });
+
+  describe("reset period", () => {
+    const periodicId = new anchor.BN(17);
+    const periodicCounter = deriveCounterPDA(authority.publicKey, periodicId);
+
+    const increment = () =>
+      program.methods
+        .increment(periodicId)
+        .accountsPartial({
+          counter: periodicCounter,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+    const setResetPeriod = (resetPeriod: any) =>
+      program.methods
+        .setResetPeriod(periodicId, resetPeriod)
+        .accountsPartial({
+          counter: periodicCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(periodicId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("rejects an empty period", async () => {
+      try {
+        await setResetPeriod({ seconds: { seconds: new anchor.BN(0) } });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidResetPeriod");
+      }
+    });
+
+    it("starts daily periods at midnight UTC", async () => {
+      await setResetPeriod({ dailyUtc: {} });
+
+      const counterAccount = await program.account.counter.fetch(
+        periodicCounter
+      );
+      expect(counterAccount.resetPeriod).to.deep.equal({ dailyUtc: {} });
+      expect(counterAccount.periodStart.toNumber() % 86400).to.equal(0);
+    });
+
+    it("resets on the first update of a new period", async () => {
+      await setResetPeriod({ seconds: { seconds: new anchor.BN(2) } });
+      await increment();
+      await increment();
+      await increment();
+      const current = await program.account.counter.fetch(periodicCounter);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await increment();
+
+      const next = await program.account.counter.fetch(periodicCounter);
+      expect(next.count.toNumber()).to.equal(1);
+      expect(next.lastPeriodCount.toNumber()).to.equal(
+        current.count.toNumber()
+      );
+      expect(next.periodStart.toNumber()).to.be.greaterThan(
+        current.periodStart.toNumber()
+      );
+    });
+  });
+
+  describe("public counter", () => {
+    const publicId = new anchor.BN(18);
+    const publicCounter = deriveCounterPDA(authority.publicKey, publicId);
+    const contributor = Keypair.generate();
+    const [contributionPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("contribution"),
+        publicCounter.toBuffer(),
+        contributor.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    const incrementBy = (
+      amount: number,
+      contribution: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .incrementBy(publicId, new anchor.BN(amount))
+        .accountsPartial({
+          counter: publicCounter,
+          signer: contributor.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution,
+          treasury: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+    const setPublic = (isPublic: boolean) =>
+      program.methods
+        .setPublic(publicId, isPublic)
+        .accountsPartial({
+          counter: publicCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const initContribution = () =>
+      program.methods
+        .initContribution(publicId)
+        .accountsPartial({
+          counter: publicCounter,
+          contributor: contributor.publicKey,
+        })
+        .signers([contributor])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(publicId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      // The contributor pays the rent of its own contribution
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: contributor.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+    });
+
+    it("keeps a private counter to its authority", async () => {
+      try {
+        await incrementBy(1, null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+
+      try {
+        await initContribution();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotPublic");
+      }
+    });
+
+    it("requires a contribution from other signers", async () => {
+      await setPublic(true);
+
+      try {
+        await incrementBy(1, null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ContributionRequired");
+      }
+    });
+
+    it("tracks each contributor's share", async () => {
+      await initContribution();
+      await incrementBy(3, contributionPDA);
+      await incrementBy(2, contributionPDA);
+
+      const contribution = await program.account.contribution.fetch(
+        contributionPDA
+      );
+      expect(contribution.amount.toNumber()).to.equal(5);
+      expect(contribution.updates.toNumber()).to.equal(2);
+      expect(
+        (await program.account.counter.fetch(publicCounter)).count.toNumber()
+      ).to.equal(5);
+    });
+
+    it("still keeps decrements to the authority", async () => {
+      try {
+        await program.methods
+          .decrement(publicId)
+          .accountsPartial({
+            counter: publicCounter,
+            signer: contributor.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: contributionPDA,
+            treasury: null,
+          })
+          .signers([contributor])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("leaderboard", () => {
+    const ids = [20, 21, 22].map((id) => new anchor.BN(id));
+    const counterOf = (id: anchor.BN) =>
+      deriveCounterPDA(authority.publicKey, id);
+    const [leaderboardPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const setCount = (id: anchor.BN, value: number) =>
+      program.methods
+        .set(id, new anchor.BN(value))
+        .accountsPartial({
+          counter: counterOf(id),
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+    const submitScore = (id: anchor.BN, counter = counterOf(id)) =>
+      program.methods
+        .submitScore(id)
+        .accountsPartial({
+          leaderboard: leaderboardPDA,
+          counter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const ranking = async () =>
+      (await program.account.leaderboard.fetch(leaderboardPDA)).entries.map(
+        (entry) => entry.count.toNumber()
+      );
+
+    before(async () => {
+      await program.methods
+        .initLeaderboard()
+        .accounts({
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      for (const [index, id] of ids.entries()) {
+        await program.methods
+          .initialize(id, ...defaultBounds, null)
+          .accounts({
+            authority: authority.publicKey,
+            multisig: null,
+          })
+          .rpc();
+        await setCount(id, [5, 9, 7][index]);
+      }
+    });
+
+    it("ranks submitted counters by count", async () => {
+      for (const id of ids) {
+        await submitScore(id);
+      }
+      expect(await ranking()).to.deep.equal([9, 7, 5]);
+
+      const leaderboard = await program.account.leaderboard.fetch(
+        leaderboardPDA
+      );
+      expect(leaderboard.entries[0].counter.toBase58()).to.equal(
+        counterOf(ids[1]).toBase58()
+      );
+      expect(leaderboard.entries[0].authority.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+    });
+
+    it("moves a resubmitted counter instead of adding it twice", async () => {
+      await setCount(ids[0], 12);
+      await submitScore(ids[0]);
+      expect(await ranking()).to.deep.equal([12, 9, 7]);
+    });
+
+    it("only takes counters owned by the program", async () => {
+      try {
+        await submitScore(ids[0], authority.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("AccountOwnedByWrongProgram");
+      }
+    });
+
+    it("only takes submissions signed by the leaderboard's authority", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods
+          .submitScore(ids[0])
+          .accountsPartial({
+            leaderboard: leaderboardPDA,
+            counter: counterOf(ids[0]),
+            authority: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+
+    it("prunes closed counters but keeps the ones that still exist", async () => {
+      const closedId = new anchor.BN(37);
+      await program.methods
+        .initialize(closedId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await setCount(closedId, 20);
+      await submitScore(closedId);
+      expect(await ranking()).to.deep.equal([20, 12, 9, 7]);
+
+      const prune = (counter: anchor.web3.PublicKey) =>
+        program.methods
+          .pruneLeaderboard()
+          .accountsPartial({ leaderboard: leaderboardPDA })
+          .remainingAccounts([
+            { pubkey: counter, isSigner: false, isWritable: false },
+          ])
+          .rpc();
+
+      try {
+        await prune(counterOf(closedId));
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotClosed");
+      }
+
+      await program.methods
+        .close(closedId)
+        .accountsPartial({
+          counter: counterOf(closedId),
+          authority: authority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .rpc();
+      await prune(counterOf(closedId));
+      expect(await ranking()).to.deep.equal([12, 9, 7]);
+    });
+  });
+
+  describe("fees", () => {
+    const feeId = new anchor.BN(24);
+    const feeCounter = deriveCounterPDA(authority.publicKey, feeId);
+    const [treasuryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), feeCounter.toBuffer()],
+      program.programId
+    );
+    const fee = 100_000;
+
+    const update = (
+      method: "increment" | "decrement",
+      treasury: anchor.web3.PublicKey | null = treasuryPDA
+    ) =>
+      program.methods[method](feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury,
+        })
+        .rpc();
+
+    const withdraw = (amount: number, signer: Keypair, recipient = signer.publicKey) =>
+      program.methods
+        .withdraw(feeId, new anchor.BN(amount))
+        .accountsPartial({
+          counter: feeCounter,
+          signer: signer.publicKey,
+          recipient,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(feeId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setFee(feeId, new anchor.BN(fee))
+        .accountsPartial({
+          counter: feeCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+    });
+
+    it("requires the treasury while a fee is set", async () => {
+      try {
+        await update("increment", null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("TreasuryRequired");
+      }
+    });
+
+    it("charges increments but not decrements", async () => {
+      await program.methods
+        .initTreasury(feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      const start = await provider.connection.getBalance(treasuryPDA);
+
+      await update("increment");
+      await update("increment");
+      await update("decrement");
+
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(treasury.collected.toNumber()).to.equal(2 * fee);
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(
+        start + 2 * fee
+      );
+    });
+
+    it("charges the signer when someone else pays for the transaction", async () => {
+      const feePayer = Keypair.generate();
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: feePayer.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+      const start = await provider.connection.getBalance(treasuryPDA);
+
+      const tx = await program.methods
+        .increment(feeId)
+        .accountsPartial({
+          counter: feeCounter,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: treasuryPDA,
+        })
+        .transaction();
+      tx.feePayer = feePayer.publicKey;
+      await provider.sendAndConfirm(tx, [feePayer]);
+
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(treasury.collected.toNumber()).to.equal(3 * fee);
+      expect(await provider.connection.getBalance(treasuryPDA)).to.equal(
+        start + fee
+      );
+    });
+
+    it("lets only the authority or admin withdraw", async () => {
+      const stranger = Keypair.generate();
+      try {
+        await withdraw(fee, stranger);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+
+    it("withdraws the fees but keeps the treasury rent-exempt", async () => {
+      const recipient = Keypair.generate().publicKey;
+      const payer = provider.wallet.payer;
+      try {
+        await withdraw(4 * fee, payer, recipient);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InsufficientFunds");
+      }
+
+      await withdraw(3 * fee, payer, recipient);
+      expect(await provider.connection.getBalance(recipient)).to.equal(3 * fee);
+    });
+  });
+
+  describe("token gate", () => {
+    const gateId = new anchor.BN(26);
+    const gateCounter = deriveCounterPDA(authority.publicKey, gateId);
+    const payer = provider.wallet.payer;
+    let mint: anchor.web3.PublicKey;
+    let tokenAccount: anchor.web3.PublicKey;
+
+    const increment = (gateTokenAccount: anchor.web3.PublicKey | null) =>
+      program.methods
+        .increment(gateId)
+        .accountsPartial({
+          counter: gateCounter,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+          gateTokenAccount,
+        })
+        .rpc();
+
+    const setGate = (gateMint: anchor.web3.PublicKey | null, minBalance = 0) =>
+      program.methods
+        .setTokenGate(gateId, gateMint, new anchor.BN(minBalance))
+        .accountsPartial({
+          counter: gateCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    const createHolding = async (
+      tokenProgram: anchor.web3.PublicKey,
+      amount: number,
+      owner = authority.publicKey
+    ) => {
+      const mint = await createMint(
+        provider.connection,
+        payer,
+        authority.publicKey,
+        null,
+        0,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      const account = await createAccount(
+        provider.connection,
+        payer,
+        mint,
+        owner,
+        undefined,
+        undefined,
+        tokenProgram
+      );
+      if (amount > 0) {
+        await mintTo(
+          provider.connection,
+          payer,
+          mint,
+          account,
+          payer,
+          amount,
+          [],
+          undefined,
+          tokenProgram
+        );
+      }
+      return [mint, account];
+    };
+
+    before(async () => {
+      await program.methods
+        .initialize(gateId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      [mint, tokenAccount] = await createHolding(TOKEN_PROGRAM_ID, 4);
+      await setGate(mint, 5);
+    });
+
+    it("requires a token account while gated", async () => {
+      try {
+        await increment(null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("GateTokenRequired");
+      }
+    });
+
+    it("rejects accounts of another mint or owner", async () => {
+      const [, otherMint] = await createHolding(TOKEN_PROGRAM_ID, 5);
+      const [, otherOwner] = await createHolding(
+        TOKEN_PROGRAM_ID,
+        5,
+        Keypair.generate().publicKey
+      );
+      for (const account of [otherMint, otherOwner]) {
+        try {
+          await increment(account);
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidGateToken");
+        }
+      }
+    });
+
+    it("enforces the minimum balance", async () => {
+      try {
+        await increment(tokenAccount);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("GateBalanceTooLow");
+      }
+
+      await mintTo(provider.connection, payer, mint, tokenAccount, payer, 1);
+      await increment(tokenAccount);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.count.toNumber()).to.equal(1);
+    });
+
+    it("accepts Token-2022 accounts", async () => {
+      const [mint2022, account2022] = await createHolding(
+        TOKEN_2022_PROGRAM_ID,
+        1
+      );
+      await setGate(mint2022);
+      await increment(account2022);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.gateMint.toBase58()).to.equal(mint2022.toBase58());
+      expect(counter.count.toNumber()).to.equal(2);
+    });
+
+    it("lifts the gate", async () => {
+      await setGate(null);
+      await increment(null);
+      const counter = await program.account.counter.fetch(gateCounter);
+      expect(counter.gateMint.toBase58()).to.equal(
+        anchor.web3.PublicKey.default.toBase58()
+      );
+      expect(counter.count.toNumber()).to.equal(3);
+    });
+  });
+
+  describe("rewards", () => {
+    const rewardId = new anchor.BN(28);
+    const rewardCounter = deriveCounterPDA(authority.publicKey, rewardId);
+    const [rewardMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_mint"), rewardCounter.toBuffer()],
+      program.programId
+    );
+    let rewardAccount: anchor.web3.PublicKey;
+
+    const update = (
+      method: "increment" | "decrement",
+      rewardTokenAccount: anchor.web3.PublicKey | null
+    ) =>
+      program.methods[method](rewardId)
+        .accountsPartial({
+          counter: rewardCounter,
+          signer: authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+          rewardMint: rewardTokenAccount ? rewardMint : null,
+          rewardTokenAccount,
+        })
+        .rpc();
+
+    const rewardBalance = async () =>
+      Number(
+        (
+          await getAccount(
+            provider.connection,
+            rewardAccount,
+            undefined,
+            TOKEN_2022_PROGRAM_ID
+          )
+        ).amount
+      );
+
+    before(async () => {
+      await program.methods
+        .initialize(rewardId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("requires a reward mint before setting a reward", async () => {
+      try {
+        await program.methods
+          .setReward(rewardId, new anchor.BN(5))
+          .accountsPartial({
+            counter: rewardCounter,
+            authority: authority.publicKey,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RewardMintRequired");
+      }
+    });
+
+    it("creates a Token-2022 mint owned by a PDA", async () => {
+      await program.methods
+        .initRewardMint(rewardId, 0)
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+      await program.methods
+        .setReward(rewardId, new anchor.BN(5))
+        .accountsPartial({
+          counter: rewardCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const mint = await getMint(
+        provider.connection,
+        rewardMint,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(mint.mintAuthority!.toBase58()).to.equal(rewardMint.toBase58());
+      const counter = await program.account.counter.fetch(rewardCounter);
+      expect(counter.rewardMint.toBase58()).to.equal(rewardMint.toBase58());
+      expect(counter.rewardAmount.toNumber()).to.equal(5);
+
+      rewardAccount = await createAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        authority.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    });
+
+    it("requires the reward accounts on increment", async () => {
+      try {
+        await update("increment", null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RewardAccountsRequired");
+      }
+    });
+
+    it("mints the reward on increments only", async () => {
+      await update("increment", rewardAccount);
+      await update("increment", rewardAccount);
+      await update("decrement", null);
+
+      expect(await rewardBalance()).to.equal(10);
+      const counter = await program.account.counter.fetch(rewardCounter);
+      expect(counter.count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("pause", () => {
+    const increment = () =>
+      program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          config: configPDA,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+
+    it("rejects updates to a frozen counter", async () => {
+      await program.methods
+        .setFrozen(counterId, true)
+        .accountsPartial({
+          counter: counterPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      try {
+        await increment();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      }
+
+      await program.methods
+        .setFrozen(counterId, false)
+        .accountsPartial({
+          counter: counterPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      await increment();
+    });
+
+    it("rejects updates to every counter while the program is paused", async () => {
+      await program.methods
+        .setPaused(true)
+        .accounts({
+          admin: authority.publicKey,
+        })
+        .rpc();
+
+      try {
+        await increment();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      } finally {
+        await program.methods
+          .setPaused(false)
+          .accounts({
+            admin: authority.publicKey,
+          })
+          .rpc();
+      }
+
+      await increment();
+    });
+
+    it("counts a left-out config as unpaused", async () => {
+      await program.methods
+        .increment(counterId)
+        .accountsPartial({
+          counter: counterPDA,
+          signer: authority.publicKey,
+          config: null,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .rpc();
+    });
+
+    it("only lets the admin pause", async () => {
+      const fakeAdmin = Keypair.generate();
+      try {
+        await program.methods
+          .setPaused(true)
+          .accounts({
+            admin: fakeAdmin.publicKey,
+          })
+          .signers([fakeAdmin])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("operators", () => {
+    const operatorsId = new anchor.BN(5);
+    const operatorsCounter = deriveCounterPDA(authority.publicKey, operatorsId);
+    const [operatorsPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("operators"), operatorsCounter.toBuffer()],
+      program.programId
+    );
+    const operator = Keypair.generate();
+
+    // Permission bits, matching Operator::INCREMENT / DECREMENT / SET
+    const INCREMENT = 1;
+    const SET = 4;
+
+    const updateAsOperator = (method: "increment" | "set") =>
+      (method === "increment"
+        ? program.methods.increment(operatorsId)
+        : program.methods.set(operatorsId, new anchor.BN(3))
+      )
+        .accountsPartial({
+          counter: operatorsCounter,
+          signer: operator.publicKey,
+          history: null,
+          operators: operatorsPDA,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .signers([operator])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(operatorsId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+
+      await program.methods
+        .initOperators(operatorsId)
+        .accountsPartial({
+          counter: operatorsCounter,
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("lets a listed operator update within its permissions", async () => {
+      await program.methods
+        .addOperator(operatorsId, operator.publicKey, INCREMENT)
+        .accountsPartial({
+          counter: operatorsCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      await updateAsOperator("increment");
+
+      const counterAccount = await program.account.counter.fetch(
+        operatorsCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(1);
+
+      try {
+        await updateAsOperator("set");
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("MissingPermission");
+      }
+    });
+
+    it("updates the permissions of a listed operator", async () => {
+      await program.methods
+        .addOperator(operatorsId, operator.publicKey, INCREMENT | SET)
+        .accountsPartial({
+          counter: operatorsCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      await updateAsOperator("set");
+
+      const operators = await program.account.operators.fetch(operatorsPDA);
+      expect(operators.operators).to.have.length(1);
+      expect(operators.operators[0].permissions).to.equal(INCREMENT | SET);
+      const counterAccount = await program.account.counter.fetch(
+        operatorsCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(3);
+    });
+
+    it("rejects an empty or unknown permission mask", async () => {
+      for (const permissions of [0, 8]) {
+        try {
+          await program.methods
+            .addOperator(operatorsId, operator.publicKey, permissions)
+            .accountsPartial({
+              counter: operatorsCounter,
+              authority: authority.publicKey,
+            })
+            .rpc();
+          expect.fail("Should have thrown an error");
+        } catch (error: any) {
+          expect(error.message).to.include("InvalidPermissions");
+        }
+      }
+    });
+
+    it("locks out a removed operator", async () => {
+      await program.methods
+        .removeOperator(operatorsId, operator.publicKey)
+        .accountsPartial({
+          counter: operatorsCounter,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      try {
+        await updateAsOperator("increment");
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+  });
+
+  describe("session keys", () => {
+    const sessionId = new anchor.BN(6);
+    const sessionCounter = deriveCounterPDA(authority.publicKey, sessionId);
+    const SESSION_KEYS_PROGRAM_ID = new anchor.web3.PublicKey(
+      "KeyspM2ssCJbqUhQ4k7sveSiY4WjnYsrXkC8oDbwde5"
+    );
+
+    // Issue a session token through the session-keys program's create_session
+    const createSession = async (
+      sessionSigner: Keypair,
+      targetProgram: anchor.web3.PublicKey,
+      validUntil: number
+    ) => {
+      const [sessionToken] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("session_token"),
+          targetProgram.toBuffer(),
+          sessionSigner.publicKey.toBuffer(),
+          authority.publicKey.toBuffer(),
+        ],
+        SESSION_KEYS_PROGRAM_ID
+      );
+      // create_session(top_up: None, valid_until: Some(validUntil), lamports: None)
+      const data = Buffer.concat([
+        Buffer.from([242, 193, 143, 179, 150, 25, 122, 227]),
+        Buffer.from([0, 1]),
+        new anchor.BN(validUntil).toArrayLike(Buffer, "le", 8),
+        Buffer.from([0]),
+      ]);
+      const ix = new anchor.web3.TransactionInstruction({
+        programId: SESSION_KEYS_PROGRAM_ID,
+        keys: [
+          { pubkey: sessionToken, isSigner: false, isWritable: true },
+          { pubkey: sessionSigner.publicKey, isSigner: true, isWritable: true },
+          { pubkey: authority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: targetProgram, isSigner: false, isWritable: false },
+          {
+            pubkey: anchor.web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        data,
+      });
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), [
+        sessionSigner,
+      ]);
+      return sessionToken;
+    };
+
+    const incrementWithSession = (
+      sessionSigner: Keypair,
+      sessionToken: anchor.web3.PublicKey
+    ) =>
+      program.methods
+        .increment(sessionId)
+        .accountsPartial({
+          counter: sessionCounter,
+          signer: sessionSigner.publicKey,
+          history: null,
+          operators: null,
+          sessionToken,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .signers([sessionSigner])
+        .rpc();
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    before(async () => {
+      await program.methods
+        .initialize(sessionId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("lets a session signer update in the authority's place", async () => {
+      const sessionSigner = Keypair.generate();
+      const sessionToken = await createSession(
+        sessionSigner,
+        program.programId,
+        now() + 60 * 60
+      );
+
+      await incrementWithSession(sessionSigner, sessionToken);
+
+      const counterAccount = await program.account.counter.fetch(
+        sessionCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(1);
+    });
+
+    it("rejects an expired session token", async () => {
+      const sessionSigner = Keypair.generate();
+      const sessionToken = await createSession(
+        sessionSigner,
+        program.programId,
+        now() - 60 * 60
+      );
+
+      try {
+        await incrementWithSession(sessionSigner, sessionToken);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidToken");
+      }
+    });
+
+    it("rejects a session token issued for another program", async () => {
+      const sessionSigner = Keypair.generate();
+      const sessionToken = await createSession(
+        sessionSigner,
+        anchor.web3.SystemProgram.programId,
+        now() + 60 * 60
+      );
+
+      try {
+        await incrementWithSession(sessionSigner, sessionToken);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidToken");
+      }
+    });
+  });
+
+  describe("multisig", () => {
+    const multisigId = new anchor.BN(8);
+    const multisigCounter = deriveCounterPDA(authority.publicKey, multisigId);
+    const [multisigPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig"), multisigCounter.toBuffer()],
+      program.programId
+    );
+    const deriveProposalPDA = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("proposal"),
+          multisigPDA.toBuffer(),
+          new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    // The wallet pays for proposals, the other signers only approve
+    const signerA = Keypair.generate();
+    const signerB = Keypair.generate();
+
+    const derivePDA = (seed: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(seed), multisigCounter.toBuffer()],
+        program.programId
+      )[0];
+
+    // Returns the index of the new proposal
+    const propose = async (action: any) => {
+      const { proposalCount } = await program.account.multisig.fetch(
+        multisigPDA
+      );
+      await program.methods
+        .createProposal(multisigId, action)
+        .accountsPartial({
+          counter: multisigCounter,
+          proposer: authority.publicKey,
+        })
+        .rpc();
+      return proposalCount.toNumber();
+    };
+
+    const approve = (index: number, member: Keypair) =>
+      program.methods
+        .approveProposal(multisigId, new anchor.BN(index))
+        .accountsPartial({
+          counter: multisigCounter,
+          member: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+
+    const execute = (index: number, accounts: any = {}) =>
+      program.methods
+        .executeProposal(multisigId, new anchor.BN(index))
+        .accountsPartial({
+          counter: multisigCounter,
+          proposer: authority.publicKey,
+          executor: authority.publicKey,
+          history: null,
+          operators: null,
+          treasury: null,
+          pendingSet: null,
+          recipient: null,
+          ...accounts,
+        })
+        .rpc();
+
+    // Approved by the wallet as proposer and signerA, meeting the threshold of 2
+    const carryOut = async (action: any, accounts: any = {}) => {
+      const index = await propose(action);
+      await approve(index, signerA);
+      await execute(index, accounts);
+    };
+
+    const update = (method: "increment" | "set", signer: Keypair | null) =>
+      (method === "increment"
+        ? program.methods.increment(multisigId)
+        : program.methods.set(multisigId, new anchor.BN(7))
+      )
+        .accountsPartial({
+          counter: multisigCounter,
+          signer: signer ? signer.publicKey : authority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: multisigPDA,
+          contribution: null,
+          treasury: null,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initialize(multisigId, ...defaultBounds, {
+          signers: [authority.publicKey, signerA.publicKey, signerB.publicKey],
+          threshold: 2,
+          updatePolicy: { anySigner: {} },
+        })
+        .accounts({
+          authority: authority.publicKey,
+          multisig: multisigPDA,
+        })
+        .rpc();
+      // signerA pays for the accounts it creates on the multisig's behalf
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: authority.publicKey,
+            toPubkey: signerA.publicKey,
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+          })
+        )
+      );
+    });
+
+    it("makes the multisig the counter's authority", async () => {
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.authority.toBase58()).to.equal(
+        multisigPDA.toBase58()
+      );
+      const multisig = await program.account.multisig.fetch(multisigPDA);
+      expect(multisig.config.threshold).to.equal(2);
+      expect(multisig.config.signers).to.have.length(3);
+    });
+
+    it("lets a single signer increment under the AnySigner policy", async () => {
+      await update("increment", signerA);
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(1);
+    });
+
+    it("requires a proposal to set", async () => {
+      try {
+        await update("set", null);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("MultisigProposalRequired");
+      }
+    });
+
+    it("sets the counter once the threshold of signers approved", async () => {
+      await propose({ set: { value: new anchor.BN(42) } });
+
+      try {
+        await execute(0);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ThresholdNotMet");
+      }
+
+      await approve(0, signerA);
+      await execute(0);
+
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(42);
+      // Executed proposals are closed
+      expect(
+        await program.account.proposal.fetchNullable(deriveProposalPDA(0))
+      ).to.be.null;
+    });
+
+    it("lets a signer create the history and records proposals in it", async () => {
+      const historyPDA = derivePDA("history");
+      await program.methods
+        .initHistory(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: signerA.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([signerA])
+        .rpc();
+
+      await carryOut(
+        { increment: { amount: new anchor.BN(3) } },
+        { history: historyPDA }
+      );
+
+      const history = await program.account.counterHistory.fetch(historyPDA);
+      expect(history.total.toNumber()).to.equal(1);
+      const [entry] = history.entries;
+      expect(entry.old.toNumber()).to.equal(42);
+      expect(entry.new.toNumber()).to.equal(45);
+      expect(entry.signer.toBase58()).to.equal(multisigPDA.toBase58());
+    });
+
+    it("rejects account creation by anyone outside the multisig", async () => {
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .initTreasury(multisigId)
+          .accountsPartial({
+            counter: multisigCounter,
+            authority: stranger.publicKey,
+            multisig: multisigPDA,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidAuth");
+      }
+    });
+
+    it("holds proposed count changes to the rate limit", async () => {
+      await carryOut({
+        setRateLimit: {
+          rateLimit: {
+            minSlots: new anchor.BN(0),
+            minSeconds: new anchor.BN(3600),
+            maxPerWindow: new anchor.BN(0),
+            windowSeconds: new anchor.BN(0),
+          },
+        },
+      });
+      const index = await propose({ increment: { amount: new anchor.BN(1) } });
+      await approve(index, signerA);
+      try {
+        await execute(index);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("RateLimited");
+      }
+
+      await carryOut({
+        setRateLimit: {
+          rateLimit: {
+            minSlots: new anchor.BN(0),
+            minSeconds: new anchor.BN(0),
+            maxPerWindow: new anchor.BN(0),
+            windowSeconds: new anchor.BN(0),
+          },
+        },
+      });
+      await execute(index);
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(46);
+    });
+
+    it("freezes the counter through a proposal", async () => {
+      await carryOut({ setFrozen: { frozen: true } });
+      const index = await propose({ increment: { amount: new anchor.BN(1) } });
+      await approve(index, signerA);
+      try {
+        await execute(index);
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("Paused");
+      }
+
+      await carryOut({ setFrozen: { frozen: false } });
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.frozen).to.equal(false);
+    });
+
+    it("adds operators through a proposal", async () => {
+      const operatorsPDA = derivePDA("operators");
+      const operator = Keypair.generate();
+      await program.methods
+        .initOperators(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: signerA.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([signerA])
+        .rpc();
+
+      try {
+        await carryOut({
+          addOperator: { operator: operator.publicKey, permissions: 1 },
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ProposalAccountsRequired");
+      }
+
+      await carryOut(
+        { addOperator: { operator: operator.publicKey, permissions: 1 } },
+        { operators: operatorsPDA }
+      );
+      const operators = await program.account.operators.fetch(operatorsPDA);
+      expect(operators.operators).to.have.length(1);
+      expect(operators.operators[0].key.toBase58()).to.equal(
+        operator.publicKey.toBase58()
+      );
+    });
+
+    it("charges proposed increments and withdraws the fees by proposal", async () => {
+      const treasuryPDA = derivePDA("treasury");
+      // Enough for the recipient to be rent-exempt on its own
+      const fee = anchor.web3.LAMPORTS_PER_SOL / 100;
+      const recipient = Keypair.generate().publicKey;
+      await program.methods
+        .initTreasury(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: signerA.publicKey,
+          multisig: multisigPDA,
+        })
+        .signers([signerA])
+        .rpc();
+      await carryOut({ setFee: { fee: new anchor.BN(fee) } });
+
+      await carryOut(
+        { increment: { amount: new anchor.BN(1) } },
+        { treasury: treasuryPDA }
+      );
+      const treasury = await program.account.treasury.fetch(treasuryPDA);
+      expect(treasury.collected.toNumber()).to.equal(fee);
+
+      await carryOut(
+        { withdraw: { amount: new anchor.BN(fee), recipient } },
+        { treasury: treasuryPDA, recipient }
+      );
+      expect(await provider.connection.getBalance(recipient)).to.equal(fee);
+
+      await carryOut({ setFee: { fee: new anchor.BN(0) } });
+    });
+
+    it("schedules and cancels sets through proposals", async () => {
+      const pendingSetPDA = derivePDA("pending_set");
+
+      try {
+        await carryOut({
+          scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(0) },
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("ProposalAccountsRequired");
+      }
+
+      await carryOut(
+        { scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(0) } },
+        { pendingSet: pendingSetPDA }
+      );
+      const pendingSet = await program.account.pendingSet.fetch(pendingSetPDA);
+      expect(pendingSet.value.toNumber()).to.equal(5);
+      // Its rent goes back to the multisig that scheduled it
+      expect(pendingSet.authority.toBase58()).to.equal(multisigPDA.toBase58());
+
+      await program.methods
+        .executeSet(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: multisigPDA,
+          executor: authority.publicKey,
+          history: null,
+          treasury: null,
+        })
+        .rpc();
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.count.toNumber()).to.equal(5);
+
+      await carryOut(
+        {
+          scheduleSet: {
+            value: new anchor.BN(6),
+            delay: new anchor.BN(60 * 60),
+          },
+        },
+        { pendingSet: pendingSetPDA }
+      );
+      await carryOut({ cancelSet: {} }, { pendingSet: pendingSetPDA });
+      expect(await program.account.pendingSet.fetchNullable(pendingSetPDA)).to
+        .be.null;
+    });
+
+    it("closes the counter and its multisig through a proposal", async () => {
+      const closeId = new anchor.BN(36);
+      const closeCounter = deriveCounterPDA(authority.publicKey, closeId);
+      const derive = (seed: string) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from(seed), closeCounter.toBuffer()],
+          program.programId
+        )[0];
+      const closeMultisig = derive("multisig");
+      const proposalPDA = (index: number) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("proposal"),
+            closeMultisig.toBuffer(),
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        )[0];
+      const recipient = Keypair.generate().publicKey;
+
+      const initialize = () =>
+        program.methods
+          .initialize(closeId, ...defaultBounds, {
+            signers: [authority.publicKey],
+            threshold: 1,
+            updatePolicy: { proposal: {} },
+          })
+          .accounts({
+            authority: authority.publicKey,
+            multisig: closeMultisig,
+          })
+          .rpc();
+      // The wallet is the only signer, its proposals are approved as they are created
+      const proposeAs = (action: any) =>
+        program.methods
+          .createProposal(closeId, action)
+          .accountsPartial({
+            counter: closeCounter,
+            proposer: authority.publicKey,
+          })
+          .rpc();
+      const executeAs = (index: number, accounts: any = {}) =>
+        program.methods
+          .executeProposal(closeId, new anchor.BN(index))
+          .accountsPartial({
+            counter: closeCounter,
+            proposer: authority.publicKey,
+            executor: authority.publicKey,
+            history: null,
+            operators: null,
+            treasury: null,
+            pendingSet: null,
+            recipient: null,
+            ...accounts,
+          });
+
+      await initialize();
+      await proposeAs({
+        scheduleSet: { value: new anchor.BN(5), delay: new anchor.BN(60 * 60) },
+      });
+      await executeAs(0, { pendingSet: derive("pending_set") }).rpc();
+      // Left open, to outlive the multisig
+      await proposeAs({ setFrozen: { frozen: true } });
+      await proposeAs({ close: { recipient } });
+
+      await executeAs(2, { recipient })
+        .remainingAccounts(
+          ["history", "operators", "treasury", "pending_set"].map((seed) => ({
+            pubkey: derive(seed),
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+
+      for (const closed of [closeCounter, closeMultisig, derive("pending_set")]) {
+        expect(await provider.connection.getAccountInfo(closed)).to.be.null;
+      }
+      expect(await provider.connection.getBalance(recipient)).to.be.greaterThan(0);
+
+      // The counter can be created again, without the proposals of the old multisig
+      await initialize();
+      try {
+        await executeAs(1).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("StaleProposal");
+      }
+      expect(
+        (await program.account.proposal.fetch(proposalPDA(1))).index.toNumber()
+      ).to.equal(1);
+    });
+
+    it("only counts approvals from the multisig's signers", async () => {
+      const newAuthority = Keypair.generate();
+      const index = await propose({
+        proposeAuthority: { newAuthority: newAuthority.publicKey },
+      });
+
+      try {
+        await approve(index, Keypair.generate());
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("NotMultisigSigner");
+      }
+
+      await approve(index, signerB);
+      await execute(index);
+
+      await program.methods
+        .acceptAuthority(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(
+        multisigCounter
+      );
+      expect(counterAccount.authority.toBase58()).to.equal(
+        newAuthority.publicKey.toBase58()
+      );
+
+      // The multisig no longer holds the counter, but is closed along with it
+      await program.methods
+        .close(multisigId)
+        .accountsPartial({
+          counter: multisigCounter,
+          authority: newAuthority.publicKey,
+          recipient: authority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(multisigPDA)).to.be.null;
+    });
+
+    it("rejects a threshold above the number of signers", async () => {
+      try {
+        const id = new anchor.BN(9);
+        const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("multisig"), deriveCounterPDA(authority.publicKey, id).toBuffer()],
+          program.programId
+        );
+        await program.methods
+          .initialize(id, ...defaultBounds, {
+            signers: [authority.publicKey],
+            threshold: 2,
+            updatePolicy: { proposal: {} },
+          })
+          .accounts({
+            authority: authority.publicKey,
+            multisig: pda,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidMultisig");
+      }
+    });
+  });
+
+  describe("authority transfer", () => {
+    const transferId = new anchor.BN(3);
+    const transferPDA = deriveCounterPDA(authority.publicKey, transferId);
+    const newAuthority = Keypair.generate();
+
+    before(async () => {
+      await program.methods
+        .initialize(transferId, ...defaultBounds, null)
+        .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+        .rpc();
+    });
+
+    it("rejects accept from a key that was not proposed", async () => {
+      await program.methods
+        .proposeAuthority(transferId, newAuthority.publicKey)
+        .accountsPartial({
+          counter: transferPDA,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+      const stranger = Keypair.generate();
+      try {
+        await program.methods
+          .acceptAuthority(transferId)
+          .accountsPartial({
+            counter: transferPDA,
+            newAuthority: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error: any) {
+        expect(error.message).to.include("NotPendingAuthority");
+      }
+    });
+
+    it("hands over the counter while keeping the PDA address", async () => {
+      await program.methods
+        .acceptAuthority(transferId)
+        .accountsPartial({
+          counter: transferPDA,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+      const counterAccount = await program.account.counter.fetch(transferPDA);
+      expect(counterAccount.authority.toBase58()).to.equal(
+        newAuthority.publicKey.toBase58()
+      );
+      expect(counterAccount.seedAuthority.toBase58()).to.equal(
+        authority.publicKey.toBase58()
+      );
+
+      // The new authority can update the counter at the same address
+      await program.methods
+        .set(transferId, new anchor.BN(5))
+        .accountsPartial({
+          counter: transferPDA,
+          signer: newAuthority.publicKey,
+          history: null,
+          operators: null,
+          sessionToken: null,
+          multisig: null,
+          contribution: null,
+          treasury: null,
+        })
+        .signers([newAuthority])
+        .rpc();
+      expect(
+        (await program.account.counter.fetch(transferPDA)).count.toNumber()
+      ).to.equal(5);
+    });
+
+    it("locks out the previous authority", async () => {
+      try {
+        await program.methods
+          .increment(transferId)
+          .accountsPartial({
+            counter: transferPDA,
+            signer: authority.publicKey,
+            history: null,
+            operators: null,
+            sessionToken: null,
+            multisig: null,
+            contribution: null,
+            treasury: null,
           })
-          .signers([fakeAuthority])
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (error: any) {
-        // The error should be that the account doesn't exist
-        expect(error.message).to.include("AccountNotInitialized");
+        expect(error.message).to.include("InvalidAuth");
       }
     });
   });
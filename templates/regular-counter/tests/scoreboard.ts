@@ -0,0 +1,170 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import { Counter } from "../target/types/counter";
+import { Scoreboard } from "../target/types/scoreboard";
+
+describe("scoreboard", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const counterProgram = anchor.workspace.counter as Program<Counter>;
+  const program = anchor.workspace.scoreboard as Program<Scoreboard>;
+  const authority = provider.wallet;
+
+  const counterId = new anchor.BN(30);
+  const defaultBounds: [anchor.BN, anchor.BN, { wrap: {} }] = [
+    new anchor.BN(0),
+    new anchor.BN(1000),
+    { wrap: {} },
+  ];
+
+  const [counterPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [authority.publicKey.toBuffer(), counterId.toArrayLike(Buffer, "le", 8)],
+    counterProgram.programId
+  );
+
+  // The scoreboard is this program's caller signer PDA for the counter
+  const [scoreboardPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("counter_signer"), counterPDA.toBuffer()],
+    program.programId
+  );
+
+  const [configPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    counterProgram.programId
+  );
+
+  const awardPoint = (admin = authority.publicKey, signers: Keypair[] = []) =>
+    program.methods
+      .awardPoint(counterId)
+      .accountsPartial({
+        scoreboard: scoreboardPDA,
+        admin,
+        counter: counterPDA,
+      })
+      .signers(signers)
+      .rpc();
+
+  const setCallerProgram = (callerProgram: anchor.web3.PublicKey | null) =>
+    counterProgram.methods
+      .setCallerProgram(counterId, callerProgram)
+      .accountsPartial({
+        counter: counterPDA,
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+  before(async () => {
+    const config = await counterProgram.account.programConfig.fetchNullable(
+      configPDA
+    );
+    if (!config) {
+      await counterProgram.methods
+        .initConfig(authority.publicKey)
+        .accounts({
+          payer: authority.publicKey,
+        })
+        .rpc();
+    }
+
+    await counterProgram.methods
+      .initialize(counterId, ...defaultBounds, null)
+      .accounts({
+          authority: authority.publicKey,
+          multisig: null,
+        })
+      .rpc();
+    await program.methods
+      .initScoreboard()
+      .accounts({
+        counter: counterPDA,
+        admin: authority.publicKey,
+      })
+      .rpc();
+  });
+
+  it("only lets the counter's authority create its scoreboard", async () => {
+    const otherId = new anchor.BN(33);
+    const [otherPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [authority.publicKey.toBuffer(), otherId.toArrayLike(Buffer, "le", 8)],
+      counterProgram.programId
+    );
+    await counterProgram.methods
+      .initialize(otherId, ...defaultBounds, null)
+      .accounts({
+        authority: authority.publicKey,
+        multisig: null,
+      })
+      .rpc();
+    const stranger = Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: authority.publicKey,
+          toPubkey: stranger.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+        })
+      )
+    );
+    try {
+      await program.methods
+        .initScoreboard()
+        .accounts({
+          counter: otherPDA,
+          admin: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("InvalidAuthority");
+    }
+  });
+
+  it("can't increment until the counter allows the program", async () => {
+    try {
+      await awardPoint();
+      expect.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("InvalidAuth");
+    }
+  });
+
+  it("increments the counter with the scoreboard PDA as signer", async () => {
+    await setCallerProgram(program.programId);
+    await awardPoint();
+    await awardPoint();
+
+    const counter = await counterProgram.account.counter.fetch(counterPDA);
+    expect(counter.count.toNumber()).to.equal(2);
+    expect(counter.callerProgram.toBase58()).to.equal(
+      program.programId.toBase58()
+    );
+    const scoreboard = await program.account.scoreboard.fetch(scoreboardPDA);
+    expect(scoreboard.points.toNumber()).to.equal(2);
+  });
+
+  it("only lets the scoreboard's admin award points", async () => {
+    const stranger = Keypair.generate();
+    try {
+      await awardPoint(stranger.publicKey, [stranger]);
+      expect.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("InvalidAdmin");
+    }
+  });
+
+  it("stops accepting the PDA once the program is revoked", async () => {
+    await setCallerProgram(null);
+    try {
+      await awardPoint();
+      expect.fail("Should have thrown an error");
+    } catch (error: any) {
+      expect(error.message).to.include("InvalidAuth");
+    }
+    const counter = await counterProgram.account.counter.fetch(counterPDA);
+    expect(counter.count.toNumber()).to.equal(2);
+  });
+});